@@ -1,61 +1,117 @@
 use json::JsonValue;
 
-use crate::analysis::components::{JsonComponent, JsonType, Record, Key, Value, Array, Matcher, Generatable};
+use crate::analysis::{components::{JsonComponent, JsonType, Record, Key, Value, Array, Matcher, EnumEncoder, Filter, FilterPredicate, Generatable, JsonComponentValue, StringMode, WidthConverter, Variant, VariantKind}, types::{TilSignal, til_streamlet::TilInstance}};
 
-use super::Analyzer;
+use super::{Analyzer, identifier, samples};
 
 impl Analyzer {
     // Analyze a record of the JSON object
     // Which results in the creation of 3 components:
     // A matcher, a key and a record
-    pub fn analyze_record(&mut self, key: &str, element: &JsonValue, outer_nesting: usize, inner_nesting: usize) -> (Option<Key>, usize) {
-        let (child, new_inner_nesting) = self.analyze_element(element, outer_nesting + 1, inner_nesting);
+    pub fn analyze_record(&mut self, key: &str, element: &JsonValue, path: &str, epc: usize, outer_nesting: usize, inner_nesting: usize) -> (Option<Key>, usize) {
+        // Path of the value of the record
+        let path = if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
 
-        let key_name = self.name_reg.register("key_parser", outer_nesting + 2);
-        let matcher_name = self.name_reg.register(&format!("{}_matcher", key), outer_nesting + 2);
+        let (child, new_inner_nesting) = self.analyze_element(element, &path, epc, outer_nesting + 1, inner_nesting);
+
+        let key_name = self.name_reg.register("key_parser", &path, outer_nesting + 2);
+        let matcher_name = self.name_reg.register_matcher(key, &path, outer_nesting + 2);
 
         // Create a components
-        let matcher = Matcher::new(&matcher_name, &key_name, key.to_string(), outer_nesting + 2);
-        let key = Key::new(&key_name, matcher.clone(), outer_nesting + 2, child.map(Box::new));
-        
-        // Convert to TilComponent
-        let matcher_component = matcher.to_til_streamlet(&self.gen_params);
-        let key_component = key.to_til_streamlet(&self.gen_params);
+        // The key filters the record stream so it runs at the EPC of the record
+        let matcher = Matcher::new(&matcher_name, &path, epc, &key_name, key.to_string(), outer_nesting + 2);
+        let optional = self.optional_paths.contains(&path);
+        let key = Key::new(&key_name, &path, epc, matcher.clone(), outer_nesting + 2, optional, child.map(Box::new));
+        let key = self.attach_presence_bit(key);
 
         // Add components to entity list
-        self.entity_list.push(matcher_component);
-        self.entity_list.push(key_component);
+        self.add_component(&matcher);
+        self.add_component(&key);
 
         // Register types
-        self.type_manager.register_from_component(&matcher);
-        self.type_manager.register_from_component(&key);
+        self.type_manager.register_from_component(&matcher, &self.gen_params);
+        self.type_manager.register_from_component(&key, &self.gen_params);
 
         // Add signals to signal list
-        self.signal_manager.add_multiple_signals(matcher.get_outgoing_signals());
-        self.signal_manager.add_multiple_signals(key.get_outgoing_signals());
-
-        // Add entity to file manager
-        self.file_manager.add_entity(matcher.get_file_type(), matcher.get_name());
-        self.file_manager.add_entity(key.get_file_type(), key.get_name());
-
+        self.signal_manager.add_multiple_signals(matcher.get_outgoing_signals(&self.gen_params));
+        self.signal_manager.add_multiple_signals(key.get_outgoing_signals(&self.gen_params));
 
         // Return the key and keep the same inner nesting level
         (Some(key), new_inner_nesting)  
     }
 
+    // Analyze an object which is used as a map
+    // A single key with a matcher for the pattern handles all keys and emits their names,
+    // the values of all keys are merged into one
+    pub fn analyze_map(&mut self, pattern: &str, element: &JsonValue, path: &str, epc: usize, outer_nesting: usize, inner_nesting: usize) -> (Option<Key>, usize) {
+        let values: Vec<JsonValue> = element.entries().map(|(_, value)| value.clone()).collect();
+        let key_length = self.key_lengths.get(path).copied().unwrap_or(0).max(1);
+
+        // Path of the values of the map
+        let path = if path.is_empty() { "*".to_string() } else { format!("{}.*", path) };
+
+        let (child, new_inner_nesting) = self.analyze_element(&samples::merge_samples(&values), &path, epc, outer_nesting + 1, inner_nesting);
+
+        let key_name = self.name_reg.register("key_parser", &path, outer_nesting + 2);
+        let matcher_name = self.name_reg.register_matcher("map_key", &path, outer_nesting + 2);
+
+        // Create a components
+        // Keys of a map come and go, so the key is always optional
+        let matcher = Matcher::new(&matcher_name, &path, epc, &key_name, pattern.to_string(), outer_nesting + 2);
+        let key = Key::new(&key_name, &path, epc, matcher.clone(), outer_nesting + 2, true, child.map(Box::new)).with_key_name(key_length);
+        let key = self.attach_presence_bit(key);
+
+        // Add components to entity list
+        self.add_component(&matcher);
+        self.add_component(&key);
+
+        // Register types
+        self.type_manager.register_from_component(&matcher, &self.gen_params);
+        self.type_manager.register_from_component(&key, &self.gen_params);
+
+        // Add signals to signal list
+        self.signal_manager.add_multiple_signals(matcher.get_outgoing_signals(&self.gen_params));
+        self.signal_manager.add_multiple_signals(key.get_outgoing_signals(&self.gen_params));
+
+        (Some(key), new_inner_nesting)
+    }
+
     // Analyze the element and recursively call itself if it is an object or array to find nested elements
-    pub fn analyze_element(&mut self, element: &JsonValue, outer_nesting: usize, inner_nesting: usize) -> (Option<JsonComponent>, usize) {
+    pub fn analyze_element(&mut self, element: &JsonValue, path: &str, parent_epc: usize, outer_nesting: usize, inner_nesting: usize) -> (Option<JsonComponent>, usize) {
+        let epc = self.resolve_epc(path, parent_epc);
+
         let (component, new_inner_nesting) = match element {
+            // Values of different kinds at the path, every kind has its own parser
+            _ if self.value_kinds.get(path).is_some_and(|kinds| kinds.len() > 1) => {
+                let kinds = self.value_kinds.remove(path).unwrap();
+                self.analyze_variant(kinds, path, epc, outer_nesting, inner_nesting)
+            },
+            // String with a known set of values
+            JsonValue::Short(_) | JsonValue::String(_) if self.get_enum_values(path).is_some() => {
+                let encoder_name = self.name_reg.register("enum_encoder", path, outer_nesting);
+                let matcher_name = self.name_reg.register("enum_matcher", path, outer_nesting + 1);
+                let encoder = EnumEncoder::new(&encoder_name, path, epc, &matcher_name, self.get_enum_values(path).unwrap(), outer_nesting);
+
+                let matcher = encoder.get_matcher().clone();
+                self.add_component(&matcher);
+                self.type_manager.register_from_component(&matcher, &self.gen_params);
+                self.signal_manager.add_multiple_signals(matcher.get_outgoing_signals(&self.gen_params));
+
+                // Types don't increase the nesting level
+                (Some(JsonComponent::Enum(encoder)), inner_nesting)
+            },
             // Element has string type
             JsonValue::Short(_) | JsonValue::String(_) => 
                 (
                     Some(
                         JsonComponent::Value(
                             Value::new(
-                                &self.name_reg.register("string_parser", outer_nesting),
+                                &self.name_reg.register("string_parser", path, outer_nesting),
+                                path,
+                                epc,
                                 JsonType::String,
                                 outer_nesting, // Strings don't increase the nesting level since the input is a string
-                            )
+                            ).with_string_mode(self.gen_params.get_string_mode(path))
                         )
                     ), 
                     // Types don't increase the nesting level
@@ -67,7 +123,9 @@ impl Analyzer {
                     Some(
                         JsonComponent::Value(
                             Value::new(
-                                &self.name_reg.register("int_parser", outer_nesting + 1),
+                                &self.name_reg.register("int_parser", path, outer_nesting + 1),
+                                path,
+                                epc,
                                 JsonType::Integer,
                                 outer_nesting + 1,
                             )
@@ -82,7 +140,9 @@ impl Analyzer {
                     Some(
                         JsonComponent::Value(
                             Value::new(
-                                &self.name_reg.register("bool_parser", outer_nesting + 1),
+                                &self.name_reg.register("bool_parser", path, outer_nesting + 1),
+                                path,
+                                epc,
                                 JsonType::Boolean,
                                 outer_nesting + 1,
                             )
@@ -100,33 +160,71 @@ impl Analyzer {
 
                 // Get the first element of the array to determine the type of the array
                 let child_element = &arr[0];
-                let (child, new_inner_nesting) = self.analyze_element(child_element, outer_nesting + 1, inner_nesting);
+                let (child, new_inner_nesting) = self.analyze_element(child_element, &format!("{}[]", path), epc, outer_nesting + 1, inner_nesting);
+
+                let array = Array::new(
+                    &self.name_reg.register("array_parser", path, outer_nesting + 1),
+                    path,
+                    epc,
+                    outer_nesting + 1,
+                    new_inner_nesting,
+                    child.map(Box::new)
+                ).with_counters(self.gen_params.array_counters.get(path).copied().unwrap_or_default(), self.get_counter_width(path));
+
+                // The slicer drops elements between the array and its elements
+                let array = match self.gen_params.array_slices.get(path) {
+                    Some(slice) => {
+                        let array = array.with_slice(&self.name_reg.register("array_slicer", path, outer_nesting + 1), *slice);
+
+                        let slicer = array.get_slicer().unwrap();
+                        self.add_component(slicer);
+                        self.type_manager.register_from_component(slicer, &self.gen_params);
+                        self.signal_manager.add_multiple_signals(slicer.get_outgoing_signals(&self.gen_params));
+
+                        array
+                    },
+                    None => array,
+                };
 
                 // Return the array with the child element
                 (
-                    Some(
-                        JsonComponent::Array(
-                            Array::new(
-                                &self.name_reg.register("array_parser", outer_nesting + 1),
-                                outer_nesting + 1,
-                                new_inner_nesting,
-                                child.map(Box::new)
-                            )
-                        )
-                    ),
+                    Some(JsonComponent::Array(array)),
                     // An array increases the inner nesting by 1
                     new_inner_nesting + 1
                 )
             },
+            // Element is an object used as a map
+            JsonValue::Object(_) if self.gen_params.map_patterns.contains_key(path) => {
+                let pattern = self.gen_params.map_patterns[path].clone();
+                let presence_name = self.register_presence(element, path, outer_nesting);
+                let (key, new_inner_nesting) = self.analyze_map(&pattern, element, path, epc, outer_nesting, inner_nesting);
+
+                let record = Record::new(
+                    &self.name_reg.register("record_parser", path, outer_nesting + 1),
+                    path,
+                    epc,
+                    outer_nesting + 1,
+                    new_inner_nesting,
+                    key.into_iter().collect()
+                );
+
+                (
+                    Some(JsonComponent::Record(self.attach_presence(record, presence_name))),
+                    // An object increases the inner nesting by 1
+                    new_inner_nesting + 1
+                )
+            },
             // Element is an object
             JsonValue::Object(_) => {
                 let mut children: Vec<Key> = Vec::new();
                 let mut new_inner_nesting = Vec::new();
 
+                let presence_name = self.register_presence(element, path, outer_nesting);
+
                 // Analyze all the records of the object
                 for key in element.entries() {
                     // Analyze the record
-                    let (child, ret_inner_nesting) = self.analyze_record(key.0, key.1, outer_nesting, inner_nesting);
+                    let (child, ret_inner_nesting) = self.analyze_record(key.0, key.1, path, epc, outer_nesting, inner_nesting);
                     
                     // Push record if it is not None
                     if let Some(key) = child {
@@ -140,18 +238,18 @@ impl Analyzer {
                 // Take the maximum inner nesting of the object's records
                 let max_inner_nesting = *(new_inner_nesting.iter().max().unwrap());
 
+                let record = Record::new(
+                    &self.name_reg.register("record_parser", path, outer_nesting + 1), 
+                    path,
+                    epc,
+                    outer_nesting + 1, 
+                    max_inner_nesting, 
+                    children
+                );
+
                 // Return the object with the children
                 (
-                    Some(
-                        JsonComponent::Record(
-                            Record::new(
-                                &self.name_reg.register("record_parser", outer_nesting + 1), 
-                                outer_nesting + 1, 
-                                max_inner_nesting, 
-                                children
-                            )
-                        )
-                    ),
+                    Some(JsonComponent::Record(self.attach_presence(record, presence_name))),
                     // An object increases the inner nesting by 1
                     max_inner_nesting + 1
                 )
@@ -159,28 +257,327 @@ impl Analyzer {
             JsonValue::Null => (None, inner_nesting),
         };
 
+        // A filter on the value takes over its output, a record can join the values of its keys
+        let component = match component {
+            Some(JsonComponent::Value(value)) => Some(JsonComponent::Value(self.attach_filter(value))),
+            Some(JsonComponent::Record(record)) => Some(JsonComponent::Record(self.attach_assembler(record))),
+            component => component,
+        };
+
         // Check if there is a component
         if let Some(component) = &component {
             // Check if the component is generatable
             let gen_component = component.get_generatable();
 
-            // Convert to TilStreamlet
-            let til_streamlet = gen_component.to_til_streamlet(&self.gen_params);
-
-            // Add components to entity list
-            self.entity_list.push(til_streamlet);
+            // Add component to entity list
+            self.add_component(gen_component);
 
             // Register types
-            self.type_manager.register_from_component(gen_component);
+            self.type_manager.register_from_component(gen_component, &self.gen_params);
 
             // Add signals to signal list
-            self.signal_manager.add_multiple_signals(gen_component.get_outgoing_signals());
-
-            // Add entity to file manager
-            self.file_manager.add_entity(gen_component.get_file_type(), gen_component.get_name());
+            self.signal_manager.add_multiple_signals(gen_component.get_outgoing_signals(&self.gen_params));
         }
 
         // Return the component and the new inner nesting level
         (component, new_inner_nesting)
     }
-}
\ No newline at end of file
+
+    // Analyze the values of every kind at the path, a variant sends every value to the parser of its kind
+    // and emits the kind of every value together with the integers and booleans
+    fn analyze_variant(&mut self, kinds: Vec<(VariantKind, JsonValue)>, path: &str, epc: usize, outer_nesting: usize, inner_nesting: usize) -> (Option<JsonComponent>, usize) {
+        let name = self.name_reg.register("variant", path, outer_nesting + 1);
+
+        let mut branches = Vec::new();
+        let mut new_inner_nesting = inner_nesting;
+        for (kind, sample) in kinds {
+            let (child, ret_inner_nesting) = self.analyze_element(&sample, path, epc, outer_nesting, inner_nesting);
+
+            if let Some(child) = child {
+                branches.push((kind, child));
+            }
+            new_inner_nesting = new_inner_nesting.max(ret_inner_nesting);
+        }
+
+        // The values have the same nesting level as an integer at the path
+        let variant = Variant::new(&name, path, epc, branches, outer_nesting + 1, &format!("output_{}_inst", name));
+
+        // The integers and booleans were analyzed with a top-level output, they go to the variant now
+        for (kind, _) in variant.get_branches() {
+            if let Some(value) = variant.get_routed_value(*kind) {
+                self.signal_manager.remove_output_signals(&value.get_instance_name());
+                self.signal_manager.add_multiple_signals(value.get_outgoing_signals(&self.gen_params));
+            }
+        }
+
+        (Some(JsonComponent::Variant(variant)), new_inner_nesting)
+    }
+
+    // Add an instance of the component, components with the same interface and VHDL share one entity
+    // which is parameterized by the generics of the instance
+    fn add_component(&mut self, component: &dyn Generatable) {
+        let streamlet = component.to_til_streamlet(&self.gen_params);
+        let template_type = component.get_file_type();
+        let context = component.get_template_context(&self.gen_params);
+
+        // Interface without the generic values, which are set by the instances
+        let mut signature = format!("{:?}\n", template_type);
+        for generic in streamlet.get_streams().get_generics() {
+            signature.push_str(&format!("{}: {}\n", generic.get_name(), generic.get_type().get_type_name()));
+        }
+        for stream in streamlet.get_streams().get_streams() {
+            signature.push_str(&format!("{}\n", stream));
+        }
+
+        // Templates can use the values of the component, so only identical VHDL is shared.
+        // A template which cannot be rendered is reported when the files are generated.
+        let mut entity_context = context.clone();
+        entity_context.insert("comp_name", "");
+        if let Ok(Some(text)) = self.file_manager.render_entity(&template_type, &entity_context, &self.gen_params) {
+            signature.push_str(&text);
+        }
+
+        let entity_name = match self.shared_entities.get(&signature) {
+            Some(entity_name) => entity_name.clone(),
+            None => {
+                let entity_name = self.name_reg.register_entity(&template_type.get_entity_kind(), &signature);
+
+                // The generics of the first instance are the defaults of the entity
+                let mut entity = streamlet.clone();
+                entity.set_name(&entity_name);
+                self.entity_list.push(entity);
+
+                self.file_manager.add_entity(template_type, &entity_name, context);
+                self.shared_entities.insert(signature, entity_name.clone());
+
+                entity_name
+            }
+        };
+
+        self.instances.push(TilInstance::new(&entity_name, &component.get_instance_name(), streamlet.get_streams().get_generics().clone()));
+    }
+
+    // Pass the value through the filter on its path, if the predicate can be tested on it
+    fn attach_filter(&mut self, value: Value) -> Value {
+        let (filter_name, predicate) = match self.gen_params.filters.get(value.get_path()) {
+            Some(filter) => filter.clone(),
+            None => return value,
+        };
+
+        // Regexes are matched on the raw characters of a string
+        if predicate.get_data_type() != *value.get_data_type() || value.get_string_mode() != StringMode::Raw {
+            return value;
+        }
+
+        let path = value.get_path().to_string();
+        let nesting = value.get_nesting_level();
+        let name = self.name_reg.register("filter", &path, nesting);
+        let matcher_name = match predicate {
+            FilterPredicate::Regex(_) => self.name_reg.register("filter_matcher", &path, nesting + 1),
+            _ => String::new(),
+        };
+
+        let filter = Filter::new(&name, &path, &identifier::sanitize(&filter_name), predicate, value.clone(), &matcher_name);
+
+        if let Some(matcher) = filter.get_matcher() {
+            self.add_component(matcher);
+            self.type_manager.register_from_component(matcher, &self.gen_params);
+            self.signal_manager.add_multiple_signals(matcher.get_outgoing_signals(&self.gen_params));
+        }
+
+        self.add_component(&filter);
+        self.type_manager.register_from_component(&filter, &self.gen_params);
+        self.signal_manager.add_multiple_signals(filter.get_outgoing_signals(&self.gen_params));
+
+        value.with_filter(filter)
+    }
+
+    // Name the key presence component of the object if it is enabled for its path, the keys are created
+    // after it so they can send their match results to it
+    fn register_presence(&mut self, element: &JsonValue, path: &str, outer_nesting: usize) -> Option<String> {
+        if !self.gen_params.key_presence.contains(path) || element.is_empty() {
+            return None;
+        }
+
+        let name = self.name_reg.register("key_presence", path, outer_nesting + 2);
+        let inst_name = format!("{}_inst", name);
+
+        // A map has a single key for all its keys
+        let key_paths: Vec<String> = match self.gen_params.map_patterns.contains_key(path) {
+            true => vec![if path.is_empty() { "*".to_string() } else { format!("{}.*", path) }],
+            false => element.entries().map(|(key, _)| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }).collect(),
+        };
+        for (bit, key_path) in key_paths.into_iter().enumerate() {
+            self.presence_bits.insert(key_path, (inst_name.clone(), bit));
+        }
+
+        Some(name)
+    }
+
+    // Send the match results of the key to the key presence component of its record if there is one
+    fn attach_presence_bit(&mut self, key: Key) -> Key {
+        match self.presence_bits.remove(key.get_path()) {
+            Some((presence_inst_name, bit)) => key.with_presence(&presence_inst_name, bit),
+            None => key,
+        }
+    }
+
+    // Add the key presence output to the record if its keys send their match results to it
+    fn attach_presence(&mut self, record: Record, presence_name: Option<String>) -> Record {
+        let name = match presence_name {
+            Some(name) => name,
+            None => return record,
+        };
+
+        let record = record.with_presence(&name);
+        let presence = record.get_presence().unwrap();
+
+        self.add_component(presence);
+        self.type_manager.register_from_component(presence, &self.gen_params);
+        self.signal_manager.add_multiple_signals(presence.get_outgoing_signals(&self.gen_params));
+
+        record
+    }
+
+    // Join the values of the keys of the record configured for its path into one element per record
+    fn attach_assembler(&mut self, record: Record) -> Record {
+        let keys = match self.gen_params.record_assemblies.get(record.get_path()) {
+            Some(keys) => keys.clone(),
+            None => return record,
+        };
+
+        let path = record.get_path().to_string();
+        let nesting = record.get_nesting_level() + 1;
+        let name = self.name_reg.register("record_assembler", &path, nesting);
+
+        let fields: Vec<(String, String)> = keys.iter().map(|key| (key.clone(), identifier::sanitize(key))).collect();
+        let record = record.with_assembler(&name, &fields);
+        let assembler = match record.get_assembler() {
+            Some(assembler) => assembler,
+            None => return record,
+        };
+
+        // The values were analyzed with a top-level output, they go to the assembler now
+        for (_, _, value) in assembler.get_fields() {
+            self.signal_manager.remove_output_signals(&value.get_instance_name());
+            self.signal_manager.add_multiple_signals(value.get_outgoing_signals(&self.gen_params));
+        }
+
+        self.add_component(assembler);
+        self.type_manager.register_from_component(assembler, &self.gen_params);
+        self.signal_manager.add_multiple_signals(assembler.get_outgoing_signals(&self.gen_params));
+
+        record
+    }
+
+    // Width of the element counter of the arrays at a JSON path, set explicitly or large enough for the longest array in the samples
+    fn get_counter_width(&self, path: &str) -> usize {
+        if let Some(width) = self.gen_params.array_counters.get(path).and_then(|counters| counters.width) {
+            return width;
+        }
+
+        let max_length = self.array_lengths.get(path).copied().unwrap_or(0);
+        let mut width = 1;
+        while (1 << width) <= max_length {
+            width += 1;
+        }
+
+        width
+    }
+
+    // Values of the string at a JSON path if it is encoded as an enum, set explicitly or a small set seen in the samples
+    fn get_enum_values(&self, path: &str) -> Option<Vec<String>> {
+        if let Some(values) = self.gen_params.enum_values.get(path) {
+            return Some(values.clone());
+        }
+
+        let max_values = self.gen_params.enum_max_values;
+        match self.string_values.get(path) {
+            // Without repetitions the samples don't show that the set is closed
+            Some((values, count)) if values.len() <= max_values && *count >= 2 * values.len() => Some(values.clone()),
+            _ => None,
+        }
+    }
+
+    // EPC of the components at a JSON path, set explicitly, derived from the expected size of
+    // the field or inherited from the parent. The root always runs at the EPC of the input.
+    fn resolve_epc(&self, path: &str, parent_epc: usize) -> usize {
+        if path.is_empty() {
+            return parent_epc;
+        }
+
+        if let Some(epc) = self.gen_params.epc_overrides.get(path) {
+            return *epc;
+        }
+
+        if let Some(field_size) = self.gen_params.field_sizes.get(path) {
+            if self.document_size > 0 {
+                // Share of the input throughput the field needs
+                let share = (self.gen_params.epc * field_size).div_ceil(self.document_size);
+                return share.next_power_of_two().min(parent_epc);
+            }
+        }
+
+        parent_epc
+    }
+
+    // Insert width converters between components which run at a different EPC
+    pub fn insert_width_converters(&mut self) {
+        let root = match &self.top_component {
+            Some(root) => root.clone(),
+            None => return,
+        };
+
+        let mut signals = Vec::new();
+
+        for signal in self.signal_manager.take_intermediate_signals() {
+            let (source, dest) = match &signal {
+                TilSignal::Intermediate { source_inst_name, dest_inst_name, .. } => (find_component(&root, source_inst_name), find_component(&root, dest_inst_name)),
+                _ => (None, None),
+            };
+
+            let (source, dest) = match (source, dest) {
+                (Some(source), Some(dest)) if source.get_generatable().get_epc() != dest.get_generatable().get_epc() => (source, dest),
+                _ => {
+                    signals.push(signal);
+                    continue;
+                }
+            };
+
+            let source = source.get_generatable();
+            let dest = dest.get_generatable();
+
+            // The converter takes the dimensionality of the input of the destination
+            let nesting = dest.get_streaming_interface(&self.gen_params).get_input_streams().iter()
+                .find_map(|stream| stream.get_type().get_stream_dim().as_ref().map(|dim| dim.get_true_value()))
+                .unwrap_or(1);
+
+            let converter = WidthConverter::new(
+                &self.name_reg.register("width_converter", dest.get_path(), nesting),
+                dest.get_path(),
+                source.get_epc(),
+                dest.get_epc(),
+                nesting,
+            );
+
+            self.add_component(&converter);
+            self.type_manager.register_from_component(&converter, &self.gen_params);
+
+            if let TilSignal::Intermediate { source_inst_name, source_stream_name, dest_inst_name, dest_stream_name } = signal {
+                signals.push(TilSignal::Intermediate { source_inst_name, source_stream_name, dest_inst_name: converter.get_instance_name(), dest_stream_name: "input".to_owned() });
+                signals.push(TilSignal::Intermediate { source_inst_name: converter.get_instance_name(), source_stream_name: "output".to_owned(), dest_inst_name, dest_stream_name });
+            }
+        }
+
+        self.signal_manager.add_multiple_signals(signals);
+    }
+}
+
+// Find a component in the tree by its instance name
+fn find_component(component: &JsonComponent, instance_name: &str) -> Option<JsonComponent> {
+    if component.get_generatable().get_instance_name() == instance_name {
+        return Some(component.clone());
+    }
+
+    component.get_children().iter().find_map(|child| find_component(child, instance_name))
+}
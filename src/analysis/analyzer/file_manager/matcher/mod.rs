@@ -1,7 +1,7 @@
 use crate::analysis::analyzer::AnalyzerError;
 
-pub fn generate_matcher(matcher: &str, comp_name: &str, project_name: &str) -> Result<String, AnalyzerError> {
-    use pyo3::prelude::*;
+pub fn generate_matcher(matchers: &[String], comp_name: &str, project_name: &str) -> Result<String, AnalyzerError> {
+    use pyo3::{prelude::*, types::PyTuple};
     
     let code = include_str!("./vhdre/vhdre/__init__.py");
 
@@ -10,10 +10,25 @@ pub fn generate_matcher(matcher: &str, comp_name: &str, project_name: &str) -> R
         let vhdre = PyModule::from_code(py, code, "vhdre/__init__.py", "vhdre")?;
 
         let regex_class = vhdre.getattr("RegexMatcher")?;
-        let regex = regex_class.call1((comp_name, project_name, matcher))?;
+        // One match output for every regex
+        let mut args = vec![comp_name, project_name];
+        args.extend(matchers.iter().map(|matcher| matcher.as_str()));
+
+        let regex = regex_class.call1(PyTuple::new(py, args))?;
 
         let vhdl: &str = regex.call_method0("__str__")?.extract::<&str>()?;
 
         Ok(String::from(vhdl))
     }).map_err(|e| AnalyzerError::PythonError(e.to_string()))
-}
\ No newline at end of file
+}
+
+// Check if a regex fully matches a string, with the same semantics as the generated matcher
+pub fn regex_matches(regex: &str, text: &str) -> Result<bool, AnalyzerError> {
+    use pyo3::prelude::*;
+
+    Python::with_gil(|py| -> PyResult<bool> {
+        let re = py.import("re")?;
+
+        Ok(!re.call_method1("fullmatch", (regex, text))?.is_none())
+    }).map_err(|e| AnalyzerError::PythonError(e.to_string()))
+}
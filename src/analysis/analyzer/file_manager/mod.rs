@@ -1,10 +1,13 @@
-use std::{fs::File, collections::HashMap, io::Write};
+use std::{fs::File, io::Write};
 
-use text_template::Template;
+pub mod matcher;
+pub mod template;
 
-mod matcher;
+use crate::analysis::{GeneratorParams, GeneratorError};
 
-use crate::analysis::GeneratorParams;
+use super::identifier;
+
+use template::{Template, TemplateContext};
 
 #[derive(Clone,Debug)]
 pub enum TemplateType {
@@ -14,11 +17,105 @@ pub enum TemplateType {
     Record,
     Key,
     String,
-    Matcher(String)
+    DecodedString,
+    Enum,
+    Filter,
+    Slicer,
+    Presence,
+    Assembler,
+    Variant,
+    WidthConverter,
+    Matcher(Vec<String>)
 }
 
+// Support packages and entities the templates depend on, in dependency order
+const SUPPORT_FILES: [(&str, &str); 5] = [
+    ("UtilInt_pkg.vhd", include_str!("templates/support/UtilInt_pkg.vhd")),
+    ("Stream_pkg.vhd", include_str!("templates/support/Stream_pkg.vhd")),
+    ("StreamSlice.vhd", include_str!("templates/support/StreamSlice.vhd")),
+    ("StreamBuffer.vhd", include_str!("templates/support/StreamBuffer.vhd")),
+    ("StreamSync.vhd", include_str!("templates/support/StreamSync.vhd")),
+];
+
+// Directory the TIL toolchain writes its VHDL to (output_path of project.toml), relative to the output directory
+const TIL_OUTPUT_PATH: &str = "output";
+
+// Placeholders a VHDL template has to contain if the built-in template uses them
+pub const REQUIRED_PLACEHOLDERS: [&str; 4] = ["comp_name", "namespace", "bit_width", "project_name"];
+
 impl TemplateType {
-    pub fn get_template(&self) -> Option<String> {
+    // Template types which are filled in from a template file
+    pub fn get_template_types() -> Vec<TemplateType> {
+        vec![
+            TemplateType::Array,
+            TemplateType::Int,
+            TemplateType::Bool,
+            TemplateType::Record,
+            TemplateType::Key,
+            TemplateType::String,
+            TemplateType::DecodedString,
+            TemplateType::Enum,
+            TemplateType::Filter,
+            TemplateType::Slicer,
+            TemplateType::Presence,
+            TemplateType::Assembler,
+            TemplateType::Variant,
+            TemplateType::WidthConverter,
+        ]
+    }
+
+    // File name of the template, a file with this name in the template directory overrides it
+    pub fn get_template_name(&self) -> Option<&'static str> {
+        match self {
+            TemplateType::Array => Some("array_parser.vhd"),
+            TemplateType::Int => Some("int_parser.vhd"),
+            TemplateType::Bool => Some("bool_parser.vhd"),
+            TemplateType::Record => Some("record_parser.vhd"),
+            TemplateType::Key => Some("key_parser.vhd"),
+            TemplateType::String => Some("string_parser.vhd"),
+            TemplateType::DecodedString => Some("string_decoder.vhd"),
+            TemplateType::Enum => Some("enum_encoder.vhd"),
+            TemplateType::Filter => Some("filter.vhd"),
+            TemplateType::Slicer => Some("array_slicer.vhd"),
+            TemplateType::Presence => Some("key_presence.vhd"),
+            TemplateType::Assembler => Some("record_assembler.vhd"),
+            TemplateType::Variant => Some("variant_dispatcher.vhd"),
+            TemplateType::WidthConverter => Some("width_converter.vhd"),
+            TemplateType::Matcher(_) => None,
+        }
+    }
+
+    // Name of the entity implementing components of this type
+    pub fn get_entity_kind(&self) -> String {
+        match self {
+            TemplateType::Matcher(matchers) if matchers.len() == 1 => format!("{}_matcher", matchers[0]),
+            TemplateType::Matcher(_) => "enum_matcher".to_string(),
+            _ => self.get_template_name().unwrap_or_default().trim_end_matches(".vhd").to_string(),
+        }
+    }
+
+    // Get the template from the template directory if it is overridden, otherwise the built-in one
+    pub fn get_template(&self, gen_params: &GeneratorParams) -> Option<String> {
+        if let (Some(dir), Some(name)) = (&gen_params.template_dir, self.get_template_name()) {
+            if let Ok(template) = std::fs::read_to_string(format!("{}/{}", dir, name)) {
+                return Some(template);
+            }
+        }
+
+        self.get_default_template()
+    }
+
+    // Placeholders which an overriding template has to contain
+    pub fn get_required_placeholders(&self) -> Vec<String> {
+        let template = self.get_default_template().unwrap_or_default();
+
+        REQUIRED_PLACEHOLDERS.iter()
+            .map(|placeholder| format!("${{{}}}", placeholder))
+            .filter(|placeholder| template.contains(placeholder))
+            .collect()
+    }
+
+    pub fn get_default_template(&self) -> Option<String> {
         match self {
             TemplateType::Array => Some(String::from(include_str!("templates/array_parser.vhd"))),
             TemplateType::Int => Some(String::from(include_str!("templates/int_parser.vhd"))),
@@ -26,14 +123,65 @@ impl TemplateType {
             TemplateType::Record => Some(String::from(include_str!("templates/record_parser.vhd"))),
             TemplateType::Key => Some(String::from(include_str!("templates/key_parser.vhd"))),
             TemplateType::String => Some(String::from(include_str!("templates/string_parser.vhd"))),
+            TemplateType::DecodedString => Some(String::from(include_str!("templates/string_decoder.vhd"))),
+            TemplateType::Enum => Some(String::from(include_str!("templates/enum_encoder.vhd"))),
+            TemplateType::Filter => Some(String::from(include_str!("templates/filter.vhd"))),
+            TemplateType::Slicer => Some(String::from(include_str!("templates/array_slicer.vhd"))),
+            TemplateType::Presence => Some(String::from(include_str!("templates/key_presence.vhd"))),
+            TemplateType::Assembler => Some(String::from(include_str!("templates/record_assembler.vhd"))),
+            TemplateType::Variant => Some(String::from(include_str!("templates/variant_dispatcher.vhd"))),
+            TemplateType::WidthConverter => Some(String::from(include_str!("templates/width_converter.vhd"))),
             TemplateType::Matcher(_) => None,
         }
     }
 }
 
+// Check the templates in a template directory, returns the names of the overridden templates
+pub fn validate_template_dir(dir: &str) -> Result<Vec<String>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir, e))?;
+
+    let template_types = TemplateType::get_template_types();
+    let known_names: Vec<&str> = template_types.iter().filter_map(|template_type| template_type.get_template_name()).collect();
+    let mut overridden = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("{}: {}", dir, e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // Only VHDL files are templates
+        if !name.ends_with(".vhd") {
+            continue;
+        }
+
+        let template_type = match template_types.iter().find(|template_type| template_type.get_template_name() == Some(name.as_str())) {
+            Some(template_type) => template_type,
+            None => return Err(format!("{}: unknown template, expected one of {}", name, known_names.join(", "))),
+        };
+
+        let template = std::fs::read_to_string(entry.path()).map_err(|e| format!("{}: {}", name, e))?;
+
+        Template::parse(&template).map_err(|e| format!("{}: {}", name, e.0))?;
+
+        let missing: Vec<String> = template_type.get_required_placeholders().into_iter()
+            .filter(|placeholder| !template.contains(placeholder))
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(format!("{}: missing placeholders {}", name, missing.join(", ")));
+        }
+
+        overridden.push(name);
+    }
+
+    overridden.sort();
+
+    Ok(overridden)
+}
+
 struct TemplateInstance {
     pub template_type: TemplateType,
     pub component_name: String,
+    pub context: TemplateContext,
 }
 
 pub struct FileManager {
@@ -47,28 +195,58 @@ impl FileManager {
         }
     }
 
-    pub fn add_entity(&mut self, template_type: TemplateType, component_name: &str) {
+    pub fn add_entity(&mut self, template_type: TemplateType, component_name: &str, context: TemplateContext) {
+        // Registered names are already valid, anything else is rewritten so the files stay valid HDL
+        let component_name = identifier::sanitize(component_name);
+
+        let mut context = context;
+        context.insert("comp_name", component_name.as_str());
+
         self.files.push(TemplateInstance {
             template_type,
-            component_name: component_name.to_owned(),
+            component_name,
+            context,
         });
     }
 
+    // Get the template type of every entity by component name
+    pub fn get_entities(&self) -> Vec<(&TemplateType, &str)> {
+        self.files.iter().map(|inst| (&inst.template_type, inst.component_name.as_str())).collect()
+    }
+
+    // Render the VHDL of an entity, matchers are generated by a script and have no template
+    pub fn render_entity(&self, template_type: &TemplateType, context: &TemplateContext, gen_params: &GeneratorParams) -> Result<Option<String>, GeneratorError> {
+        let template_str = match template_type {
+            TemplateType::Matcher(_) => return Ok(None),
+            _ => template_type.get_template(gen_params),
+        };
+
+        // Check if a template exists
+        let template_str = template_str.ok_or_else(|| GeneratorError::InvalidTemplate(format!("no template for {:?}", template_type)))?;
+
+        // Fill in the template with the context of the component
+        Template::parse(&template_str)
+            .and_then(|template| template.render(context))
+            .map(Some)
+            .map_err(|e| GeneratorError::InvalidTemplate(format!("{}: {}", template_type.get_template_name().unwrap_or_default(), e.0)))
+    }
+
     pub fn generate_toml(&self, output_path: &str, gen_params: &GeneratorParams) {
         // Generate the files
         let file_name = format!("{}/project.toml", output_path);
         let mut file = File::create(file_name).unwrap();
 
-        let template = Template::from(include_str!("templates/toml_template.toml")); 
+        let template = Template::parse(include_str!("templates/toml_template.toml")).unwrap();
 
-        let mut templ_values: HashMap<&str, &str> = HashMap::new();
-        templ_values.insert("project_name", &gen_params.project_name);
+        let mut context = TemplateContext::new();
+        context.insert("project_name", gen_params.project_name.as_str());
+        context.insert("output_path", TIL_OUTPUT_PATH);
 
-        let text = template.fill_in(&templ_values).to_string();
+        let text = template.render(&context).unwrap();
         file.write_all(text.as_bytes()).unwrap();
     }
 
-    pub fn generate_files(&self, output_path: &str, gen_params: &GeneratorParams) {
+    pub fn generate_files(&self, output_path: &str, gen_params: &GeneratorParams) -> Result<(), GeneratorError> {
         // Create the directory if it doesn't exist
         let path = format!("{}/{}", output_path, "vhdl_dir");
         std::fs::create_dir_all(&path).unwrap();
@@ -78,42 +256,122 @@ impl FileManager {
             let file_name = format!("{}/{}_0_{}.vhd", path, gen_params.comp_namespace, inst.component_name);
             let mut file = File::create(file_name).unwrap();
 
-            let text = self.file_from_template(inst, gen_params);
+            let text = self.file_from_template(inst, gen_params)?;
+            file.write_all(text.as_bytes()).unwrap();
+        }
+
+        Ok(())
+    }
+
+    // Write the support files to the vhdl_lib directory
+    pub fn generate_support_files(&self, output_path: &str) {
+        let path = format!("{}/{}", output_path, "vhdl_lib");
+        std::fs::create_dir_all(&path).unwrap();
+
+        for (file_name, text) in SUPPORT_FILES {
+            let mut file = File::create(format!("{}/{}", path, file_name)).unwrap();
             file.write_all(text.as_bytes()).unwrap();
         }
     }
 
-    fn file_from_template(&self, template_inst: &TemplateInstance, gen_params: &GeneratorParams) -> String {
+    // Support files relative to the output directory, in dependency order
+    pub fn get_support_files(&self) -> Vec<String> {
+        SUPPORT_FILES.iter().map(|(file_name, _)| format!("vhdl_lib/{}", file_name)).collect()
+    }
+
+    // Entity files relative to the output directory
+    pub fn get_entity_files(&self, gen_params: &GeneratorParams) -> Vec<String> {
+        self.files.iter().map(|inst| format!("vhdl_dir/{}_0_{}.vhd", gen_params.comp_namespace, inst.component_name)).collect()
+    }
+
+    // Package of the TIL toolchain output relative to the output directory, the entities use it
+    pub fn get_til_package(&self, gen_params: &GeneratorParams) -> String {
+        format!("{}/{}_pkg.vhd", TIL_OUTPUT_PATH, gen_params.project_name)
+    }
+
+    // Top level of the TIL toolchain output relative to the output directory, it instantiates the entities
+    pub fn get_til_top(&self, gen_params: &GeneratorParams) -> String {
+        format!("{}/{}_0_top.vhd", TIL_OUTPUT_PATH, gen_params.comp_namespace)
+    }
+
+    // Every VHDL file of the project relative to the output directory, in the order they have to be compiled
+    pub fn get_compile_order(&self, gen_params: &GeneratorParams) -> Vec<String> {
+        let mut files = self.get_support_files();
+        files.push(self.get_til_package(gen_params));
+        files.extend(self.get_entity_files(gen_params));
+        files.push(self.get_til_top(gen_params));
+
+        files
+    }
+
+    pub fn generate_compile_order(&self, output_path: &str, gen_params: &GeneratorParams) {
+        let mut file = File::create(format!("{}/compile_order.txt", output_path)).unwrap();
+
+        for file_name in self.get_compile_order(gen_params) {
+            file.write_all(format!("{}\n", file_name).as_bytes()).unwrap();
+        }
+    }
+
+    // Write a GHDL Makefile and a FuseSoC core for the generated files
+    pub fn generate_project_files(&self, output_path: &str, gen_params: &GeneratorParams) {
+        let to_list = |files: Vec<String>| -> Vec<TemplateContext> {
+            files.into_iter().map(|file_name| {
+                let mut context = TemplateContext::new();
+                context.insert("path", file_name);
+                context
+            }).collect()
+        };
+
+        let mut context = TemplateContext::new();
+        context.insert("project_name", gen_params.project_name.as_str());
+        context.insert("top_entity", format!("{}_0_top_com", gen_params.comp_namespace));
+        context.insert("lib_files", to_list(self.get_support_files()));
+        context.insert("til_package", self.get_til_package(gen_params));
+        context.insert("entity_files", to_list(self.get_entity_files(gen_params)));
+        context.insert("til_top", self.get_til_top(gen_params));
+
+        let files = [
+            ("Makefile".to_string(), include_str!("templates/project/Makefile")),
+            (format!("{}.core", gen_params.project_name), include_str!("templates/project/project.core")),
+        ];
+
+        for (file_name, template) in files {
+            let text = Template::parse(template).unwrap().render(&context).unwrap();
+
+            let mut file = File::create(format!("{}/{}", output_path, file_name)).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        }
+    }
+
+    fn file_from_template(&self, template_inst: &TemplateInstance, gen_params: &GeneratorParams) -> Result<String, GeneratorError> {
         match template_inst.template_type {
             // Matcher needs to be handled differently as the python script fills in the template
-            TemplateType::Matcher(ref matcher_str) => {
-                matcher::generate_matcher(matcher_str, &format!("{}_0_{}_com", gen_params.comp_namespace, template_inst.component_name), &gen_params.project_name).unwrap()
-            },
-            _ => {
-                // Get the template
-                let template = template_inst.template_type.get_template();
-
-                // Check if a template exists
-                let template_str = match template {
-                    Some(template_str) => template_str,
-                    None => todo!("Template for {:?} not implemented", template_inst.template_type),
-                };
-        
-                // Convert to template struct
-                let template = Template::from(template_str.as_str());
-        
-                // Create map of values to fill in
-                let mut templ_values: HashMap<&str, &str> = HashMap::new();
-                templ_values.insert("comp_name", &template_inst.component_name);
-                let bit_width = gen_params.bit_width.to_string();
-                templ_values.insert("bit_width", &bit_width);
-                templ_values.insert("namespace", &gen_params.comp_namespace);
-                templ_values.insert("project_name", &gen_params.project_name);
-
-                // Fill in the template
-                template.fill_in(&templ_values).to_string()
+            TemplateType::Matcher(ref matchers) => {
+                matcher::generate_matcher(matchers, &format!("{}_0_{}_com", gen_params.comp_namespace, template_inst.component_name), &gen_params.project_name)
+                    .map_err(GeneratorError::AnalyzerError)
             },
+            _ => Ok(self.render_entity(&template_inst.template_type, &template_inst.context, gen_params)?.unwrap_or_default()),
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_order_includes_the_til_output() {
+        let gen_params = GeneratorParams::new(4, 8, 64, "", "schema_parser");
+        let mut file_manager = FileManager::new();
+        file_manager.add_entity(TemplateType::Int, "int_parser", TemplateContext::new());
+
+        let files = file_manager.get_compile_order(&gen_params);
+        let support = file_manager.get_support_files().len();
+
+        // The entities use the TIL package and are instantiated by the TIL top level
+        assert_eq!(files[support], "output/schema_parser_pkg.vhd");
+        assert_eq!(files[support + 1], "vhdl_dir/schema_0_parser_0_int_parser.vhd");
+        assert_eq!(files.last().unwrap(), "output/schema_0_parser_0_top.vhd");
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+/**********************************************************************************
+ * Template engine used to fill in the VHDL templates and project files.          *
+ *                                                                                *
+ * Syntax:                                                                        *
+ *   ${name}                       value of a variable, nested values as ${a.b}   *
+ *   ${if name} .. ${else} .. ${end}  conditional, ${if !name} negates            *
+ *   ${for item in list} .. ${end}    loop, ${loop.index}, ${loop.first} and      *
+ *                                    ${loop.last} are available inside           *
+ * Block tags which are alone on a line do not leave an empty line behind.        *
+ **********************************************************************************/
+
+#[derive(Clone, Debug)]
+pub enum TemplateValue {
+    Text(String),
+    Bool(bool),
+    List(Vec<TemplateContext>),
+    Map(TemplateContext),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TemplateContext {
+    values: HashMap<String, TemplateValue>,
+}
+
+#[derive(Debug)]
+pub struct TemplateError(pub String);
+
+enum Node {
+    Text(String),
+    Var(String),
+    If { name: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node> },
+    For { var: String, list: String, body: Vec<Node> },
+}
+
+enum Token {
+    Text(String),
+    Tag(Tag),
+}
+
+enum Tag {
+    Var(String),
+    If(String, bool),
+    Else,
+    End,
+    For(String, String),
+}
+
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        TemplateValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        TemplateValue::Text(value)
+    }
+}
+
+impl From<usize> for TemplateValue {
+    fn from(value: usize) -> Self {
+        TemplateValue::Text(value.to_string())
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(value: bool) -> Self {
+        TemplateValue::Bool(value)
+    }
+}
+
+impl From<Vec<TemplateContext>> for TemplateValue {
+    fn from(value: Vec<TemplateContext>) -> Self {
+        TemplateValue::List(value)
+    }
+}
+
+impl From<TemplateContext> for TemplateValue {
+    fn from(value: TemplateContext) -> Self {
+        TemplateValue::Map(value)
+    }
+}
+
+impl TemplateValue {
+    fn is_true(&self) -> bool {
+        match self {
+            TemplateValue::Text(text) => !text.is_empty() && text != "0",
+            TemplateValue::Bool(value) => *value,
+            TemplateValue::List(list) => !list.is_empty(),
+            TemplateValue::Map(_) => true,
+        }
+    }
+}
+
+impl TemplateContext {
+    pub fn new() -> TemplateContext {
+        TemplateContext::default()
+    }
+
+    pub fn insert<V: Into<TemplateValue>>(&mut self, name: &str, value: V) {
+        self.values.insert(name.to_string(), value.into());
+    }
+
+    // Add all values of another context, overwriting existing ones
+    pub fn extend(&mut self, other: TemplateContext) {
+        self.values.extend(other.values);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TemplateValue> {
+        self.values.get(name)
+    }
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, TemplateError> {
+        let tokens = tokenize(template)?;
+
+        let (nodes, closing) = parse_nodes(&mut tokens.into_iter())?;
+
+        match closing {
+            None => Ok(Template { nodes }),
+            Some(Tag::Else) => Err(TemplateError("${else} without ${if}".to_string())),
+            Some(_) => Err(TemplateError("${end} without ${if} or ${for}".to_string())),
+        }
+    }
+
+    pub fn render(&self, context: &TemplateContext) -> Result<String, TemplateError> {
+        let mut output = String::new();
+        let mut scopes = vec![context.clone()];
+
+        render_nodes(&self.nodes, &mut scopes, &mut output)?;
+
+        Ok(output)
+    }
+}
+
+// Split the template into text and tags
+fn tokenize(template: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    // Whether the remaining template starts at the beginning of a line
+    let mut at_line_begin = true;
+
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}').ok_or_else(|| TemplateError("Unclosed ${".to_string()))? + start;
+        let content = rest[start + 2..end].trim();
+
+        let tag = parse_tag(content)?;
+        let mut text = &rest[..start];
+        let mut after = end + 1;
+        let mut removed_line = false;
+
+        // Block tags alone on a line remove the line
+        if !matches!(tag, Tag::Var(_)) {
+            let line_start = text.rfind('\n').map_or(0, |idx| idx + 1);
+            let line_end = rest[after..].find('\n').map(|idx| after + idx);
+
+            let only_whitespace_before = text[line_start..].chars().all(|c| c == ' ' || c == '\t');
+            let only_whitespace_after = rest[after..line_end.unwrap_or(rest.len())].chars().all(|c| c == ' ' || c == '\t' || c == '\r');
+
+            if only_whitespace_before && only_whitespace_after && (line_start > 0 || at_line_begin) {
+                text = &text[..line_start];
+                after = line_end.map_or(rest.len(), |idx| idx + 1);
+                removed_line = true;
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        tokens.push(Token::Tag(tag));
+
+        rest = &rest[after..];
+        at_line_begin = removed_line;
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_tag(content: &str) -> Result<Tag, TemplateError> {
+    let words: Vec<&str> = content.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["if", name] => match name.strip_prefix('!') {
+            Some(name) => Ok(Tag::If(name.to_string(), true)),
+            None => Ok(Tag::If(name.to_string(), false)),
+        },
+        ["else"] => Ok(Tag::Else),
+        ["end"] => Ok(Tag::End),
+        ["for", var, "in", list] => Ok(Tag::For(var.to_string(), list.to_string())),
+        [name] => Ok(Tag::Var(name.to_string())),
+        _ => Err(TemplateError(format!("Invalid tag ${{{}}}", content))),
+    }
+}
+
+// Parse nodes until the end of the template or a closing tag, which is returned
+fn parse_nodes(tokens: &mut impl Iterator<Item = Token>) -> Result<(Vec<Node>, Option<Tag>), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Tag(Tag::Var(name)) => nodes.push(Node::Var(name)),
+            Token::Tag(Tag::If(name, negate)) => {
+                let (then, closing) = parse_nodes(tokens)?;
+
+                let otherwise = match closing {
+                    Some(Tag::End) => Vec::new(),
+                    Some(Tag::Else) => match parse_nodes(tokens)? {
+                        (otherwise, Some(Tag::End)) => otherwise,
+                        _ => return Err(TemplateError(format!("${{if {}}} is not closed", name))),
+                    },
+                    _ => return Err(TemplateError(format!("${{if {}}} is not closed", name))),
+                };
+
+                nodes.push(Node::If { name, negate, then, otherwise });
+            },
+            Token::Tag(Tag::For(var, list)) => match parse_nodes(tokens)? {
+                (body, Some(Tag::End)) => nodes.push(Node::For { var, list, body }),
+                _ => return Err(TemplateError(format!("${{for {} in {}}} is not closed", var, list))),
+            },
+            Token::Tag(tag) => return Ok((nodes, Some(tag))),
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+// Look up a (dotted) name, starting from the innermost scope
+fn lookup<'a>(scopes: &'a [TemplateContext], name: &str) -> Result<&'a TemplateValue, TemplateError> {
+    let mut parts = name.split('.');
+    let first = parts.next().unwrap_or_default();
+
+    let mut value = scopes.iter().rev()
+        .find_map(|scope| scope.get(first))
+        .ok_or_else(|| TemplateError(format!("Unknown value ${{{}}}", name)))?;
+
+    for part in parts {
+        value = match value {
+            TemplateValue::Map(map) => map.get(part),
+            _ => None,
+        }.ok_or_else(|| TemplateError(format!("Unknown value ${{{}}}", name)))?;
+    }
+
+    Ok(value)
+}
+
+fn render_nodes(nodes: &[Node], scopes: &mut Vec<TemplateContext>, output: &mut String) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Var(name) => match lookup(scopes, name)? {
+                TemplateValue::Text(text) => output.push_str(text),
+                TemplateValue::Bool(value) => output.push_str(if *value { "true" } else { "false" }),
+                _ => return Err(TemplateError(format!("${{{}}} cannot be printed", name))),
+            },
+            Node::If { name, negate, then, otherwise } => {
+                if lookup(scopes, name)?.is_true() != *negate {
+                    render_nodes(then, scopes, output)?;
+                } else {
+                    render_nodes(otherwise, scopes, output)?;
+                }
+            },
+            Node::For { var, list, body } => {
+                let items = match lookup(scopes, list)? {
+                    TemplateValue::List(items) => items.clone(),
+                    _ => return Err(TemplateError(format!("${{{}}} is not a list", list))),
+                };
+
+                for (index, item) in items.iter().enumerate() {
+                    let mut loop_info = TemplateContext::new();
+                    loop_info.insert("index", index);
+                    loop_info.insert("first", index == 0);
+                    loop_info.insert("last", index + 1 == items.len());
+
+                    let mut scope = TemplateContext::new();
+                    scope.insert(var, item.clone());
+                    scope.insert("loop", loop_info);
+
+                    scopes.push(scope);
+                    let result = render_nodes(body, scopes, output);
+                    scopes.pop();
+                    result?;
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, context: &TemplateContext) -> Result<String, TemplateError> {
+        Template::parse(template)?.render(context)
+    }
+
+    fn ports() -> TemplateContext {
+        let port = |name: &str, direction: &str| {
+            let mut port = TemplateContext::new();
+            port.insert("name", name);
+            port.insert("direction", direction);
+            port
+        };
+
+        let mut context = TemplateContext::new();
+        context.insert("comp_name", "key_parser");
+        context.insert("epc", 4);
+        context.insert("ports", vec![port("input", "in"), port("output", "out"), port("matcher", "out")]);
+        context
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        let mut context = ports();
+        let mut generic = TemplateContext::new();
+        generic.insert("value", 8);
+        context.insert("generic", generic);
+        context.insert("optional", true);
+
+        assert_eq!(render("entity ${comp_name} is -- ${ epc }", &context).unwrap(), "entity key_parser is -- 4");
+        assert_eq!(render("${generic.value} ${optional}", &context).unwrap(), "8 true");
+        assert_eq!(render("no tags", &context).unwrap(), "no tags");
+    }
+
+    #[test]
+    fn conditionals() {
+        let mut context = TemplateContext::new();
+        context.insert("yes", true);
+        context.insert("no", false);
+        context.insert("zero", 0);
+        context.insert("empty", "");
+        context.insert("list", Vec::<TemplateContext>::new());
+
+        assert_eq!(render("${if yes}a${else}b${end}", &context).unwrap(), "a");
+        assert_eq!(render("${if no}a${else}b${end}", &context).unwrap(), "b");
+        assert_eq!(render("${if !no}a${end}", &context).unwrap(), "a");
+        assert_eq!(render("${if zero}a${end}${if empty}b${end}${if list}c${end}", &context).unwrap(), "");
+    }
+
+    #[test]
+    fn nested_loops_and_conditionals() {
+        let mut context = ports();
+        let mut inner = TemplateContext::new();
+        inner.insert("name", "bit");
+        context.insert("inner", vec![inner.clone(), inner]);
+
+        let template = "${for port in ports}${if !loop.first}, ${end}${loop.index}:${port.name}${end}";
+        assert_eq!(render(template, &context).unwrap(), "0:input, 1:output, 2:matcher");
+
+        // Inner scopes shadow the outer ones and the outer values stay visible
+        let template = "${for port in ports}${for port in inner}${port.name}${end}-${comp_name}${if loop.last};${end}${end}";
+        assert_eq!(render(template, &context).unwrap(), "bitbit-key_parserbitbit-key_parserbitbit-key_parser;");
+    }
+
+    #[test]
+    fn removes_lines_with_only_block_tags() {
+        let template = "port (\n    ${for port in ports}\n    ${port.name} : ${port.direction};\n    ${end}\n);\n";
+        assert_eq!(render(template, &ports()).unwrap(), "port (\n    input : in;\n    output : out;\n    matcher : out;\n);\n");
+
+        let template = "${if epc}\nfirst\n  ${else}  \nsecond\n${end}\nlast";
+        assert_eq!(render(template, &ports()).unwrap(), "first\nlast");
+
+        // Variables and tags next to text keep their line
+        let template = "a ${if epc}b${end}\n${comp_name}\n";
+        assert_eq!(render(template, &ports()).unwrap(), "a b\nkey_parser\n");
+    }
+
+    #[test]
+    fn unknown_variables_are_errors() {
+        let context = ports();
+
+        assert!(render("${missing}", &context).is_err());
+        assert!(render("${if missing}a${end}", &context).is_err());
+        assert!(render("${for item in missing}${end}", &context).is_err());
+        assert!(render("${comp_name.value}", &context).is_err());
+        // Lists cannot be printed or iterated when they are not lists
+        assert!(render("${ports}", &context).is_err());
+        assert!(render("${for item in comp_name}${end}", &context).is_err());
+    }
+
+    #[test]
+    fn unterminated_blocks_are_errors() {
+        assert!(Template::parse("${if a}").is_err());
+        assert!(Template::parse("${if a}${else}").is_err());
+        assert!(Template::parse("${for a in b}${if a}${end}").is_err());
+        assert!(Template::parse("${end}").is_err());
+        assert!(Template::parse("${else}").is_err());
+        assert!(Template::parse("${comp_name").is_err());
+        assert!(Template::parse("${for a b}").is_err());
+    }
+}
@@ -24,6 +24,24 @@ entity ${namespace}_0_${comp_name}_com is
     input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     input_strb : in std_logic_vector(EPC-1 downto 0);
+${if count}
+    count_valid : out std_logic;
+    count_ready : in std_logic;
+    count_data : out std_logic_vector(EPC*ELEMENT_COUNTER_BW-1 downto 0);
+    count_last : out std_logic_vector(OUTER_NESTING_LEVEL*EPC-1 downto 0);
+    count_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    count_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    count_strb : out std_logic_vector(EPC-1 downto 0);
+${end}
+${if index}
+    index_valid : out std_logic;
+    index_ready : in std_logic;
+    index_data : out std_logic_vector(EPC*ELEMENT_COUNTER_BW-1 downto 0);
+    index_last : out std_logic_vector((OUTER_NESTING_LEVEL+1)*EPC-1 downto 0);
+    index_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    index_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    index_strb : out std_logic_vector(EPC-1 downto 0);
+${end}
     output_valid : out std_logic;
     output_ready : in std_logic;
     output_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
@@ -78,6 +96,21 @@ begin
 
     variable is_top_array     : std_logic;
 
+    -- Number of elements so far and whether the current element has any characters.
+    variable elem_count : unsigned(ELEMENT_COUNTER_BW-1 downto 0) := (others => '0');
+    variable elem_data  : std_logic := '0';
+
+    -- Count and index output holding registers.
+    type counter_array is array (natural range <>) of unsigned(ELEMENT_COUNTER_BW-1 downto 0);
+    variable cd : counter_array(0 to EPC-1);
+    variable cl : std_logic_vector(OUTER_NESTING_LEVEL*EPC-1 downto 0);
+    variable cs : std_logic_vector(EPC-1 downto 0);
+    variable cv : std_logic := '0';
+    variable xd : counter_array(0 to EPC-1);
+    variable xl : std_logic_vector((OUTER_NESTING_LEVEL+1)*EPC-1 downto 0);
+    variable xs : std_logic_vector(EPC-1 downto 0);
+    variable xv : std_logic := '0';
+
   begin
     if rising_edge(clk) then
 
@@ -103,15 +136,31 @@ begin
       if to_x01(output_ready) = '1' then
         ov := '0';
       end if;
+${if count}
+      if to_x01(count_ready) = '1' then
+        cv := '0';
+      end if;
+${end}
+${if index}
+      if to_x01(index_ready) = '1' then
+        xv := '0';
+      end if;
+${end}
 
-      -- Do processing when both registers are ready.
-      if to_x01(iv) = '1' and to_x01(ov) /= '1' then
+      -- Do processing when all registers are ready.
+      if to_x01(iv) = '1' and to_x01(ov) /= '1' and to_x01(cv) /= '1' and to_x01(xv) /= '1' then
         for idx in 0 to EPC-1 loop
 
           -- Default behavior.
           od(idx).data       := id(idx).data;
           od(idx).last(OUTER_NESTING_LEVEL+1 downto 0)   := id(idx).last & "00";
           od(idx).strb       := '0';
+          cd(idx) := (others => '0');
+          cl(OUTER_NESTING_LEVEL*(idx+1)-1 downto OUTER_NESTING_LEVEL*idx) := id(idx).last;
+          cs(idx) := '0';
+          xd(idx) := (others => '0');
+          xl((OUTER_NESTING_LEVEL+1)*(idx+1)-1 downto (OUTER_NESTING_LEVEL+1)*idx) := id(idx).last & '0';
+          xs(idx) := '0';
           
           -- Element-wise processing only when the lane is valid.
           if to_x01(id(idx).strb) = '1' then
@@ -152,22 +201,47 @@ begin
                       -- Keep processing values if we are still in an inner array.
                       if is_top_array = '1' then
                         state := STATE_ARRAY;
+                        elem_data := '1';
                       else
                         state := STATE_IDLE;
                         od(idx).last(0) := '1';
                         od(idx).last(1) := '1';
         
                         od(idx).strb   := '0';
+
+                        -- The last element, an empty array has none.
+                        if elem_data = '1' then
+                          xd(idx) := elem_count;
+                          xs(idx) := '1';
+                          elem_count := elem_count + 1;
+                        end if;
+                        xl((OUTER_NESTING_LEVEL+1)*idx) := '1';
+                        cd(idx) := elem_count;
+                        cs(idx) := '1';
+                        elem_count := (others => '0');
+                        elem_data := '0';
                       end if;
+                    else
+                      elem_data := '1';
                     end if;
                   when X"2C" => -- ','
                     if or_reduce(nesting_inner) = '0' then
                       state := STATE_ARRAY;
                       od(idx).last(0) := '1';
                       od(idx).strb   := '0';
+
+                      xd(idx) := elem_count;
+                      xs(idx) := '1';
+                      elem_count := elem_count + 1;
+                      elem_data := '0';
+                    else
+                      elem_data := '1';
                     end if;
+                  when X"20" | X"09" | X"0A" | X"0D" => -- whitespace
+                    state := STATE_ARRAY;
                   when others =>
                     state := STATE_ARRAY;
+                    elem_data := '1';
                 end case;
             end case;
           end if;
@@ -175,6 +249,8 @@ begin
           -- up everything.
           if or_reduce(id(idx).last) /= '0' then
             state := STATE_IDLE;
+            elem_count := (others => '0');
+            elem_data := '0';
           end if;
         end loop;
 
@@ -183,6 +259,16 @@ begin
             ov := '1';
           end if;
         end loop;
+${if count}
+        if or_reduce(cs) = '1' or or_reduce(cl) = '1' then
+          cv := '1';
+        end if;
+${end}
+${if index}
+        if or_reduce(xs) = '1' or or_reduce(xl) = '1' then
+          xv := '1';
+        end if;
+${end}
         iv := '0';
       end if;
 
@@ -190,7 +276,11 @@ begin
       if to_x01(rst) /= '0' then
         iv    := '0';
         ov    := '0';
+        cv    := '0';
+        xv    := '0';
         state := STATE_IDLE;
+        elem_count := (others => '0');
+        elem_data  := '0';
       end if;
 
       -- Forward output holding register.
@@ -204,6 +294,28 @@ begin
         output_endi <= std_logic_vector(endi);
         output_strb(idx) <= od(idx).strb;
       end loop;
+${if count}
+
+      count_valid <= to_x01(cv);
+      for idx in 0 to EPC-1 loop
+        count_data(ELEMENT_COUNTER_BW*(idx+1)-1 downto ELEMENT_COUNTER_BW*idx) <= std_logic_vector(cd(idx));
+      end loop;
+      count_last <= cl;
+      count_strb <= cs;
+      count_stai <= (others => '0');
+      count_endi <= (others => '1');
+${end}
+${if index}
+
+      index_valid <= to_x01(xv);
+      for idx in 0 to EPC-1 loop
+        index_data(ELEMENT_COUNTER_BW*(idx+1)-1 downto ELEMENT_COUNTER_BW*idx) <= std_logic_vector(xd(idx));
+      end loop;
+      index_last <= xl;
+      index_strb <= xs;
+      index_stai <= (others => '0');
+      index_endi <= (others => '1');
+${end}
     end if;
   end process;
 end behav;
\ No newline at end of file
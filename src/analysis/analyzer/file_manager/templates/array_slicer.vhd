@@ -0,0 +1,155 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+
+-- Keeps the elements of every array with an index from START up to STOP (all
+-- elements from START if STOP is 0) in steps of STRIDE. The characters of the
+-- other elements are dropped together with the last which closes them, so
+-- the element parsers never see them.
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC : positive := 4;
+    OUTER_NESTING_LEVEL : positive := 2;
+    INDEX_BW : positive := 4;
+    START : natural := 0;
+    STOP : natural := 0;
+    STRIDE : positive := 1
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(EPC*${bit_width}-1 downto 0);
+    input_last : in std_logic_vector(((OUTER_NESTING_LEVEL + 2) * EPC) - 1 downto 0);
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_strb : in std_logic_vector(EPC-1 downto 0);
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    output_last : out std_logic_vector(((OUTER_NESTING_LEVEL + 2) * EPC) - 1 downto 0);
+    output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_strb : out std_logic_vector(EPC-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+  constant LW : natural := OUTER_NESTING_LEVEL + 2;
+begin
+  clk_proc: process (clk) is
+    -- Input holding register.
+    variable id_data : std_logic_vector(EPC*${bit_width}-1 downto 0);
+    variable id_last : std_logic_vector(LW*EPC-1 downto 0);
+    variable id_strb : std_logic_vector(EPC-1 downto 0);
+    variable iv      : std_logic := '0';
+    variable ir      : std_logic := '0';
+
+    -- Output holding register.
+    variable od_data : std_logic_vector(EPC*${bit_width}-1 downto 0);
+    variable od_last : std_logic_vector(LW*EPC-1 downto 0);
+    variable od_strb : std_logic_vector(EPC-1 downto 0);
+    variable ov      : std_logic := '0';
+
+    -- Index of the current element, saturating so arrays longer than the
+    -- slice can't wrap around into it again.
+    variable elem_index : unsigned(INDEX_BW-1 downto 0) := (others => '0');
+    variable stride_cnt : natural range 0 to STRIDE-1 := 0;
+    variable selected   : std_logic;
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch input holding register if we said we would.
+      if to_x01(ir) = '1' then
+        iv := input_valid;
+        id_data := input_data;
+        id_last := input_last;
+        for idx in 0 to EPC-1 loop
+          if idx < unsigned(input_stai) then
+            id_strb(idx) := '0';
+          elsif idx > unsigned(input_endi) then
+            id_strb(idx) := '0';
+          else
+            id_strb(idx) := input_strb(idx);
+          end if;
+        end loop;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Do processing when both registers are ready.
+      if to_x01(iv) = '1' and to_x01(ov) /= '1' then
+        od_data := id_data;
+        od_last := id_last;
+
+        for idx in 0 to EPC-1 loop
+          -- Whether the current element is in the slice.
+          if elem_index >= START and (STOP = 0 or elem_index < STOP) and stride_cnt = 0 then
+            selected := '1';
+          else
+            selected := '0';
+          end if;
+
+          od_strb(idx) := id_strb(idx) and selected;
+
+          -- The element is closed.
+          if id_last(LW*idx) = '1' then
+            if selected = '0' then
+              od_last(LW*idx) := '0';
+            end if;
+
+            if elem_index >= START then
+              if stride_cnt = STRIDE-1 then
+                stride_cnt := 0;
+              else
+                stride_cnt := stride_cnt + 1;
+              end if;
+            end if;
+
+            if elem_index /= (elem_index'range => '1') then
+              elem_index := elem_index + 1;
+            end if;
+          end if;
+
+          -- The array is closed, the next one starts at the first element.
+          if or_reduce(id_last(LW*(idx+1)-1 downto LW*idx+1)) = '1' then
+            elem_index := (others => '0');
+            stride_cnt := 0;
+          end if;
+        end loop;
+
+        ov := '1';
+        iv := '0';
+      end if;
+
+      -- Handle reset.
+      if to_x01(rst) /= '0' then
+        iv         := '0';
+        ov         := '0';
+        elem_index := (others => '0');
+        stride_cnt := 0;
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      ir := not iv and not rst;
+      input_ready <= ir and not rst;
+      output_data <= od_data;
+      output_last <= od_last;
+      output_stai <= (others => '0');
+      output_endi <= (others => '1');
+      output_strb <= od_strb;
+    end if;
+  end process;
+end behav;
@@ -0,0 +1,70 @@
+"""Pushes the analyzed sample documents through the design and checks every output."""
+
+import json
+import os
+
+import cocotb
+from cocotb.clock import Clock
+from cocotb.triggers import ClockCycles, RisingEdge
+
+from tydi_stream import StreamDriver, StreamMonitor, load_transfers
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+TIMEOUT_CYCLES = 10000
+
+
+def _load(name):
+    with open(os.path.join(HERE, name)) as f:
+        return json.load(f)
+
+
+def _normalize(params, values):
+    kind = params["kind"]
+    if kind == "int":
+        return [value & ((1 << params["data_bits"]) - 1) for value in values]
+    if kind == "group":
+        masks = {field["name"]: (1 << field["bits"]) - 1 for field in params["fields"] if field["kind"] == "int"}
+        return [
+            {name: value & masks[name] if name in masks else value for name, value in group.items()}
+            for group in values
+        ]
+    if kind == "union":
+        masks = {field["name"]: (1 << field["bits"]) - 1 for field in params["fields"] if field["kind"] == "int"}
+        return [
+            {"tag": variant["tag"], "value": variant["value"] & masks[variant["tag"]] if variant["tag"] in masks else variant["value"]}
+            for variant in values
+        ]
+    return values
+
+
+@cocotb.test()
+async def test_samples(dut):
+    streams = _load("streams.json")
+    expected = _load("expected.json")
+
+    cocotb.start_soon(Clock(dut.clk, 10, units="ns").start())
+
+    monitors = {
+        name: StreamMonitor(dut, name, params)
+        for name, params in streams["outputs"].items()
+    }
+    driver = StreamDriver(dut, "input", streams["input"])
+
+    dut.rst.value = 1
+    await ClockCycles(dut.clk, 5)
+    dut.rst.value = 0
+    await RisingEdge(dut.clk)
+
+    await driver.send(load_transfers(os.path.join(HERE, "input_transfers.txt")))
+
+    # Wait until every output produced the expected number of values
+    for _ in range(TIMEOUT_CYCLES):
+        if all(len(monitors[name].values()) >= len(values) for name, values in expected.items()):
+            break
+        await RisingEdge(dut.clk)
+
+    for name, values in expected.items():
+        params = streams["outputs"][name]
+        received = monitors[name].values()
+        want = _normalize(params, values)
+        assert received == want, "{}: expected {}, got {}".format(name, want, received)
@@ -0,0 +1,171 @@
+"""Drivers and monitors for Tydi physical streams.
+
+The stream parameters (lanes, data bits, dimensionality, complexity) are
+written to streams.json by the generator and match the generated ports.
+"""
+
+import cocotb
+from cocotb.triggers import ReadOnly, RisingEdge
+
+
+def _bits_to_int(bits):
+    return int(bits, 2) if bits else 0
+
+
+def load_transfers(path):
+    """Read transfers as written by the generator: data last stai endi strb."""
+    transfers = []
+    with open(path) as f:
+        for line in f:
+            line = line.rstrip("\n")
+            if not line:
+                continue
+            data, last, stai, endi, strb = line.split(" ")
+            transfers.append({
+                "data": _bits_to_int(data),
+                "last": _bits_to_int(last),
+                "stai": _bits_to_int(stai),
+                "endi": _bits_to_int(endi),
+                "strb": _bits_to_int(strb),
+            })
+    return transfers
+
+
+class StreamDriver:
+    """Drives transfers onto a sink port of the design."""
+
+    def __init__(self, dut, name, params):
+        self.dut = dut
+        self.name = name
+        self.params = params
+        self._signal("valid").value = 0
+
+    def _signal(self, suffix):
+        return getattr(self.dut, "{}_{}".format(self.name, suffix))
+
+    def _has_signal(self, suffix):
+        return hasattr(self.dut, "{}_{}".format(self.name, suffix))
+
+    async def send(self, transfers):
+        for transfer in transfers:
+            for suffix in ("data", "last", "stai", "endi", "strb"):
+                if self._has_signal(suffix):
+                    self._signal(suffix).value = transfer[suffix]
+            self._signal("valid").value = 1
+
+            while True:
+                await RisingEdge(self.dut.clk)
+                if self._signal("ready").value.integer == 1:
+                    break
+
+        self._signal("valid").value = 0
+
+
+class StreamMonitor:
+    """Accepts transfers from a source port and decodes them into values."""
+
+    def __init__(self, dut, name, params):
+        self.dut = dut
+        self.name = name
+        self.params = params
+        self.elements = []
+        self.sequences = []
+        self._current = []
+        self._signal("ready").value = 1
+        cocotb.start_soon(self._run())
+
+    def _signal(self, suffix):
+        return getattr(self.dut, "{}_{}".format(self.name, suffix))
+
+    def _value(self, suffix, default):
+        name = "{}_{}".format(self.name, suffix)
+        if not hasattr(self.dut, name):
+            return default
+        return getattr(self.dut, name).value.integer
+
+    async def _run(self):
+        lanes = self.params["lanes"]
+        data_bits = self.params["data_bits"]
+        dims = self.params["dimensionality"]
+        lane_last = self.params["complexity"] >= 8
+
+        while True:
+            await RisingEdge(self.dut.clk)
+            await ReadOnly()
+            if self._signal("valid").value.integer != 1:
+                continue
+
+            data = self._value("data", 0)
+            last = self._value("last", 0)
+            stai = self._value("stai", 0)
+            endi = self._value("endi", lanes - 1)
+            strb = self._value("strb", (1 << lanes) - 1)
+
+            for lane in range(stai, endi + 1):
+                if (strb >> lane) & 1:
+                    element = (data >> (lane * data_bits)) & ((1 << data_bits) - 1)
+                    self.elements.append(element)
+                    self._current.append(element)
+
+                if lane_last:
+                    lane_last_bits = (last >> (lane * dims)) & ((1 << dims) - 1)
+                else:
+                    lane_last_bits = last if lane == endi else 0
+
+                if dims > 0 and lane_last_bits & 1:
+                    self.sequences.append(self._current)
+                    self._current = []
+
+    def values(self):
+        """Decode the received data according to the kind of stream."""
+        kind = self.params["kind"]
+        if kind == "int":
+            return list(self.elements)
+        if kind == "bool":
+            return [bool(element) for element in self.elements]
+        if kind == "enum":
+            unknown = 1 << (self.params["data_bits"] - 1)
+            return [-1 if element & unknown else element for element in self.elements]
+        if kind == "string":
+            return [bytes(seq).decode("utf-8", errors="replace") for seq in self.sequences]
+        if kind == "fixed_string":
+            width = self.params["data_bits"] // 8
+            return [
+                element.to_bytes(width, "little").rstrip(b"\0").decode("utf-8", errors="replace")
+                for element in self.elements
+            ]
+        if kind == "group":
+            groups = []
+            for element in self.elements:
+                group = {}
+                for field in self.params["fields"]:
+                    value = element & ((1 << field["bits"]) - 1)
+                    group[field["name"]] = bool(value) if field["kind"] == "bool" else value
+                    element >>= field["bits"]
+                groups.append(group)
+            return groups
+        if kind == "union":
+            # Tag of the field in the lowest bits, the value of the field above it
+            fields = self.params["fields"]
+            tag_bits = (len(fields) - 1).bit_length()
+            variants = []
+            for element in self.elements:
+                field = fields[element & ((1 << tag_bits) - 1)]
+                value = (element >> tag_bits) & ((1 << field["bits"]) - 1)
+                if field["kind"] == "bool":
+                    value = bool(value)
+                elif field["kind"] != "int":
+                    value = None
+                variants.append({"tag": field["name"], "value": value})
+            return variants
+        if kind == "prefixed_string":
+            strings = []
+            idx = 0
+            while idx + 2 <= len(self.elements):
+                length = (self.elements[idx] << 8) | self.elements[idx + 1]
+                if idx + 2 + length > len(self.elements):
+                    break
+                strings.append(bytes(self.elements[idx + 2:idx + 2 + length]).decode("utf-8", errors="replace"))
+                idx += 2 + length
+            return strings
+        return [bytes(seq).decode("utf-8", errors="replace") for seq in self.sequences if seq]
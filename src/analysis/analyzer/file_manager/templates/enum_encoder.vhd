@@ -0,0 +1,354 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+use work.Stream_pkg.all;
+
+-- Encodes JSON strings with a known set of values into the code of the value.
+-- The characters of every string are sent to a matcher with a regex for every
+-- value, the match bits of the lane closing the string select the code. The
+-- bit above the code is set when the string is none of the values.
+--
+-- Code table:
+${for value in values}
+--   ${value.code}: ${value.value}
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC                  : positive := 4;
+    NESTING_LEVEL        : positive := 1;
+    CODE_WIDTH           : positive := 1;
+    NUM_VALUES           : positive := 2;
+    DLY_COMP_BUFF_DEPTH  : integer := 5
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+
+    -- Stream(
+    --     Bits(8),
+    --     t=EPC,
+    --     d=NESTING_LEVEL+1,
+    --     c=8
+    -- )
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(8*EPC-1 downto 0);
+    input_last : in std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0) := (others => '0');
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '0');
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '1');
+    input_strb : in std_logic_vector(EPC-1 downto 0) := (others => '1');
+
+    matcher_str_valid : out std_logic;
+    matcher_str_ready : in std_logic;
+    matcher_str_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    matcher_str_last : out std_logic_vector(EPC-1 downto 0);
+    matcher_str_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_str_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_str_strb : out std_logic_vector(EPC-1 downto 0);
+    matcher_match_valid : in std_logic;
+    matcher_match_ready : out std_logic;
+    matcher_match_data : in std_logic_vector(NUM_VALUES*EPC-1 downto 0);
+    matcher_match_last : in std_logic_vector(EPC-1 downto 0);
+    matcher_match_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_match_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_match_strb : in std_logic_vector(EPC-1 downto 0);
+
+    -- Stream(
+    --     Bits(CODE_WIDTH+1),
+    --     t=EPC,
+    --     d=NESTING_LEVEL,
+    --     c=8
+    -- )
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector((CODE_WIDTH+1)*EPC-1 downto 0);
+    output_last : out std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_strb : out std_logic_vector(EPC-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+  constant OUT_WIDTH       : integer := CODE_WIDTH+1;
+
+  -- Index constants for packing the closing lanes and lasts into a single vector.
+  constant BUFF_WIDTH      : integer := EPC*(1 + NESTING_LEVEL);
+  constant BUFF_END_STAI   : integer := 0;
+  constant BUFF_END_ENDI   : integer := EPC-1;
+  constant BUFF_LAST_STAI  : integer := EPC;
+  constant BUFF_LAST_ENDI  : integer := EPC + NESTING_LEVEL*EPC-1;
+
+  -- Input with the characters of the strings and the lanes closing them marked.
+  signal tag_valid         : std_logic;
+  signal tag_ready         : std_logic;
+  signal tag_data          : std_logic_vector(8*EPC-1 downto 0);
+  signal tag_char          : std_logic_vector(EPC-1 downto 0);
+  signal tag_end           : std_logic_vector(EPC-1 downto 0);
+  signal tag_last          : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+
+  signal buff_in_valid     : std_logic;
+  signal buff_in_valid_t   : std_logic;
+  signal buff_in_ready     : std_logic;
+  signal buff_in_data      : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+  signal buff_out_valid    : std_logic;
+  signal buff_out_ready    : std_logic;
+  signal buff_out_data     : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+begin
+
+  tag_proc: process (clk) is
+    -- Input holding register.
+    type in_type is record
+      data  : std_logic_vector(7 downto 0);
+      last  : std_logic_vector(NESTING_LEVEL-1 downto 0);
+      strb  : std_logic;
+    end record;
+
+    type in_array is array (natural range <>) of in_type;
+    variable id : in_array(0 to EPC-1);
+    variable iv : std_logic := '0';
+    variable ir : std_logic := '0';
+
+    -- Output holding register.
+    variable od_data : std_logic_vector(8*EPC-1 downto 0);
+    variable od_char : std_logic_vector(EPC-1 downto 0);
+    variable od_end  : std_logic_vector(EPC-1 downto 0);
+    variable od_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable ov      : std_logic := '0';
+
+    -- Enumeration type for our state machine.
+    type state_t is (STATE_IDLE,
+                     STATE_STRING,
+                     STATE_ESCAPE);
+
+    -- State variable
+    variable state : state_t;
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch input holding register if we said we would.
+      if to_x01(ir) = '1' then
+        iv := input_valid;
+        for idx in 0 to EPC-1 loop
+          id(idx).data := input_data(8*idx+7 downto 8*idx);
+          id(idx).last := input_last((NESTING_LEVEL+1)*(idx+1)-1 downto (NESTING_LEVEL+1)*idx+1);
+          if idx < unsigned(input_stai) then
+            id(idx).strb := '0';
+          elsif idx > unsigned(input_endi) then
+            id(idx).strb := '0';
+          else
+            id(idx).strb := input_strb(idx);
+          end if;
+        end loop;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(tag_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Do processing when both registers are ready.
+      if to_x01(iv) = '1' and to_x01(ov) /= '1' then
+        for idx in 0 to EPC-1 loop
+
+          -- Default behavior.
+          od_data(8*idx+7 downto 8*idx) := id(idx).data;
+          od_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx) := id(idx).last;
+          od_char(idx) := '0';
+          od_end(idx)  := '0';
+
+          -- Element-wise processing only when the lane is valid.
+          if to_x01(id(idx).strb) = '1' then
+
+            case state is
+              when STATE_IDLE =>
+                if id(idx).data = X"22" then -- '"'
+                  state := STATE_STRING;
+                end if;
+
+              when STATE_STRING =>
+                case id(idx).data is
+                  when X"22" => -- '"'
+                    state := STATE_IDLE;
+                    od_end(idx) := '1';
+                  when X"5C" => -- '\'
+                    state := STATE_ESCAPE;
+                    od_char(idx) := '1';
+                  when others =>
+                    od_char(idx) := '1';
+                end case;
+
+              when STATE_ESCAPE =>
+                state := STATE_STRING;
+                od_char(idx) := '1';
+            end case;
+          end if;
+
+          -- Clear state upon any last, to prevent broken elements from messing
+          -- up everything.
+          if or_reduce(id(idx).last) /= '0' then
+            state := STATE_IDLE;
+          end if;
+        end loop;
+        ov := '1';
+        iv := '0';
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        iv    := '0';
+        ov    := '0';
+        state := STATE_IDLE;
+      end if;
+
+      -- Forward output holding register.
+      tag_valid <= to_x01(ov);
+      ir := not iv and not rst;
+      input_ready <= ir and not rst;
+      tag_data <= od_data;
+      tag_char <= od_char;
+      tag_end  <= od_end;
+      tag_last <= od_last;
+    end if;
+  end process;
+
+  -- Every transfer goes to the matcher, the buffer only keeps the ones with
+  -- a result or a last until the matcher is done.
+  dly_comp_buff: StreamBuffer
+    generic map (
+      DATA_WIDTH              => BUFF_WIDTH,
+      MIN_DEPTH               => DLY_COMP_BUFF_DEPTH
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid                => buff_in_valid_t,
+      in_ready                => buff_in_ready,
+      in_data                 => buff_in_data,
+      out_valid               => buff_out_valid,
+      out_ready               => buff_out_ready,
+      out_data                => buff_out_data
+    );
+
+  tag_sync: StreamSync
+    generic map (
+      NUM_INPUTS              => 1,
+      NUM_OUTPUTS             => 2
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid(0)             => tag_valid,
+      in_ready(0)             => tag_ready,
+      out_valid(0)            => buff_in_valid,
+      out_valid(1)            => matcher_str_valid,
+      out_ready(0)            => buff_in_ready,
+      out_ready(1)            => matcher_str_ready
+    );
+
+  buff_in_data(BUFF_END_ENDI downto BUFF_END_STAI)   <= tag_end;
+  buff_in_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI) <= tag_last;
+  buff_in_valid_t <= buff_in_valid and (or_reduce(tag_end) or or_reduce(tag_last));
+
+  matcher_str_data <= tag_data;
+  matcher_str_strb <= tag_char;
+  matcher_str_last <= tag_end;
+  matcher_str_stai <= (others => '0');
+  matcher_str_endi <= (others => '1');
+
+  encode_proc: process (clk) is
+    -- Buffer input holding register.
+    variable b_end  : std_logic_vector(EPC-1 downto 0);
+    variable b_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable bv     : std_logic := '0';
+    variable br     : std_logic := '0';
+
+    -- Matcher input holding register.
+    variable m_data : std_logic_vector(NUM_VALUES*EPC-1 downto 0);
+    variable mv     : std_logic := '0';
+    variable mr     : std_logic := '0';
+
+    -- Output holding register.
+    variable od_data : std_logic_vector(OUT_WIDTH*EPC-1 downto 0);
+    variable od_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable od_strb : std_logic_vector(EPC-1 downto 0);
+    variable ov      : std_logic := '0';
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch buffer input holding register.
+      if to_x01(br) = '1' then
+        bv     := buff_out_valid;
+        b_end  := buff_out_data(BUFF_END_ENDI downto BUFF_END_STAI);
+        b_last := buff_out_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI);
+      end if;
+
+      -- Latch matcher input holding register.
+      if to_x01(mr) = '1' then
+        mv     := matcher_match_valid;
+        m_data := matcher_match_data;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Transfers closing a string wait for the result of the matcher.
+      if to_x01(bv) = '1' and to_x01(ov) /= '1' and (or_reduce(b_end) = '0' or to_x01(mv) = '1') then
+        for idx in 0 to EPC-1 loop
+          od_data(OUT_WIDTH*(idx+1)-1 downto OUT_WIDTH*idx) := (others => '0');
+          od_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx) := b_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx);
+          od_strb(idx) := b_end(idx);
+
+          if b_end(idx) = '1' then
+            -- Unknown unless one of the values matched, the first match wins.
+            od_data(OUT_WIDTH*idx+CODE_WIDTH) := '1';
+            for code in NUM_VALUES-1 downto 0 loop
+              if m_data(NUM_VALUES*idx+code) = '1' then
+                od_data(OUT_WIDTH*idx+CODE_WIDTH-1 downto OUT_WIDTH*idx) := std_logic_vector(to_unsigned(code, CODE_WIDTH));
+                od_data(OUT_WIDTH*idx+CODE_WIDTH) := '0';
+              end if;
+            end loop;
+          end if;
+        end loop;
+
+        if or_reduce(b_end) = '1' then
+          mv := '0';
+        end if;
+        bv := '0';
+        ov := '1';
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        bv := '0';
+        mv := '0';
+        ov := '0';
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      output_data  <= od_data;
+      output_last  <= od_last;
+      output_strb  <= od_strb;
+      output_stai  <= (others => '0');
+      output_endi  <= (others => '1');
+
+      br := not bv and not rst;
+      buff_out_ready <= br;
+      mr := not mv and not rst;
+      matcher_match_ready <= mr;
+    end if;
+  end process;
+end architecture;
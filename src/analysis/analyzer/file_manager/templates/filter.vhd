@@ -0,0 +1,328 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+use work.Stream_pkg.all;
+
+-- Passes the values of a field through unchanged and emits for every value
+-- whether it is selected by the predicate: ${predicate}
+${if regex}
+-- The characters of every string are sent to a matcher with the regex, the
+-- match bit of the lane closing the string is the selected bit.
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+${if compare}
+    NESTING_LEVEL        : positive := 1;
+    BITWIDTH             : positive := 64
+${end}
+${if bool}
+    NESTING_LEVEL        : positive := 1
+${end}
+${if regex}
+    EPC                  : positive := 4;
+    NESTING_LEVEL        : positive := 1;
+    DLY_COMP_BUFF_DEPTH  : integer := 5
+${end}
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+
+${if compare}
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(BITWIDTH-1 downto 0);
+    input_last : in std_logic_vector(NESTING_LEVEL-1 downto 0);
+    input_strb : in std_logic;
+
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(BITWIDTH-1 downto 0);
+    output_last : out std_logic_vector(NESTING_LEVEL-1 downto 0);
+    output_strb : out std_logic;
+${end}
+${if bool}
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic;
+    input_last : in std_logic_vector(NESTING_LEVEL-1 downto 0);
+    input_strb : in std_logic;
+
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic;
+    output_last : out std_logic_vector(NESTING_LEVEL-1 downto 0);
+    output_strb : out std_logic;
+${end}
+${if regex}
+    -- Stream(
+    --     Bits(8),
+    --     t=EPC,
+    --     d=NESTING_LEVEL+1,
+    --     c=8
+    -- )
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(8*EPC-1 downto 0);
+    input_last : in std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0) := (others => '0');
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '0');
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '1');
+    input_strb : in std_logic_vector(EPC-1 downto 0) := (others => '1');
+
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(8*EPC-1 downto 0);
+    output_last : out std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0);
+    output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_strb : out std_logic_vector(EPC-1 downto 0);
+
+    matcher_str_valid : out std_logic;
+    matcher_str_ready : in std_logic;
+    matcher_str_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    matcher_str_last : out std_logic_vector(EPC-1 downto 0);
+    matcher_str_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_str_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_str_strb : out std_logic_vector(EPC-1 downto 0);
+    matcher_match_valid : in std_logic;
+    matcher_match_ready : out std_logic;
+    matcher_match_data : in std_logic_vector(EPC-1 downto 0);
+    matcher_match_last : in std_logic_vector(EPC-1 downto 0);
+    matcher_match_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_match_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    matcher_match_strb : in std_logic_vector(EPC-1 downto 0);
+
+    -- Stream(
+    --     Bits(1),
+    --     t=EPC,
+    --     d=NESTING_LEVEL,
+    --     c=8
+    -- )
+    selected_valid : out std_logic;
+    selected_ready : in std_logic;
+    selected_data : out std_logic_vector(EPC-1 downto 0);
+    selected_last : out std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    selected_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    selected_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    selected_strb : out std_logic_vector(EPC-1 downto 0)
+${else}
+    -- Stream(
+    --     Bits(1),
+    --     d=NESTING_LEVEL,
+    --     c=8
+    -- )
+    selected_valid : out std_logic;
+    selected_ready : in std_logic;
+    selected_data : out std_logic;
+    selected_last : out std_logic_vector(NESTING_LEVEL-1 downto 0);
+    selected_strb : out std_logic
+${end}
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+${if compare}
+  constant THRESHOLD       : unsigned(BITWIDTH-1 downto 0) := resize(unsigned'("${constant}"), BITWIDTH);
+${end}
+${if regex}
+  -- Index constants for packing the closing lanes and lasts into a single vector.
+  constant BUFF_WIDTH      : integer := EPC*(1 + NESTING_LEVEL);
+  constant BUFF_END_STAI   : integer := 0;
+  constant BUFF_END_ENDI   : integer := EPC-1;
+  constant BUFF_LAST_STAI  : integer := EPC;
+  constant BUFF_LAST_ENDI  : integer := EPC + NESTING_LEVEL*EPC-1;
+
+  -- Lanes closing a string and the outer lasts of the input.
+  signal in_end            : std_logic_vector(EPC-1 downto 0);
+  signal in_last           : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+  signal in_strb           : std_logic_vector(EPC-1 downto 0);
+
+  signal buff_in_valid     : std_logic;
+  signal buff_in_valid_t   : std_logic;
+  signal buff_in_ready     : std_logic;
+  signal buff_in_data      : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+  signal buff_out_valid    : std_logic;
+  signal buff_out_ready    : std_logic;
+  signal buff_out_data     : std_logic_vector(BUFF_WIDTH-1 downto 0);
+${end}
+
+begin
+
+${if regex}
+  -- Every transfer is passed through and goes to the matcher, the buffer only
+  -- keeps the ones closing a string or with a last until the matcher is done.
+  in_sync: StreamSync
+    generic map (
+      NUM_INPUTS              => 1,
+      NUM_OUTPUTS             => 3
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid(0)             => input_valid,
+      in_ready(0)             => input_ready,
+      out_valid(0)            => output_valid,
+      out_valid(1)            => buff_in_valid,
+      out_valid(2)            => matcher_str_valid,
+      out_ready(0)            => output_ready,
+      out_ready(1)            => buff_in_ready,
+      out_ready(2)            => matcher_str_ready
+    );
+
+  output_data <= input_data;
+  output_last <= input_last;
+  output_stai <= input_stai;
+  output_endi <= input_endi;
+  output_strb <= input_strb;
+
+  lane_proc: process (input_last, input_stai, input_endi, input_strb) is
+  begin
+    for idx in 0 to EPC-1 loop
+      in_end(idx) <= input_last((NESTING_LEVEL+1)*idx);
+      in_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx) <= input_last((NESTING_LEVEL+1)*(idx+1)-1 downto (NESTING_LEVEL+1)*idx+1);
+      if idx < unsigned(input_stai) or idx > unsigned(input_endi) then
+        in_strb(idx) <= '0';
+      else
+        in_strb(idx) <= input_strb(idx);
+      end if;
+    end loop;
+  end process;
+
+  dly_comp_buff: StreamBuffer
+    generic map (
+      DATA_WIDTH              => BUFF_WIDTH,
+      MIN_DEPTH               => DLY_COMP_BUFF_DEPTH
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid                => buff_in_valid_t,
+      in_ready                => buff_in_ready,
+      in_data                 => buff_in_data,
+      out_valid               => buff_out_valid,
+      out_ready               => buff_out_ready,
+      out_data                => buff_out_data
+    );
+
+  buff_in_data(BUFF_END_ENDI downto BUFF_END_STAI)   <= in_end;
+  buff_in_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI) <= in_last;
+  buff_in_valid_t <= buff_in_valid and (or_reduce(in_end) or or_reduce(in_last));
+
+  matcher_str_data <= input_data;
+  matcher_str_strb <= in_strb;
+  matcher_str_last <= in_end;
+  matcher_str_stai <= (others => '0');
+  matcher_str_endi <= (others => '1');
+
+  select_proc: process (clk) is
+    -- Buffer input holding register.
+    variable b_end  : std_logic_vector(EPC-1 downto 0);
+    variable b_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable bv     : std_logic := '0';
+    variable br     : std_logic := '0';
+
+    -- Matcher input holding register.
+    variable m_data : std_logic_vector(EPC-1 downto 0);
+    variable mv     : std_logic := '0';
+    variable mr     : std_logic := '0';
+
+    -- Output holding register.
+    variable od_data : std_logic_vector(EPC-1 downto 0);
+    variable od_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable od_strb : std_logic_vector(EPC-1 downto 0);
+    variable ov      : std_logic := '0';
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch buffer input holding register.
+      if to_x01(br) = '1' then
+        bv     := buff_out_valid;
+        b_end  := buff_out_data(BUFF_END_ENDI downto BUFF_END_STAI);
+        b_last := buff_out_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI);
+      end if;
+
+      -- Latch matcher input holding register.
+      if to_x01(mr) = '1' then
+        mv     := matcher_match_valid;
+        m_data := matcher_match_data;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(selected_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Transfers closing a string wait for the result of the matcher.
+      if to_x01(bv) = '1' and to_x01(ov) /= '1' and (or_reduce(b_end) = '0' or to_x01(mv) = '1') then
+        od_data := m_data and b_end;
+        od_last := b_last;
+        od_strb := b_end;
+
+        if or_reduce(b_end) = '1' then
+          mv := '0';
+        end if;
+        bv := '0';
+        ov := '1';
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        bv := '0';
+        mv := '0';
+        ov := '0';
+      end if;
+
+      -- Forward output holding register.
+      selected_valid <= to_x01(ov);
+      selected_data  <= od_data;
+      selected_last  <= od_last;
+      selected_strb  <= od_strb;
+      selected_stai  <= (others => '0');
+      selected_endi  <= (others => '1');
+
+      br := not bv and not rst;
+      buff_out_ready <= br;
+      mr := not mv and not rst;
+      matcher_match_ready <= mr;
+    end if;
+  end process;
+${else}
+  -- Every value is passed through and tested at the same time.
+  in_sync: StreamSync
+    generic map (
+      NUM_INPUTS              => 1,
+      NUM_OUTPUTS             => 2
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid(0)             => input_valid,
+      in_ready(0)             => input_ready,
+      out_valid(0)            => output_valid,
+      out_valid(1)            => selected_valid,
+      out_ready(0)            => output_ready,
+      out_ready(1)            => selected_ready
+    );
+
+  output_data <= input_data;
+  output_last <= input_last;
+  output_strb <= input_strb;
+
+${if compare}
+  selected_data <= '1' when unsigned(input_data) ${operator} THRESHOLD else '0';
+${end}
+${if bool}
+  selected_data <= '1' when input_data = ${expected} else '0';
+${end}
+  selected_last <= input_last;
+  selected_strb <= input_strb;
+${end}
+end architecture;
@@ -8,10 +8,26 @@ use work.${project_name}.all;
 use work.UtilInt_pkg.all;
 use work.Stream_pkg.all;
 
+${if key_name}
+-- The matcher is a pattern for all keys of a map. The name of every matched key
+-- is emitted as one element of KEY_NAME_LENGTH bytes with the first character
+-- in the lowest bits, padded with zeros or truncated. The name is taken when
+-- the transfer closing the key is latched, the generator only uses this with
+-- an EPC of 1 so every transfer closes at most one key.
+
+${end}
+${if presence}
+-- The match results of the matcher are copied to the key presence component
+-- of the record.
+
+${end}
 entity ${namespace}_0_${comp_name}_com is
   generic (
     EPC : positive := 4;
     OUTER_NESTING_LEVEL : positive := 2;
+${if key_name}
+    KEY_NAME_LENGTH : positive := 16;
+${end}
     DLY_COMP_BUFF_DEPTH  : integer := 5
   );
   port (
@@ -38,6 +54,22 @@ entity ${namespace}_0_${comp_name}_com is
     matcher_match_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     matcher_match_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
     matcher_match_strb : in std_logic_vector(EPC-1 downto 0);
+${if presence}
+    presence_valid : out std_logic;
+    presence_ready : in std_logic;
+    presence_data : out std_logic_vector(EPC-1 downto 0);
+    presence_last : out std_logic_vector(EPC-1 downto 0);
+    presence_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    presence_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    presence_strb : out std_logic_vector(EPC-1 downto 0);
+${end}
+${if key_name}
+    key_name_valid : out std_logic;
+    key_name_ready : in std_logic;
+    key_name_data : out std_logic_vector(8*KEY_NAME_LENGTH-1 downto 0);
+    key_name_last : out std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0);
+    key_name_strb : out std_logic;
+${end}
     output_valid : out std_logic;
     output_ready : in std_logic;
     output_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
@@ -70,6 +102,9 @@ architecture behav of ${namespace}_0_${comp_name}_com is
   signal matcher_slice_in        : std_logic_vector(2*EPC-1 downto 0);
   signal matcher_slice_out       : std_logic_vector(2*EPC-1 downto 0);
 
+  signal matcher_in_valid        : std_logic;
+  signal matcher_in_ready        : std_logic;
+
   signal matcher_match_valid_s   : std_logic;
   signal matcher_match_ready_s   : std_logic;
   signal matcher_match_strb_s    : std_logic_vector(EPC-1 downto 0);
@@ -103,6 +138,34 @@ architecture behav of ${namespace}_0_${comp_name}_com is
         out_data                => buff_out_data
       );
 
+${if presence}
+      -- Every match result goes to the filter and to the key presence component.
+      match_sync: StreamSync
+      generic map (
+        NUM_INPUTS              => 1,
+        NUM_OUTPUTS             => 2
+      )
+      port map (
+        clk                     => clk,
+        reset                   => rst,
+        in_valid(0)             => matcher_match_valid,
+        in_ready(0)             => matcher_match_ready,
+        out_valid(0)            => matcher_in_valid,
+        out_valid(1)            => presence_valid,
+        out_ready(0)            => matcher_in_ready,
+        out_ready(1)            => presence_ready
+      );
+
+      presence_data <= matcher_match_data;
+      presence_last <= matcher_match_last;
+      presence_stai <= matcher_match_stai;
+      presence_endi <= matcher_match_endi;
+      presence_strb <= matcher_match_strb;
+${else}
+      matcher_in_valid    <= matcher_match_valid;
+      matcher_match_ready <= matcher_in_ready;
+${end}
+
       matcher_slice_in(EPC-1 downto 0)     <= matcher_match_data;
       matcher_slice_in(2*EPC-1 downto EPC) <= matcher_match_strb;
 
@@ -116,8 +179,8 @@ architecture behav of ${namespace}_0_${comp_name}_com is
       port map (
         clk                     => clk,
         reset                   => rst,
-        in_valid                => matcher_match_valid,
-        in_ready                => matcher_match_ready,
+        in_valid                => matcher_in_valid,
+        in_ready                => matcher_in_ready,
         in_data                 => matcher_slice_in,
         out_valid               => matcher_match_valid_s,
         out_ready               => matcher_match_ready_s,
@@ -171,7 +234,12 @@ architecture behav of ${namespace}_0_${comp_name}_com is
       matcher_str_last <= last and (not in_tag_f);
       matcher_str_endi <= (others => '1');
 
+${if key_name}
+      -- Transfers with only key characters are kept for the key names.
+      buff_in_valid_t <= buff_in_valid;
+${else}
       buff_in_valid_t <= buff_in_valid and (or_reduce(in_tag_f) or or_reduce(input_last));
+${end}
     end process;
 
     filter_proc: process (clk) is
@@ -208,6 +276,21 @@ architecture behav of ${namespace}_0_${comp_name}_com is
 
       variable outer_last : std_logic;
       variable match_last : std_logic;
+${if key_name}
+
+      -- Name of the key being received and of the last closed key.
+      variable kb     : std_logic_vector(8*KEY_NAME_LENGTH-1 downto 0);
+      variable kl     : natural range 0 to KEY_NAME_LENGTH;
+      variable kc     : std_logic := '1';
+      variable kdone  : std_logic_vector(8*KEY_NAME_LENGTH-1 downto 0);
+
+      -- Key name output holding register.
+      variable kod    : std_logic_vector(8*KEY_NAME_LENGTH-1 downto 0);
+      variable kolast : std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0);
+      variable kostrb : std_logic;
+      variable kov    : std_logic := '0';
+      variable klast  : std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0);
+${end}
   
       -- Enumeration type for our state machine.
       type state_t is (STATE_IDLE,
@@ -231,6 +314,33 @@ architecture behav of ${namespace}_0_${comp_name}_com is
             id(idx).strb  := buff_out_data(BUFF_STRB_STAI+idx);
             id(idx).last  := buff_out_data(BUFF_LAST_STAI+(OUTER_NESTING_LEVEL+1)*idx+OUTER_NESTING_LEVEL downto BUFF_LAST_STAI+(OUTER_NESTING_LEVEL+1)*idx);
           end loop;
+${if key_name}
+
+          -- Collect the characters of the keys.
+          if to_x01(bv) = '1' then
+            for idx in 0 to EPC-1 loop
+              if id(idx).tag = '0' and id(idx).strb = '1' then
+                if kc = '1' then
+                  kb := (others => '0');
+                  kl := 0;
+                  kc := '0';
+                end if;
+                if kl < KEY_NAME_LENGTH then
+                  kb(8*kl+7 downto 8*kl) := id(idx).data;
+                  kl := kl + 1;
+                end if;
+              end if;
+              if id(idx).tag = '0' and id(idx).last(0) = '1' then
+                if kc = '1' then
+                  kdone := (others => '0');
+                else
+                  kdone := kb;
+                end if;
+                kc := '1';
+              end if;
+            end loop;
+          end if;
+${end}
         end if;
 
         if to_x01(mr) = '1' then
@@ -252,11 +362,24 @@ architecture behav of ${namespace}_0_${comp_name}_com is
           end if;
           ov         := '0';
         end if;
+${if key_name}
+
+        if to_x01(key_name_ready) = '1' then
+          if kov = '1' then
+            kolast := (others => '0');
+            kostrb := '0';
+          end if;
+          kov := '0';
+        end if;
+${end}
         
         -- Do processing when both registers are ready.
-        if to_x01(bv) = '1' and to_x01(ov) = '0' then
+        if to_x01(bv) = '1' and to_x01(ov) = '0'${if key_name} and to_x01(kov) = '0'${end} then
           outer_last := '0';
           bv         := '0';
+${if key_name}
+          klast      := (others => '0');
+${end}
           for idx in 0 to EPC-1 loop
   
             -- Default behavior.
@@ -267,6 +390,9 @@ architecture behav of ${namespace}_0_${comp_name}_com is
             if or_reduce(id(idx).last(OUTER_NESTING_LEVEL downto 1)) = '1' then
               outer_last := '1';
             end if;
+${if key_name}
+            klast := klast or id(idx).last(OUTER_NESTING_LEVEL downto 1);
+${end}
 
             case state is
               when STATE_IDLE =>
@@ -287,6 +413,11 @@ architecture behav of ${namespace}_0_${comp_name}_com is
                     if to_x01(id(idx).match_strb) = '1' then
                       if to_x01(id(idx).match) = '1' then
                         state := STATE_MATCH;
+${if key_name}
+                        kod    := kdone;
+                        kostrb := '1';
+                        kov    := '1';
+${end}
                       else
                         state := STATE_DROP;
                       end if;
@@ -311,6 +442,14 @@ architecture behav of ${namespace}_0_${comp_name}_com is
                 end if;
             end case;
           end loop;
+${if key_name}
+
+          -- Close the outer dimensions of the key names once the transfer is done.
+          if bv = '0' and outer_last = '1' then
+            kolast := klast;
+            kov    := '1';
+          end if;
+${end}
         end if;
   
         -- Handle reset.
@@ -324,6 +463,12 @@ architecture behav of ${namespace}_0_${comp_name}_com is
             od(idx).last(0) := '0';
             od(idx).strb    := '0';
           end loop;
+${if key_name}
+          kc         := '1';
+          kov        := '0';
+          kolast     := (others => '0');
+          kostrb     := '0';
+${end}
         end if;
   
         -- Forward output holding register.
@@ -339,6 +484,12 @@ architecture behav of ${namespace}_0_${comp_name}_com is
           output_endi <= (others => '1');
           output_strb(idx) <= od(idx).strb;
         end loop;
+${if key_name}
+        key_name_valid <= to_x01(kov);
+        key_name_data <= kod;
+        key_name_last <= kolast;
+        key_name_strb <= kostrb;
+${end}
       end if;
     end process;
 end behav;
\ No newline at end of file
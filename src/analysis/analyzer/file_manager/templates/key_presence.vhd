@@ -0,0 +1,247 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+use work.Stream_pkg.all;
+
+-- Emits for every record a vector with a bit for every key telling if the
+-- key was present in the record. The key parser of every key sends a copy of
+-- its match results, the match bits of the lanes closing the keys are
+-- collected until the record is closed.
+--
+-- Key bits:
+${for key in keys}
+--   ${key.bit}: ${key.key}
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC                  : positive := 4;
+    OUTER_NESTING_LEVEL  : positive := 2;
+    NUM_KEYS             : positive := 1;
+    DLY_COMP_BUFF_DEPTH  : integer := 5
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(EPC*${bit_width}+EPC-1 downto 0);
+    input_last : in std_logic_vector(((OUTER_NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_strb : in std_logic_vector(EPC-1 downto 0);
+${for key in keys}
+    match_${key.bit}_valid : in std_logic;
+    match_${key.bit}_ready : out std_logic;
+    match_${key.bit}_data : in std_logic_vector(EPC-1 downto 0);
+    match_${key.bit}_last : in std_logic_vector(EPC-1 downto 0);
+    match_${key.bit}_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    match_${key.bit}_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    match_${key.bit}_strb : in std_logic_vector(EPC-1 downto 0);
+${end}
+
+    -- Stream(
+    --     Bits(NUM_KEYS),
+    --     t=EPC,
+    --     d=OUTER_NESTING_LEVEL-1,
+    --     c=8
+    -- )
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(NUM_KEYS*EPC-1 downto 0);
+    output_last : out std_logic_vector((OUTER_NESTING_LEVEL-1)*EPC-1 downto 0);
+    output_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    output_strb : out std_logic_vector(EPC-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+  constant IN_TAG_STAI     : integer := EPC*8;
+  constant IN_TAG_ENDI     : integer := EPC*8+EPC-1;
+
+  -- Index constants for packing the closing lanes and lasts into a single vector.
+  constant BUFF_WIDTH      : integer := EPC*(1 + OUTER_NESTING_LEVEL);
+  constant BUFF_END_STAI   : integer := 0;
+  constant BUFF_END_ENDI   : integer := EPC-1;
+  constant BUFF_LAST_STAI  : integer := EPC;
+  constant BUFF_LAST_ENDI  : integer := EPC + OUTER_NESTING_LEVEL*EPC-1;
+
+  signal key_end           : std_logic_vector(EPC-1 downto 0);
+
+  -- Match results of all keys, the bits of a lane are next to each other.
+  signal match_valid       : std_logic;
+  signal match_ready       : std_logic;
+  signal match_data        : std_logic_vector(NUM_KEYS*EPC-1 downto 0);
+  signal key_last          : std_logic_vector(OUTER_NESTING_LEVEL*EPC-1 downto 0);
+
+  signal buff_in_valid     : std_logic;
+  signal buff_in_valid_t   : std_logic;
+  signal buff_in_ready     : std_logic;
+  signal buff_in_data      : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+  signal buff_out_valid    : std_logic;
+  signal buff_out_ready    : std_logic;
+  signal buff_out_data     : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+begin
+
+  -- The buffer only keeps the transfers closing a key or a record until the
+  -- match results of the key parsers are there.
+  dly_comp_buff: StreamBuffer
+    generic map (
+      DATA_WIDTH              => BUFF_WIDTH,
+      MIN_DEPTH               => DLY_COMP_BUFF_DEPTH
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid                => buff_in_valid_t,
+      in_ready                => buff_in_ready,
+      in_data                 => buff_in_data,
+      out_valid               => buff_out_valid,
+      out_ready               => buff_out_ready,
+      out_data                => buff_out_data
+    );
+
+  buff_in_valid <= input_valid;
+  input_ready   <= buff_in_ready;
+
+  -- The key parsers see the same transfers, so their match results are taken
+  -- together.
+  match_sync: StreamSync
+    generic map (
+      NUM_INPUTS              => NUM_KEYS,
+      NUM_OUTPUTS             => 1
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+${for key in keys}
+      in_valid(${key.bit})             => match_${key.bit}_valid,
+      in_ready(${key.bit})             => match_${key.bit}_ready,
+${end}
+      out_valid(0)            => match_valid,
+      out_ready(0)            => match_ready
+    );
+
+${for key in keys}
+  match_${key.bit}_lanes: for idx in 0 to EPC-1 generate
+    match_data(NUM_KEYS*idx+${key.bit}) <= match_${key.bit}_data(idx);
+  end generate;
+${end}
+
+  input_interfacing: process (input_data, input_last) is
+    variable last     : std_logic_vector(EPC-1 downto 0);
+    variable in_tag_f : std_logic_vector(EPC-1 downto 0);
+  begin
+    in_tag_f := input_data(IN_TAG_ENDI downto IN_TAG_STAI);
+
+    for idx in 0 to EPC-1 loop
+      last(idx) := input_last((OUTER_NESTING_LEVEL+1)*idx);
+      key_last(OUTER_NESTING_LEVEL*(idx+1)-1 downto OUTER_NESTING_LEVEL*idx) <= input_last((OUTER_NESTING_LEVEL+1)*(idx+1)-1 downto (OUTER_NESTING_LEVEL+1)*idx+1);
+    end loop;
+
+    key_end <= last and (not in_tag_f);
+  end process;
+
+  buff_in_data(BUFF_END_ENDI downto BUFF_END_STAI)   <= key_end;
+  buff_in_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI) <= key_last;
+  buff_in_valid_t <= buff_in_valid and (or_reduce(key_end) or or_reduce(key_last));
+
+  presence_proc: process (clk) is
+    -- Buffer input holding register.
+    variable b_end  : std_logic_vector(EPC-1 downto 0);
+    variable b_last : std_logic_vector(OUTER_NESTING_LEVEL*EPC-1 downto 0);
+    variable bv     : std_logic := '0';
+    variable br     : std_logic := '0';
+
+    -- Match input holding register.
+    variable m_data : std_logic_vector(NUM_KEYS*EPC-1 downto 0);
+    variable mv     : std_logic := '0';
+    variable mr     : std_logic := '0';
+
+    -- Output holding register.
+    variable od_data : std_logic_vector(NUM_KEYS*EPC-1 downto 0);
+    variable od_last : std_logic_vector((OUTER_NESTING_LEVEL-1)*EPC-1 downto 0);
+    variable od_strb : std_logic_vector(EPC-1 downto 0);
+    variable ov      : std_logic := '0';
+
+    -- Keys seen in the current record.
+    variable presence : std_logic_vector(NUM_KEYS-1 downto 0) := (others => '0');
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch buffer input holding register.
+      if to_x01(br) = '1' then
+        bv     := buff_out_valid;
+        b_end  := buff_out_data(BUFF_END_ENDI downto BUFF_END_STAI);
+        b_last := buff_out_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI);
+      end if;
+
+      -- Latch match input holding register.
+      if to_x01(mr) = '1' then
+        mv     := match_valid;
+        m_data := match_data;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Transfers closing a key wait for the match results.
+      if to_x01(bv) = '1' and to_x01(ov) /= '1' and (or_reduce(b_end) = '0' or to_x01(mv) = '1') then
+        for idx in 0 to EPC-1 loop
+          od_data(NUM_KEYS*(idx+1)-1 downto NUM_KEYS*idx) := (others => '0');
+          od_last((OUTER_NESTING_LEVEL-1)*(idx+1)-1 downto (OUTER_NESTING_LEVEL-1)*idx) := b_last(OUTER_NESTING_LEVEL*(idx+1)-1 downto OUTER_NESTING_LEVEL*idx+1);
+          od_strb(idx) := '0';
+
+          if b_end(idx) = '1' then
+            presence := presence or m_data(NUM_KEYS*(idx+1)-1 downto NUM_KEYS*idx);
+          end if;
+
+          -- The record is closed, emit the keys seen in it.
+          if b_last(OUTER_NESTING_LEVEL*idx) = '1' then
+            od_data(NUM_KEYS*(idx+1)-1 downto NUM_KEYS*idx) := presence;
+            od_strb(idx) := '1';
+            presence := (others => '0');
+          end if;
+        end loop;
+
+        if or_reduce(b_end) = '1' then
+          mv := '0';
+        end if;
+        bv := '0';
+        ov := '1';
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        bv       := '0';
+        mv       := '0';
+        ov       := '0';
+        presence := (others => '0');
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      output_data  <= od_data;
+      output_last  <= od_last;
+      output_strb  <= od_strb;
+      output_stai  <= (others => '0');
+      output_endi  <= (others => '1');
+
+      br := not bv and not rst;
+      buff_out_ready <= br;
+      mr := not mv and not rst;
+      match_ready <= mr;
+    end if;
+  end process;
+end architecture;
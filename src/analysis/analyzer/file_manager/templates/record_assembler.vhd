@@ -0,0 +1,116 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.all;
+
+library work;
+use work.${project_name}.all;
+
+-- Joins the values of keys of a record into one element per record. Every
+-- field stream is consumed up to the transfer closing the record or an outer
+-- sequence, the value in it is held until all fields got there. Fields without
+-- a value in the record are zero.
+--
+-- Group fields, from the lowest bits:
+${for field in fields}
+--   ${field.name} (${field.high} downto ${field.low}): ${field.key}
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    OUTER_NESTING_LEVEL  : positive := 2
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+${for field in fields}
+    field_${field.name}_valid : in std_logic;
+    field_${field.name}_ready : out std_logic;
+    field_${field.name}_data : in ${if field.boolean}std_logic${else}std_logic_vector(${field.bits}-1 downto 0)${end};
+    field_${field.name}_last : in std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0);
+    field_${field.name}_strb : in std_logic;
+${end}
+
+    -- Stream(
+    --     Group(...),
+    --     t=1,
+    --     d=OUTER_NESTING_LEVEL-1,
+    --     c=2
+    -- )
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(${group_bits}-1 downto 0);
+    output_last : out std_logic_vector(OUTER_NESTING_LEVEL-2 downto 0);
+    output_strb : out std_logic
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+begin
+
+  assembler_proc: process (clk) is
+    -- Value and closing lasts of every field, done once the field closed the record or a sequence
+    variable data : std_logic_vector(${group_bits}-1 downto 0) := (others => '0');
+    variable last : std_logic_vector(OUTER_NESTING_LEVEL-1 downto 0) := (others => '0');
+    variable done : std_logic_vector(${num_fields}-1 downto 0) := (others => '0');
+
+    -- Output holding register.
+    variable od : std_logic_vector(${group_bits}-1 downto 0);
+    variable ol : std_logic_vector(OUTER_NESTING_LEVEL-2 downto 0);
+    variable os : std_logic;
+    variable ov : std_logic := '0';
+
+  begin
+    if rising_edge(clk) then
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Take the transfers of the fields which did not close yet.
+${for field in fields}
+      if to_x01(field_${field.name}_valid) = '1' and done(${field.index}) = '0' then
+        if to_x01(field_${field.name}_strb) = '1' then
+          ${if field.boolean}data(${field.low}) := field_${field.name}_data;${else}data(${field.high} downto ${field.low}) := field_${field.name}_data;${end}
+        end if;
+        if or_reduce(field_${field.name}_last) = '1' then
+          last := last or field_${field.name}_last;
+          done(${field.index}) := '1';
+        end if;
+      end if;
+${end}
+
+      -- Emit the group once every field closed, a record is only closed by the innermost last.
+      if and_reduce(done) = '1' and to_x01(ov) /= '1' then
+        od := data;
+        ol := last(OUTER_NESTING_LEVEL-1 downto 1);
+        os := last(0);
+        ov := '1';
+
+        data := (others => '0');
+        last := (others => '0');
+        done := (others => '0');
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        data := (others => '0');
+        last := (others => '0');
+        done := (others => '0');
+        ov   := '0';
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      output_data  <= od;
+      output_last  <= ol;
+      output_strb  <= os;
+
+      -- Fields which closed wait for the others.
+${for field in fields}
+      field_${field.name}_ready <= not done(${field.index}) and not rst;
+${end}
+    end if;
+  end process;
+end architecture;
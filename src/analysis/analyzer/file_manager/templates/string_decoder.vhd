@@ -0,0 +1,499 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+
+-- Parses JSON strings and decodes the escape sequences in them into UTF-8
+-- bytes. Invalid escape sequences and unpaired surrogates are replaced by
+-- U+FFFD.
+${if decoded}
+-- Every string is emitted as a sequence of bytes.
+${end}
+${if fixed}
+-- Every string is emitted as one element of LENGTH bytes with the first byte
+-- in the lowest bits. Shorter strings are padded with zeros, longer strings
+-- are truncated.
+${end}
+${if length_prefixed}
+-- Every string is emitted as its length in 2 bytes (big-endian) followed by
+-- its bytes. Longer strings are truncated to LENGTH bytes.
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+      EPC                    : positive := 1;
+${if decoded}
+      NESTING_LEVEL          : positive := 1
+${else}
+      NESTING_LEVEL          : positive := 1;
+      LENGTH                 : positive := 16
+${end}
+      );
+  port (
+      clk                   : in  std_logic;
+      rst                   : in  std_logic;
+
+      -- Stream(
+      --     Bits(8),
+      --     t=EPC,
+      --     d=NESTING_LEVEL+1,
+      --     c=8
+      -- )
+      input_valid              : in  std_logic;
+      input_ready              : out std_logic;
+      input_data               : in  std_logic_vector(8*EPC-1 downto 0);
+      input_last               : in  std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0) := (others => '0');
+      input_stai               : in  std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '0');
+      input_endi               : in  std_logic_vector(log2ceil(EPC)-1 downto 0) := (others => '1');
+      input_strb               : in  std_logic_vector(EPC-1 downto 0) := (others => '1');
+
+${if fixed}
+      -- Stream(
+      --     Bits(8*LENGTH),
+      --     d=NESTING_LEVEL,
+      --     c=8
+      -- )
+      output_valid             : out std_logic;
+      output_ready             : in  std_logic;
+      output_data              : out std_logic_vector(8*LENGTH-1 downto 0);
+      output_strb              : out std_logic;
+      output_last              : out std_logic_vector(NESTING_LEVEL-1 downto 0)
+${else}
+      -- Stream(
+      --     Bits(8),
+      --     t=EPC,
+${if decoded}
+      --     d=NESTING_LEVEL+1,
+${else}
+      --     d=NESTING_LEVEL,
+${end}
+      --     c=8
+      -- )
+      output_valid             : out std_logic;
+      output_ready             : in  std_logic;
+      output_data              : out std_logic_vector(8*EPC-1 downto 0);
+${if decoded}
+      output_last              : out std_logic_vector((NESTING_LEVEL+1)*EPC-1 downto 0);
+${else}
+      output_last              : out std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+${end}
+      output_stai              : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+      output_endi              : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+      output_strb              : out std_logic_vector(EPC-1 downto 0)
+${end}
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+begin
+  clk_proc: process (clk) is
+${if decoded}
+    -- Bytes with the end of the string in the innermost last.
+    constant OUT_LANES : positive := EPC;
+    constant ODW       : positive := 8;
+    constant OLW       : positive := NESTING_LEVEL+1;
+    -- A lane produces at most two code points and a last.
+    constant PEND_SIZE : positive := 8;
+${end}
+${if fixed}
+    -- One string per transfer.
+    constant OUT_LANES : positive := 1;
+    constant ODW       : positive := 8*LENGTH;
+    constant OLW       : positive := NESTING_LEVEL;
+    -- A lane produces at most a string and a last.
+    constant PEND_SIZE : positive := 2;
+${end}
+${if length_prefixed}
+    -- Length and bytes of the strings in one sequence.
+    constant OUT_LANES : positive := EPC;
+    constant ODW       : positive := 8;
+    constant OLW       : positive := NESTING_LEVEL;
+    -- A lane produces at most a complete string and a last.
+    constant PEND_SIZE : positive := LENGTH+3;
+${end}
+
+    -- Input holding register.
+    type in_type is record
+      data  : std_logic_vector(7 downto 0);
+      last  : std_logic_vector(NESTING_LEVEL-1 downto 0);
+      strb  : std_logic;
+    end record;
+
+    type in_array is array (natural range <>) of in_type;
+    variable id   : in_array(0 to EPC-1);
+    variable iv   : std_logic := '0';
+    variable ir   : std_logic := '0';
+    -- Next lane of the input holding register to decode.
+    variable ipos : natural range 0 to EPC-1 := 0;
+
+    -- Output elements, decoding a lane can produce more elements than fit in
+    -- the output so they wait in the pending queue.
+    type item_type is record
+      data  : std_logic_vector(ODW-1 downto 0);
+      last  : std_logic_vector(OLW-1 downto 0);
+      strb  : std_logic;
+    end record;
+
+    type item_array is array (natural range <>) of item_type;
+    variable pend : item_array(0 to PEND_SIZE-1);
+    variable ph   : natural range 0 to PEND_SIZE := 0;
+    variable pn   : natural range 0 to PEND_SIZE := 0;
+
+    -- Output holding register.
+    variable od   : item_array(0 to OUT_LANES-1);
+    variable oc   : natural range 0 to OUT_LANES := 0;
+    variable ov   : std_logic := '0';
+    variable endi : unsigned(log2ceil(EPC)-1 downto 0);
+
+${if !decoded}
+    -- Bytes of the current string.
+    type byte_array is array (natural range <>) of std_logic_vector(7 downto 0);
+    variable buf  : byte_array(0 to LENGTH-1);
+    variable blen : natural range 0 to LENGTH := 0;
+
+${end}
+    -- Enumeration type for our state machine.
+    type state_t is (STATE_IDLE,
+                     STATE_STRING,
+                     STATE_ESCAPE,
+                     STATE_UNICODE,
+                     STATE_SURROGATE);
+
+    -- State variable
+    variable state     : state_t := STATE_IDLE;
+
+    -- Code unit of a \u escape sequence.
+    variable hex_count : natural range 0 to 4 := 0;
+    variable code      : unsigned(15 downto 0);
+
+    -- High surrogate waiting for its low surrogate.
+    variable high      : unsigned(9 downto 0);
+    variable has_high  : boolean := false;
+
+    -- Value of a hexadecimal digit, -1 if the character is not one.
+    function hex_value(c : std_logic_vector(7 downto 0)) return integer is
+      variable v : integer;
+    begin
+      v := to_integer(unsigned(c));
+      if v >= 16#30# and v <= 16#39# then
+        return v - 16#30#;
+      elsif v >= 16#41# and v <= 16#46# then
+        return v - 16#41# + 10;
+      elsif v >= 16#61# and v <= 16#66# then
+        return v - 16#61# + 10;
+      else
+        return -1;
+      end if;
+    end function;
+
+    procedure push_item(data : std_logic_vector(ODW-1 downto 0); last : std_logic_vector(OLW-1 downto 0); strb : std_logic) is
+    begin
+      pend(ph+pn).data := data;
+      pend(ph+pn).last := last;
+      pend(ph+pn).strb := strb;
+      pn := pn + 1;
+    end procedure;
+
+    procedure push_byte(b : std_logic_vector(7 downto 0)) is
+    begin
+${if decoded}
+      push_item(b, (others => '0'), '1');
+${else}
+      if blen < LENGTH then
+        buf(blen) := b;
+        blen      := blen + 1;
+      end if;
+${end}
+    end procedure;
+
+    -- Encode a code point in UTF-8.
+    procedure push_code_point(point : unsigned(20 downto 0)) is
+    begin
+      if point < 16#80# then
+        push_byte(std_logic_vector(point(7 downto 0)));
+      elsif point < 16#800# then
+        push_byte("110" & std_logic_vector(point(10 downto 6)));
+        push_byte("10" & std_logic_vector(point(5 downto 0)));
+      elsif point < 16#10000# then
+        push_byte("1110" & std_logic_vector(point(15 downto 12)));
+        push_byte("10" & std_logic_vector(point(11 downto 6)));
+        push_byte("10" & std_logic_vector(point(5 downto 0)));
+      else
+        push_byte("11110" & std_logic_vector(point(20 downto 18)));
+        push_byte("10" & std_logic_vector(point(17 downto 12)));
+        push_byte("10" & std_logic_vector(point(11 downto 6)));
+        push_byte("10" & std_logic_vector(point(5 downto 0)));
+      end if;
+    end procedure;
+
+    -- U+FFFD replacement character.
+    procedure push_replacement is
+    begin
+      push_byte(X"EF");
+      push_byte(X"BF");
+      push_byte(X"BD");
+    end procedure;
+
+    procedure close_string is
+${if fixed}
+      variable item : std_logic_vector(ODW-1 downto 0);
+${end}
+${if length_prefixed}
+      variable len  : unsigned(15 downto 0);
+${end}
+    begin
+${if decoded}
+      -- Mark the last byte, or emit an empty element if it was already sent.
+      if pn > 0 then
+        pend(ph+pn-1).last(0) := '1';
+      else
+        push_item((others => '0'), (0 => '1', others => '0'), '0');
+      end if;
+${end}
+${if fixed}
+      item := (others => '0');
+      for idx in 0 to LENGTH-1 loop
+        if idx < blen then
+          item(8*idx+7 downto 8*idx) := buf(idx);
+        end if;
+      end loop;
+      push_item(item, (others => '0'), '1');
+      blen := 0;
+${end}
+${if length_prefixed}
+      len := to_unsigned(blen, 16);
+      push_item(std_logic_vector(len(15 downto 8)), (others => '0'), '1');
+      push_item(std_logic_vector(len(7 downto 0)), (others => '0'), '1');
+      for idx in 0 to LENGTH-1 loop
+        if idx < blen then
+          push_item(buf(idx), (others => '0'), '1');
+        end if;
+      end loop;
+      blen := 0;
+${end}
+    end procedure;
+
+    procedure decode_char(c : std_logic_vector(7 downto 0)) is
+      -- The character does not belong to an escape sequence and is handled
+      -- as part of the string.
+      variable in_string : boolean := false;
+      variable hex       : integer;
+    begin
+      case state is
+        when STATE_IDLE =>
+          if c = X"22" then -- '"'
+            state := STATE_STRING;
+          end if;
+
+        when STATE_STRING =>
+          in_string := true;
+
+        when STATE_ESCAPE =>
+          -- A high surrogate has to be followed by \u.
+          if has_high and c /= X"75" then
+            push_replacement;
+            has_high := false;
+          end if;
+
+          state := STATE_STRING;
+          case c is
+            when X"22" | X"5C" | X"2F" => -- '"' '\' '/'
+              push_byte(c);
+            when X"62" => -- 'b'
+              push_byte(X"08");
+            when X"66" => -- 'f'
+              push_byte(X"0C");
+            when X"6E" => -- 'n'
+              push_byte(X"0A");
+            when X"72" => -- 'r'
+              push_byte(X"0D");
+            when X"74" => -- 't'
+              push_byte(X"09");
+            when X"75" => -- 'u'
+              state     := STATE_UNICODE;
+              hex_count := 0;
+              code      := (others => '0');
+            when others =>
+              push_replacement;
+          end case;
+
+        when STATE_UNICODE =>
+          hex := hex_value(c);
+          if hex < 0 then
+            push_replacement;
+            has_high  := false;
+            state     := STATE_STRING;
+            in_string := true;
+          else
+            code      := code(11 downto 0) & to_unsigned(hex, 4);
+            hex_count := hex_count + 1;
+
+            if hex_count = 4 then
+              state := STATE_STRING;
+              if code >= 16#DC00# and code <= 16#DFFF# then
+                -- Low surrogate, completes the pending high surrogate.
+                if has_high then
+                  push_code_point(to_unsigned(16#10000#, 21) + (high & code(9 downto 0)));
+                else
+                  push_replacement;
+                end if;
+                has_high := false;
+              else
+                if has_high then
+                  push_replacement;
+                  has_high := false;
+                end if;
+
+                if code >= 16#D800# and code <= 16#DBFF# then
+                  high     := code(9 downto 0);
+                  has_high := true;
+                  state    := STATE_SURROGATE;
+                else
+                  push_code_point(resize(code, 21));
+                end if;
+              end if;
+            end if;
+          end if;
+
+        when STATE_SURROGATE =>
+          if c = X"5C" then -- '\'
+            state := STATE_ESCAPE;
+          else
+            push_replacement;
+            has_high  := false;
+            state     := STATE_STRING;
+            in_string := true;
+          end if;
+      end case;
+
+      if in_string then
+        case c is
+          when X"22" => -- '"'
+            close_string;
+            state := STATE_IDLE;
+          when X"5C" => -- '\'
+            state := STATE_ESCAPE;
+          when others =>
+            push_byte(c);
+        end case;
+      end if;
+    end procedure;
+
+  begin
+    if rising_edge(clk) then
+
+      -- Latch input holding register if we said we would.
+      if to_x01(ir) = '1' then
+        iv   := input_valid;
+        ipos := 0;
+        for idx in 0 to EPC-1 loop
+          id(idx).data := input_data(8*idx+7 downto 8*idx);
+          id(idx).last := input_last((NESTING_LEVEL+1)*(idx+1)-1 downto (NESTING_LEVEL+1)*idx+1);
+          if idx < unsigned(input_stai) then
+            id(idx).strb := '0';
+          elsif idx > unsigned(input_endi) then
+            id(idx).strb := '0';
+          else
+            id(idx).strb := input_strb(idx);
+          end if;
+        end loop;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      -- Fill the output holding register with the pending elements, decode
+      -- the next input lane when there are none left.
+      if to_x01(ov) /= '1' then
+        oc := 0;
+        for idx in 0 to OUT_LANES-1 loop
+          od(idx).data := (others => '0');
+          od(idx).last := (others => '0');
+          od(idx).strb := '0';
+        end loop;
+
+        for step in 0 to OUT_LANES+EPC loop
+          if pn > 0 then
+            exit when oc = OUT_LANES;
+            od(oc) := pend(ph);
+            oc     := oc + 1;
+            ph     := ph + 1;
+            pn     := pn - 1;
+          elsif to_x01(iv) = '1' then
+            ph := 0;
+
+            -- Element-wise processing only when the lane is valid.
+            if to_x01(id(ipos).strb) = '1' then
+              decode_char(id(ipos).data);
+            end if;
+
+            -- Forward the lasts and clear state upon any last, to prevent
+            -- broken elements from messing up everything.
+            if or_reduce(id(ipos).last) /= '0' then
+${if decoded}
+              push_item((others => '0'), id(ipos).last & "0", '0');
+${else}
+              push_item((others => '0'), id(ipos).last, '0');
+              blen := 0;
+${end}
+              state    := STATE_IDLE;
+              has_high := false;
+            end if;
+
+            if ipos = EPC-1 then
+              iv   := '0';
+              ipos := 0;
+            else
+              ipos := ipos + 1;
+            end if;
+          else
+            exit;
+          end if;
+        end loop;
+
+        if oc > 0 then
+          ov   := '1';
+          endi := to_unsigned(oc-1, endi'length);
+        end if;
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        iv       := '0';
+        ov       := '0';
+        ipos     := 0;
+        ph       := 0;
+        pn       := 0;
+        state    := STATE_IDLE;
+        has_high := false;
+${if !decoded}
+        blen     := 0;
+${end}
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      ir := not iv and not rst;
+      input_ready <= ir and not rst;
+${if fixed}
+      output_data <= od(0).data;
+      output_last <= od(0).last;
+      output_strb <= od(0).strb;
+${else}
+      for idx in 0 to EPC-1 loop
+        output_data(8*idx+7 downto 8*idx) <= od(idx).data;
+        output_last(OLW*(idx+1)-1 downto OLW*idx) <= od(idx).last;
+        output_strb(idx) <= od(idx).strb;
+      end loop;
+      output_stai <= (others => '0');
+      output_endi <= std_logic_vector(endi);
+${end}
+    end if;
+  end process;
+end architecture;
@@ -0,0 +1,87 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+library work;
+use work.UtilInt_pkg.all;
+
+-- FIFO buffer of MIN_DEPTH entries, or a pass-through for a depth of 0. See
+-- Stream_pkg for the interface.
+entity StreamBuffer is
+  generic (
+    DATA_WIDTH                  : natural;
+    MIN_DEPTH                   : natural
+  );
+  port (
+    clk                         : in  std_logic;
+    reset                       : in  std_logic;
+    in_valid                    : in  std_logic;
+    in_ready                    : out std_logic;
+    in_data                     : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+    out_valid                   : out std_logic;
+    out_ready                   : in  std_logic;
+    out_data                    : out std_logic_vector(DATA_WIDTH-1 downto 0)
+  );
+end entity StreamBuffer;
+
+architecture behav of StreamBuffer is
+
+  constant DEPTH                : positive := imax(MIN_DEPTH, 1);
+
+  type mem_type is array (0 to DEPTH-1) of std_logic_vector(DATA_WIDTH-1 downto 0);
+
+  signal mem                    : mem_type;
+  signal rd_ptr                 : natural range 0 to DEPTH-1;
+  signal wr_ptr                 : natural range 0 to DEPTH-1;
+  signal count                  : natural range 0 to DEPTH;
+
+begin
+
+  pass_gen: if MIN_DEPTH = 0 generate
+  begin
+    in_ready  <= out_ready;
+    out_valid <= in_valid;
+    out_data  <= in_data;
+  end generate;
+
+  fifo_gen: if MIN_DEPTH > 0 generate
+  begin
+
+    reg_proc: process (clk) is
+      variable push             : boolean;
+      variable pop              : boolean;
+    begin
+      if rising_edge(clk) then
+        push := in_valid = '1' and count < DEPTH;
+        pop  := out_ready = '1' and count > 0;
+
+        if push then
+          mem(wr_ptr) <= in_data;
+          wr_ptr <= (wr_ptr + 1) mod DEPTH;
+        end if;
+
+        if pop then
+          rd_ptr <= (rd_ptr + 1) mod DEPTH;
+        end if;
+
+        if push and not pop then
+          count <= count + 1;
+        elsif pop and not push then
+          count <= count - 1;
+        end if;
+
+        if reset = '1' then
+          rd_ptr <= 0;
+          wr_ptr <= 0;
+          count  <= 0;
+        end if;
+      end if;
+    end process;
+
+    in_ready  <= '1' when count < DEPTH else '0';
+    out_valid <= '1' when count > 0 else '0';
+    out_data  <= mem(rd_ptr);
+
+  end generate;
+
+end architecture behav;
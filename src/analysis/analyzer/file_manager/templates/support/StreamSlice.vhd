@@ -0,0 +1,64 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+-- Register slice with a skid register, so in_ready does not depend on
+-- out_ready combinatorially. See Stream_pkg for the interface.
+entity StreamSlice is
+  generic (
+    DATA_WIDTH                  : natural
+  );
+  port (
+    clk                         : in  std_logic;
+    reset                       : in  std_logic;
+    in_valid                    : in  std_logic;
+    in_ready                    : out std_logic;
+    in_data                     : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+    out_valid                   : out std_logic;
+    out_ready                   : in  std_logic;
+    out_data                    : out std_logic_vector(DATA_WIDTH-1 downto 0)
+  );
+end entity StreamSlice;
+
+architecture behav of StreamSlice is
+
+  signal main_valid             : std_logic;
+  signal main_data              : std_logic_vector(DATA_WIDTH-1 downto 0);
+  signal skid_valid             : std_logic;
+  signal skid_data              : std_logic_vector(DATA_WIDTH-1 downto 0);
+
+begin
+
+  reg_proc: process (clk) is
+  begin
+    if rising_edge(clk) then
+
+      if main_valid = '0' or out_ready = '1' then
+        -- The output register is empty or read, refill it
+        if skid_valid = '1' then
+          main_valid <= '1';
+          main_data  <= skid_data;
+          skid_valid <= '0';
+        else
+          main_valid <= in_valid;
+          main_data  <= in_data;
+        end if;
+      elsif in_valid = '1' and skid_valid = '0' then
+        -- The output is stalled, keep the accepted transfer aside
+        skid_valid <= '1';
+        skid_data  <= in_data;
+      end if;
+
+      if reset = '1' then
+        main_valid <= '0';
+        skid_valid <= '0';
+      end if;
+
+    end if;
+  end process;
+
+  in_ready  <= not skid_valid;
+  out_valid <= main_valid;
+  out_data  <= main_data;
+
+end architecture behav;
@@ -0,0 +1,68 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+-- Synchronizes the handshakes of the input and output streams. See
+-- Stream_pkg for the interface.
+entity StreamSync is
+  generic (
+    NUM_INPUTS                  : positive := 1;
+    NUM_OUTPUTS                 : positive := 1
+  );
+  port (
+    clk                         : in  std_logic;
+    reset                       : in  std_logic;
+    in_valid                    : in  std_logic_vector(NUM_INPUTS-1 downto 0);
+    in_ready                    : out std_logic_vector(NUM_INPUTS-1 downto 0);
+    out_valid                   : out std_logic_vector(NUM_OUTPUTS-1 downto 0);
+    out_ready                   : in  std_logic_vector(NUM_OUTPUTS-1 downto 0)
+  );
+end entity StreamSync;
+
+architecture behav of StreamSync is
+
+  -- Outputs which already accepted the current transfer
+  signal done                   : std_logic_vector(NUM_OUTPUTS-1 downto 0);
+
+  signal all_valid              : std_logic;
+  signal complete               : std_logic;
+
+begin
+
+  comb_proc: process (in_valid, out_ready, done) is
+    variable valid              : std_logic;
+    variable accepted           : std_logic;
+  begin
+    valid := '1';
+    for idx in in_valid'range loop
+      valid := valid and in_valid(idx);
+    end loop;
+
+    accepted := '1';
+    for idx in out_ready'range loop
+      accepted := accepted and (done(idx) or out_ready(idx));
+    end loop;
+
+    all_valid <= valid;
+    complete  <= valid and accepted;
+  end process;
+
+  reg_proc: process (clk) is
+  begin
+    if rising_edge(clk) then
+      if complete = '1' then
+        done <= (others => '0');
+      elsif all_valid = '1' then
+        done <= done or out_ready;
+      end if;
+
+      if reset = '1' then
+        done <= (others => '0');
+      end if;
+    end if;
+  end process;
+
+  out_valid <= (out_valid'range => all_valid) and not done;
+  in_ready  <= (in_ready'range => complete);
+
+end architecture behav;
@@ -0,0 +1,63 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+-- Valid/ready stream primitives used by the generated components.
+package Stream_pkg is
+
+  -- Register slice which cuts the combinatorial paths of both the data and
+  -- the handshake without losing throughput.
+  component StreamSlice is
+    generic (
+      DATA_WIDTH                : natural
+    );
+    port (
+      clk                       : in  std_logic;
+      reset                     : in  std_logic;
+      in_valid                  : in  std_logic;
+      in_ready                  : out std_logic;
+      in_data                   : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+      out_valid                 : out std_logic;
+      out_ready                 : in  std_logic;
+      out_data                  : out std_logic_vector(DATA_WIDTH-1 downto 0)
+    );
+  end component;
+
+  -- FIFO buffer which holds at least MIN_DEPTH transfers. A depth of 0
+  -- passes the stream through.
+  component StreamBuffer is
+    generic (
+      DATA_WIDTH                : natural;
+      MIN_DEPTH                 : natural
+    );
+    port (
+      clk                       : in  std_logic;
+      reset                     : in  std_logic;
+      in_valid                  : in  std_logic;
+      in_ready                  : out std_logic;
+      in_data                   : in  std_logic_vector(DATA_WIDTH-1 downto 0);
+      out_valid                 : out std_logic;
+      out_ready                 : in  std_logic;
+      out_data                  : out std_logic_vector(DATA_WIDTH-1 downto 0)
+    );
+  end component;
+
+  -- Synchronizes the handshakes of a number of input and output streams. A
+  -- transfer completes once all inputs are valid and every output accepted
+  -- it, the outputs may accept in different cycles.
+  component StreamSync is
+    generic (
+      NUM_INPUTS                : positive := 1;
+      NUM_OUTPUTS               : positive := 1
+    );
+    port (
+      clk                       : in  std_logic;
+      reset                     : in  std_logic;
+      in_valid                  : in  std_logic_vector(NUM_INPUTS-1 downto 0);
+      in_ready                  : out std_logic_vector(NUM_INPUTS-1 downto 0);
+      out_valid                 : out std_logic_vector(NUM_OUTPUTS-1 downto 0);
+      out_ready                 : in  std_logic_vector(NUM_OUTPUTS-1 downto 0)
+    );
+  end component;
+
+end package Stream_pkg;
@@ -0,0 +1,75 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+-- Integer utility functions used by the generated components.
+package UtilInt_pkg is
+
+  -- Minimum and maximum of two integers.
+  function imin(a : integer; b : integer) return integer;
+  function imax(a : integer; b : integer) return integer;
+
+  -- Base 2 logarithm, rounded down and up. Both return 0 for 0 and 1.
+  function log2floor(i : natural) return natural;
+  function log2ceil(i : natural) return natural;
+
+  -- Integer division rounded up.
+  function divCeil(a : natural; b : positive) return natural;
+
+end package UtilInt_pkg;
+
+package body UtilInt_pkg is
+
+  function imin(a : integer; b : integer) return integer is
+  begin
+    if a < b then
+      return a;
+    else
+      return b;
+    end if;
+  end function imin;
+
+  function imax(a : integer; b : integer) return integer is
+  begin
+    if a > b then
+      return a;
+    else
+      return b;
+    end if;
+  end function imax;
+
+  function log2floor(i : natural) return natural is
+    variable x : natural;
+    variable r : natural;
+  begin
+    x := i;
+    r := 0;
+    while x > 1 loop
+      x := x / 2;
+      r := r + 1;
+    end loop;
+    return r;
+  end function log2floor;
+
+  function log2ceil(i : natural) return natural is
+    variable x : natural;
+    variable r : natural;
+  begin
+    if i <= 1 then
+      return 0;
+    end if;
+    x := i - 1;
+    r := 0;
+    while x > 0 loop
+      x := x / 2;
+      r := r + 1;
+    end loop;
+    return r;
+  end function log2ceil;
+
+  function divCeil(a : natural; b : positive) return natural is
+  begin
+    return (a + b - 1) / b;
+  end function divCeil;
+
+end package body UtilInt_pkg;
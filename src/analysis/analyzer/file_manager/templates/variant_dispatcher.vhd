@@ -0,0 +1,379 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+use work.Stream_pkg.all;
+
+-- Sends every value to the parser of its kind, which is told apart by the
+-- first character of the value. For every value a union element is emitted
+-- with the tag of its kind in the lowest bits. Integers and booleans come back
+-- from their parsers and are put above the tag, the lanes closing values and
+-- sequences wait in a buffer until then.
+--
+-- Union fields, by tag:
+${for branch in branches}
+--   ${branch.index}: ${branch.name}
+${end}
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC                  : positive := 4;
+    NESTING_LEVEL        : positive := 2;
+    DLY_COMP_BUFF_DEPTH  : integer := 5
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(EPC*${bit_width}-1 downto 0);
+    input_last : in std_logic_vector(((NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    input_stai : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_endi : in std_logic_vector(log2ceil(EPC)-1 downto 0);
+    input_strb : in std_logic_vector(EPC-1 downto 0);
+${for branch in branches}
+    branch_${branch.name}_valid : out std_logic;
+    branch_${branch.name}_ready : in std_logic;
+    branch_${branch.name}_data : out std_logic_vector(EPC*${bit_width}-1 downto 0);
+    branch_${branch.name}_last : out std_logic_vector(((NESTING_LEVEL + ${branch.additive}) * EPC) - 1 downto 0);
+    branch_${branch.name}_stai : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    branch_${branch.name}_endi : out std_logic_vector(log2ceil(EPC)-1 downto 0);
+    branch_${branch.name}_strb : out std_logic_vector(EPC-1 downto 0);
+${end}
+${for branch in branches}
+${if branch.routed}
+    value_${branch.name}_valid : in std_logic;
+    value_${branch.name}_ready : out std_logic;
+    value_${branch.name}_data : in ${if branch.boolean}std_logic${else}std_logic_vector(${branch.bits}-1 downto 0)${end};
+    value_${branch.name}_last : in std_logic_vector(NESTING_LEVEL-1 downto 0);
+    value_${branch.name}_strb : in std_logic;
+${end}
+${end}
+
+    -- Stream(
+    --     Union(...),
+    --     t=1,
+    --     d=NESTING_LEVEL,
+    --     c=2
+    -- )
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(${union_bits}-1 downto 0);
+    output_last : out std_logic_vector(NESTING_LEVEL-1 downto 0);
+    output_strb : out std_logic
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+  constant TAG_BITS        : positive := ${tag_bits};
+
+  -- Index constants for packing the closing lanes, their tags and lasts into a single vector.
+  constant BUFF_WIDTH      : integer := EPC*(1 + TAG_BITS + NESTING_LEVEL);
+  constant BUFF_END_STAI   : integer := 0;
+  constant BUFF_END_ENDI   : integer := EPC-1;
+  constant BUFF_TAG_STAI   : integer := EPC;
+  constant BUFF_TAG_ENDI   : integer := EPC+TAG_BITS*EPC-1;
+  constant BUFF_LAST_STAI  : integer := EPC+TAG_BITS*EPC;
+  constant BUFF_LAST_ENDI  : integer := BUFF_WIDTH-1;
+
+  signal buff_in_valid     : std_logic;
+  signal buff_in_ready     : std_logic;
+  signal buff_in_data      : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+  signal buff_out_valid    : std_logic;
+  signal buff_out_ready    : std_logic;
+  signal buff_out_data     : std_logic_vector(BUFF_WIDTH-1 downto 0);
+
+begin
+
+  -- The closing lanes wait here until the parsers are done.
+  dly_comp_buff: StreamBuffer
+    generic map (
+      DATA_WIDTH              => BUFF_WIDTH,
+      MIN_DEPTH               => DLY_COMP_BUFF_DEPTH
+    )
+    port map (
+      clk                     => clk,
+      reset                   => rst,
+      in_valid                => buff_in_valid,
+      in_ready                => buff_in_ready,
+      in_data                 => buff_in_data,
+      out_valid               => buff_out_valid,
+      out_ready               => buff_out_ready,
+      out_data                => buff_out_data
+    );
+
+  dispatch_proc: process (clk) is
+    -- Input holding register.
+    variable id : std_logic_vector(EPC*${bit_width}-1 downto 0);
+    variable il : std_logic_vector(((NESTING_LEVEL + 1) * EPC) - 1 downto 0);
+    variable ist : std_logic_vector(EPC-1 downto 0);
+    variable iv : std_logic := '0';
+    variable ir : std_logic := '0';
+
+    -- Branch holding registers, every branch gets the characters of its values.
+    variable bd : std_logic_vector(EPC*${bit_width}-1 downto 0);
+${for branch in branches}
+    variable ${branch.name}_l : std_logic_vector(((NESTING_LEVEL + ${branch.additive}) * EPC) - 1 downto 0);
+    variable ${branch.name}_s : std_logic_vector(EPC-1 downto 0);
+    variable ${branch.name}_v : std_logic := '0';
+${end}
+
+    -- Buffer input holding register.
+    variable t_end  : std_logic_vector(EPC-1 downto 0);
+    variable t_tag  : std_logic_vector(TAG_BITS*EPC-1 downto 0);
+    variable t_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable tv     : std_logic := '0';
+
+    -- Whether a value is being dispatched and the tag of its kind.
+    variable in_value : std_logic := '0';
+    variable tag      : natural range 0 to ${num_branches}-1 := 0;
+
+    variable busy   : std_logic;
+    variable c      : std_logic_vector(7 downto 0);
+    variable close  : std_logic;
+  begin
+    if rising_edge(clk) then
+
+      -- Latch input holding register if we said we would.
+      if to_x01(ir) = '1' then
+        iv := input_valid;
+        id := input_data;
+        il := input_last;
+        for idx in 0 to EPC-1 loop
+          if idx < unsigned(input_stai) or idx > unsigned(input_endi) then
+            ist(idx) := '0';
+          else
+            ist(idx) := input_strb(idx);
+          end if;
+        end loop;
+      end if;
+
+      -- Clear the holding registers of which the transfer was accepted.
+      busy := '0';
+${for branch in branches}
+      if to_x01(branch_${branch.name}_ready) = '1' then
+        ${branch.name}_v := '0';
+      end if;
+      busy := busy or to_x01(${branch.name}_v);
+${end}
+      if to_x01(buff_in_ready) = '1' then
+        tv := '0';
+      end if;
+      busy := busy or to_x01(tv);
+
+      -- Split the transfer once all branches and the buffer are free.
+      if to_x01(iv) = '1' and busy = '0' then
+        bd := id;
+
+        for idx in 0 to EPC-1 loop
+          c     := id(${bit_width}*idx+7 downto ${bit_width}*idx);
+          close := il((NESTING_LEVEL+1)*idx);
+
+          -- The first character of a value selects its branch.
+          if ist(idx) = '1' and in_value = '0' then
+${for branch in branches}
+            if ${branch.condition} then
+              tag      := ${branch.index};
+              in_value := '1';
+            end if;
+${end}
+          end if;
+
+          -- Every branch closes the outer sequences, only the branch of the value gets its characters.
+${for branch in branches}
+          ${branch.name}_l((NESTING_LEVEL+${branch.additive})*(idx+1)-1 downto (NESTING_LEVEL+${branch.additive})*idx) := il((NESTING_LEVEL+1)*idx+NESTING_LEVEL+${branch.additive}-1 downto (NESTING_LEVEL+1)*idx);
+          if in_value = '1' and tag = ${branch.index} then
+            ${branch.name}_s(idx) := ist(idx);
+          else
+            ${branch.name}_s(idx) := '0';
+            ${branch.name}_l((NESTING_LEVEL+${branch.additive})*idx) := '0';
+          end if;
+${end}
+
+          t_end(idx) := close and in_value;
+          t_tag(TAG_BITS*(idx+1)-1 downto TAG_BITS*idx) := std_logic_vector(to_unsigned(tag, TAG_BITS));
+          t_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx) := il((NESTING_LEVEL+1)*(idx+1)-1 downto (NESTING_LEVEL+1)*idx+1);
+
+          if close = '1' then
+            in_value := '0';
+          end if;
+        end loop;
+
+${for branch in branches}
+        ${branch.name}_v := or_reduce(${branch.name}_s) or or_reduce(${branch.name}_l);
+${end}
+        tv := or_reduce(t_end) or or_reduce(t_last);
+        iv := '0';
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        iv       := '0';
+        tv       := '0';
+        in_value := '0';
+${for branch in branches}
+        ${branch.name}_v := '0';
+${end}
+      end if;
+
+      -- Forward the branch holding registers.
+${for branch in branches}
+      branch_${branch.name}_valid <= to_x01(${branch.name}_v);
+      branch_${branch.name}_data  <= bd;
+      branch_${branch.name}_last  <= ${branch.name}_l;
+      branch_${branch.name}_stai  <= (others => '0');
+      branch_${branch.name}_endi  <= (others => '1');
+      branch_${branch.name}_strb  <= ${branch.name}_s;
+${end}
+
+      -- Forward the buffer input holding register.
+      buff_in_valid <= to_x01(tv);
+      buff_in_data(BUFF_END_ENDI downto BUFF_END_STAI)   <= t_end;
+      buff_in_data(BUFF_TAG_ENDI downto BUFF_TAG_STAI)   <= t_tag;
+      buff_in_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI) <= t_last;
+
+      -- Determine input holding register ready status.
+      ir := not iv and not rst;
+      input_ready <= ir;
+    end if;
+  end process;
+
+  merge_proc: process (clk) is
+    -- Buffer output holding register.
+    variable b_end  : std_logic_vector(EPC-1 downto 0);
+    variable b_tag  : std_logic_vector(TAG_BITS*EPC-1 downto 0);
+    variable b_last : std_logic_vector(NESTING_LEVEL*EPC-1 downto 0);
+    variable bv     : std_logic := '0';
+    variable br     : std_logic := '0';
+
+    -- Value holding registers of the parsers of which the value is emitted.
+${for branch in branches}
+${if branch.routed}
+    variable ${branch.name}_d : ${if branch.boolean}std_logic${else}std_logic_vector(${branch.bits}-1 downto 0)${end};
+    variable ${branch.name}_s : std_logic;
+    variable ${branch.name}_v : std_logic := '0';
+    variable ${branch.name}_r : std_logic := '0';
+${end}
+${end}
+
+    -- Output holding register.
+    variable od : std_logic_vector(${union_bits}-1 downto 0);
+    variable ol : std_logic_vector(NESTING_LEVEL-1 downto 0);
+    variable os : std_logic;
+    variable ov : std_logic := '0';
+
+    -- Lane of the buffered transfer from which the next element is emitted.
+    variable lane  : natural range 0 to EPC := 0;
+    variable found : boolean;
+    variable done  : boolean;
+    variable tag   : natural;
+  begin
+    if rising_edge(clk) then
+
+      -- Latch buffer output holding register.
+      if to_x01(br) = '1' then
+        bv     := buff_out_valid;
+        b_end  := buff_out_data(BUFF_END_ENDI downto BUFF_END_STAI);
+        b_tag  := buff_out_data(BUFF_TAG_ENDI downto BUFF_TAG_STAI);
+        b_last := buff_out_data(BUFF_LAST_ENDI downto BUFF_LAST_STAI);
+        lane   := 0;
+      end if;
+
+      -- Latch value holding registers, transfers without a value only close
+      -- sequences which are already in the buffer.
+${for branch in branches}
+${if branch.routed}
+      if to_x01(${branch.name}_r) = '1' then
+        ${branch.name}_v := value_${branch.name}_valid;
+        ${branch.name}_d := value_${branch.name}_data;
+        ${branch.name}_s := value_${branch.name}_strb;
+      end if;
+      if to_x01(${branch.name}_s) /= '1' then
+        ${branch.name}_v := '0';
+      end if;
+${end}
+${end}
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        ov := '0';
+      end if;
+
+      if to_x01(bv) = '1' and to_x01(ov) /= '1' then
+        -- Find the next lane closing a value or a sequence.
+        found := false;
+        for idx in 0 to EPC-1 loop
+          if not found and idx >= lane then
+            if b_end(idx) = '1' or or_reduce(b_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx)) = '1' then
+              found := true;
+              lane  := idx;
+            end if;
+          end if;
+        end loop;
+
+        if not found then
+          bv := '0';
+        else
+          tag  := to_integer(unsigned(b_tag(TAG_BITS*(lane+1)-1 downto TAG_BITS*lane)));
+          done := true;
+
+          od := (others => '0');
+          od(TAG_BITS-1 downto 0) := b_tag(TAG_BITS*(lane+1)-1 downto TAG_BITS*lane);
+
+          -- Integers and booleans wait for the value of their parser.
+${for branch in branches}
+${if branch.routed}
+          if b_end(lane) = '1' and tag = ${branch.index} then
+            if to_x01(${branch.name}_v) = '1' then
+              ${if branch.boolean}od(TAG_BITS) := ${branch.name}_d;${else}od(${branch.high} downto TAG_BITS) := ${branch.name}_d;${end}
+              ${branch.name}_v := '0';
+            else
+              done := false;
+            end if;
+          end if;
+${end}
+${end}
+
+          if done then
+            ol   := b_last(NESTING_LEVEL*(lane+1)-1 downto NESTING_LEVEL*lane);
+            os   := b_end(lane);
+            ov   := '1';
+            lane := lane + 1;
+          end if;
+        end if;
+      end if;
+
+      -- Handle rst.
+      if to_x01(rst) /= '0' then
+        bv   := '0';
+        ov   := '0';
+        lane := 0;
+${for branch in branches}
+${if branch.routed}
+        ${branch.name}_v := '0';
+${end}
+${end}
+      end if;
+
+      -- Forward output holding register.
+      output_valid <= to_x01(ov);
+      output_data  <= od;
+      output_last  <= ol;
+      output_strb  <= os;
+
+      br := not bv and not rst;
+      buff_out_ready <= br;
+${for branch in branches}
+${if branch.routed}
+      ${branch.name}_r := not ${branch.name}_v and not rst;
+      value_${branch.name}_ready <= ${branch.name}_r;
+${end}
+${end}
+    end if;
+  end process;
+end architecture;
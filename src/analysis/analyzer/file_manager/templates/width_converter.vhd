@@ -0,0 +1,153 @@
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+use ieee.std_logic_misc.or_reduce;
+
+library work;
+use work.${project_name}.all;
+use work.UtilInt_pkg.all;
+
+entity ${namespace}_0_${comp_name}_com is
+  generic (
+    EPC_IN : positive := 4;
+    EPC_OUT : positive := 1;
+    NESTING_LEVEL : positive := 1
+  );
+  port (
+    clk : in std_logic;
+    rst : in std_logic;
+    input_valid : in std_logic;
+    input_ready : out std_logic;
+    input_data : in std_logic_vector(EPC_IN*${bit_width}-1 downto 0);
+    input_last : in std_logic_vector(NESTING_LEVEL*EPC_IN-1 downto 0);
+    input_stai : in std_logic_vector(log2ceil(EPC_IN)-1 downto 0);
+    input_endi : in std_logic_vector(log2ceil(EPC_IN)-1 downto 0);
+    input_strb : in std_logic_vector(EPC_IN-1 downto 0);
+    output_valid : out std_logic;
+    output_ready : in std_logic;
+    output_data : out std_logic_vector(EPC_OUT*${bit_width}-1 downto 0);
+    output_last : out std_logic_vector(NESTING_LEVEL*EPC_OUT-1 downto 0);
+    output_stai : out std_logic_vector(log2ceil(EPC_OUT)-1 downto 0);
+    output_endi : out std_logic_vector(log2ceil(EPC_OUT)-1 downto 0);
+    output_strb : out std_logic_vector(EPC_OUT-1 downto 0)
+  );
+end ${namespace}_0_${comp_name}_com;
+
+architecture behav of ${namespace}_0_${comp_name}_com is
+begin
+  clk_proc: process (clk) is
+    -- Element of the stream, used for both sides.
+    type elem_type is record
+      data  : std_logic_vector(${bit_width}-1 downto 0);
+      last  : std_logic_vector(NESTING_LEVEL-1 downto 0);
+      strb  : std_logic;
+    end record;
+
+    constant ELEM_INIT : elem_type := (data => (others => '0'), last => (others => '0'), strb => '0');
+
+    type elem_array is array (natural range <>) of elem_type;
+
+    -- Input holding register and the next lane to move out of it.
+    variable id   : elem_array(0 to EPC_IN-1);
+    variable iv   : std_logic := '0';
+    variable ir   : std_logic := '0';
+    variable ridx : natural range 0 to EPC_IN := 0;
+
+    -- Output holding register and the next lane to fill.
+    variable od   : elem_array(0 to EPC_OUT-1) := (others => ELEM_INIT);
+    variable ov   : std_logic := '0';
+    variable widx : natural range 0 to EPC_OUT := 0;
+
+    -- Whether a lane closing a sequence was placed in the output register.
+    variable closed : std_logic := '0';
+
+    variable stai : natural;
+    variable endi : natural;
+  begin
+    if rising_edge(clk) then
+
+      -- Latch input holding register if we said we would.
+      if to_x01(ir) = '1' then
+        iv := input_valid;
+        stai := to_integer(unsigned(input_stai));
+        endi := to_integer(unsigned(input_endi));
+        for idx in 0 to EPC_IN-1 loop
+          id(idx).data := input_data(${bit_width}*idx+${bit_width}-1 downto ${bit_width}*idx);
+          -- Lanes outside of the active range carry nothing.
+          if idx >= stai and idx <= endi then
+            id(idx).last := input_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx);
+            id(idx).strb := input_strb(idx);
+          else
+            id(idx).last := (others => '0');
+            id(idx).strb := '0';
+          end if;
+        end loop;
+        ridx := 0;
+      end if;
+
+      -- Clear output holding register if transfer was accepted.
+      if to_x01(output_ready) = '1' then
+        if ov = '1' then
+          od     := (others => ELEM_INIT);
+          widx   := 0;
+          closed := '0';
+        end if;
+        ov := '0';
+      end if;
+
+      -- Move as many lanes as fit from the input to the output register.
+      if to_x01(iv) = '1' and to_x01(ov) = '0' then
+        for idx in 0 to EPC_IN-1 loop
+          if idx >= ridx and widx < EPC_OUT then
+            -- Lanes without data or last bits do not need to be forwarded.
+            if id(idx).strb = '1' or or_reduce(id(idx).last) = '1' then
+              od(widx) := id(idx);
+              widx := widx + 1;
+              if or_reduce(id(idx).last) = '1' then
+                closed := '1';
+              end if;
+            end if;
+            ridx := idx + 1;
+          end if;
+        end loop;
+
+        if ridx = EPC_IN then
+          iv := '0';
+        end if;
+
+        -- Send the output when it is full or when a sequence was closed
+        -- and there is nothing left to add.
+        if widx = EPC_OUT or (iv = '0' and closed = '1') then
+          ov := '1';
+        end if;
+      end if;
+
+      -- Handle reset.
+      if to_x01(rst) /= '0' then
+        iv     := '0';
+        ov     := '0';
+        ridx   := 0;
+        widx   := 0;
+        closed := '0';
+        od     := (others => ELEM_INIT);
+      end if;
+
+      -- Forward output holding register.
+      ir := not iv and not rst;
+      input_ready <= ir;
+      output_valid <= to_x01(ov);
+      for idx in 0 to EPC_OUT-1 loop
+        output_data(${bit_width}*idx+${bit_width}-1 downto ${bit_width}*idx) <= od(idx).data;
+        output_last(NESTING_LEVEL*(idx+1)-1 downto NESTING_LEVEL*idx) <= od(idx).last;
+        output_strb(idx) <= od(idx).strb;
+      end loop;
+      output_stai <= (others => '0');
+      -- The last filled lane ends the transfer, EPC_OUT does not have to be a power of two.
+      if widx > 0 then
+        output_endi <= std_logic_vector(to_unsigned(widx-1, log2ceil(EPC_OUT)));
+      else
+        output_endi <= (others => '0');
+      end if;
+    end if;
+  end process;
+end behav;
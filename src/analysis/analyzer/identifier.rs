@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+/**********************************************************************************
+ * Set of functions to turn arbitrary names (e.g. JSON keys) into identifiers     *
+ * which are valid in both VHDL and Verilog                                       *
+ **********************************************************************************/
+
+// Maximum length of a generated identifier, including namespace and suffixes
+pub const MAX_IDENTIFIER_LENGTH: usize = 96;
+
+const VHDL_RESERVED: &[&str] = &[
+    "abs", "access", "after", "alias", "all", "and", "architecture", "array", "assert", "assume",
+    "assume_guarantee", "attribute", "begin", "block", "body", "buffer", "bus", "case", "component",
+    "configuration", "constant", "context", "cover", "default", "disconnect", "downto", "else", "elsif",
+    "end", "entity", "exit", "fairness", "file", "for", "force", "function", "generate", "generic",
+    "group", "guarded", "if", "impure", "in", "inertial", "inout", "is", "label", "library", "linkage",
+    "literal", "loop", "map", "mod", "nand", "new", "next", "nor", "not", "null", "of", "on", "open",
+    "or", "others", "out", "package", "parameter", "port", "postponed", "procedure", "process",
+    "property", "protected", "pure", "range", "record", "register", "reject", "release", "rem",
+    "report", "restrict", "restrict_guarantee", "return", "rol", "ror", "select", "sequence",
+    "severity", "shared", "signal", "sla", "sll", "sra", "srl", "strong", "subtype", "then", "to",
+    "transport", "type", "unaffected", "units", "until", "use", "variable", "vmode", "vprop", "vunit",
+    "wait", "when", "while", "with", "xnor", "xor",
+];
+
+const VERILOG_RESERVED: &[&str] = &[
+    "always", "always_comb", "always_ff", "always_latch", "and", "assign", "automatic", "begin", "bit",
+    "buf", "bufif0", "bufif1", "byte", "case", "casex", "casez", "cell", "class", "cmos", "config",
+    "const", "deassign", "default", "defparam", "design", "disable", "do", "edge", "else", "end",
+    "endcase", "endclass", "endconfig", "endfunction", "endgenerate", "endinterface", "endmodule",
+    "endpackage", "endprimitive", "endspecify", "endtable", "endtask", "enum", "event", "export",
+    "extends", "for", "force", "forever", "fork", "function", "generate", "genvar", "highz0", "highz1",
+    "if", "ifnone", "import", "incdir", "include", "initial", "inout", "input", "instance", "int",
+    "integer", "interface", "join", "large", "liblist", "library", "localparam", "logic", "longint",
+    "macromodule", "medium", "module", "nand", "negedge", "new", "nmos", "nor", "noshowcancelled",
+    "not", "notif0", "notif1", "null", "or", "output", "package", "packed", "parameter", "pmos",
+    "posedge", "primitive", "pull0", "pull1", "pulldown", "pullup", "pulsestyle_ondetect",
+    "pulsestyle_onevent", "rcmos", "real", "realtime", "reg", "release", "repeat", "return", "rnmos",
+    "rpmos", "rtran", "rtranif0", "rtranif1", "scalared", "shortint", "showcancelled", "signed",
+    "small", "specify", "specparam", "static", "string", "strong0", "strong1", "struct", "supply0",
+    "supply1", "table", "task", "time", "tran", "tranif0", "tranif1", "tri", "tri0", "tri1", "triand",
+    "trior", "trireg", "typedef", "union", "unsigned", "use", "uwire", "vectored", "void", "wait",
+    "wand", "weak0", "weak1", "while", "wire", "wor", "xnor", "xor",
+];
+
+// Check if a name is a reserved word in VHDL or Verilog, VHDL is case insensitive
+pub fn is_reserved(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+
+    VHDL_RESERVED.contains(&lower.as_str()) || VERILOG_RESERVED.contains(&name)
+}
+
+// Rewrite a name into a valid identifier:
+// - only ASCII letters, digits and single underscores
+// - starts with a letter and does not end with an underscore
+// - not a reserved word
+pub fn sanitize(name: &str) -> String {
+    let mut identifier = String::new();
+
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c);
+            continue;
+        }
+
+        // Every other character separates parts, consecutive underscores are not allowed
+        if !identifier.is_empty() && !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+
+        // Unicode characters are kept by their code point so different keys stay different
+        if !c.is_ascii() {
+            identifier.push_str(&format!("u{:x}_", c as u32));
+        }
+    }
+
+    let mut identifier = identifier.trim_end_matches('_').to_string();
+
+    if identifier.is_empty() {
+        identifier.push_str("id");
+    }
+
+    // Identifiers have to start with a letter
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, 'n');
+    }
+
+    if is_reserved(&identifier) {
+        identifier.push_str("_id");
+    }
+
+    identifier
+}
+
+// Shorten an identifier to the maximum length, the hash of the original name keeps it unique
+pub fn shorten(identifier: &str, max_length: usize, original: &str) -> String {
+    if identifier.len() <= max_length {
+        return identifier.to_string();
+    }
+
+    // Leave room for the underscore and the hash
+    let prefix = identifier[..max_length.saturating_sub(9)].trim_end_matches('_');
+
+    format!("{}_{:08x}", prefix, fnv_hash(original))
+}
+
+// FNV-1a hash, stable between runs and compiler versions
+pub fn fnv_hash(text: &str) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+
+    for byte in text.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+
+    hash
+}
+
+// Set of identifiers in use, compared case insensitively as VHDL does
+#[derive(Default)]
+pub struct IdentifierSet {
+    used: HashMap<String, String>,
+}
+
+impl IdentifierSet {
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.used.contains_key(&identifier.to_ascii_lowercase())
+    }
+
+    // Reserve an identifier for the given origin, a different origin which maps to the same
+    // identifier gets the hash of its origin appended
+    pub fn reserve(&mut self, identifier: String, origin: &str) -> String {
+        let mut identifier = identifier;
+
+        while let Some(registered) = self.used.get(&identifier.to_ascii_lowercase()) {
+            if registered == origin {
+                break;
+            }
+
+            identifier = format!("{}_{:08x}", identifier, fnv_hash(&format!("{}#{}", identifier, origin)));
+        }
+
+        self.used.insert(identifier.to_ascii_lowercase(), origin.to_string());
+
+        identifier
+    }
+}
@@ -1,18 +1,24 @@
+use std::collections::{HashMap, HashSet};
+
 use json::JsonValue;
 
-use crate::analysis::components::JsonComponent;
+use crate::analysis::components::{JsonComponent, JsonComponentValue, Generatable, Filter, Record, VariantKind};
 
 use self::{file_manager::FileManager, signal_manager::SignalManager};
 
-use super::{types::TilStreamlet, GeneratorParams, analyzer::{name_reg::NameReg, type_manager::{TypeManager, StreamType}}};
+use super::{types::{TilStreamlet, til_streamlet::TilInstance, stream_types::StreamTypeDecl, logical_type::LogicalType, physical_stream::PhysicalStream}, GeneratorParams, analyzer::{name_reg::{NameReg, Collisions}, type_manager::TypeManager}};
 
 mod analysis;
+mod identifier;
 mod name_reg;
+pub mod samples;
 pub mod type_manager;
 pub mod top_component;
 pub mod file_manager;
 pub mod signal_manager;
 
+pub use name_reg::NamingStrategy;
+
 /**********************************************************************************
  * Set of functions to analyze the parsed JSON object into a component structure  *
  * which can be used to generate HDL code.                                        *
@@ -22,30 +28,93 @@ pub struct Analyzer {
     name_reg: NameReg,
     type_manager: TypeManager,
     entity_list: Vec<TilStreamlet>,
+    // Instances of the entities, one for every component
+    instances: Vec<TilInstance>,
+    // Entity implementing the components with the same interface and VHDL
+    shared_entities: HashMap<String, String>,
     file_manager: FileManager,
     gen_params: GeneratorParams,
     signal_manager: SignalManager,
     top_component: Option<JsonComponent>,
+    document_size: usize,
+    // Paths of the keys which are not present in every sample
+    optional_paths: HashSet<String>,
+    // Distinct string values at every path and the number of strings seen there
+    string_values: HashMap<String, (Vec<String>, usize)>,
+    // Length of the longest array at every path
+    array_lengths: HashMap<String, usize>,
+    // Length of the longest key of the maps at every path
+    key_lengths: HashMap<String, usize>,
+    // Kinds of the values at every path, with a sample of every kind
+    value_kinds: HashMap<String, Vec<(VariantKind, JsonValue)>>,
+    // Key presence instance and bit of the keys at every path whose record emits the presence of its keys
+    presence_bits: HashMap<String, (String, usize)>,
 }
 
 impl Analyzer {
     pub fn new() -> Analyzer {
         Analyzer {
-            name_reg: NameReg::new(),
+            name_reg: NameReg::new(NamingStrategy::default(), identifier::MAX_IDENTIFIER_LENGTH),
             type_manager: TypeManager::new(),
             entity_list: Vec::new(),
+            instances: Vec::new(),
+            shared_entities: HashMap::new(),
             file_manager: FileManager::new(),
             gen_params: GeneratorParams::default(),
             signal_manager: SignalManager::default(),
             top_component: None,
+            document_size: 0,
+            optional_paths: HashSet::new(),
+            string_values: HashMap::new(),
+            array_lengths: HashMap::new(),
+            key_lengths: HashMap::new(),
+            value_kinds: HashMap::new(),
+            presence_bits: HashMap::new(),
         }
     }
 
-    pub fn analyze(&mut self, root: &JsonValue, gen_params: GeneratorParams) {
+    pub fn analyze(&mut self, samples: &[JsonValue], gen_params: GeneratorParams) {
+        self.analyze_with_collisions(samples, gen_params.clone(), Collisions::default());
+
+        // Path derived names and entity variants which collide are only known once every component is
+        // registered, the analysis is repeated so all of them get a hash instead of all but the first
+        let collisions = self.name_reg.get_collisions().clone();
+        if !collisions.is_empty() {
+            *self = Analyzer::new();
+            self.analyze_with_collisions(samples, gen_params, collisions);
+        }
+    }
+
+    fn analyze_with_collisions(&mut self, samples: &[JsonValue], gen_params: GeneratorParams, collisions: Collisions) {
         self.gen_params = gen_params;
 
-        let (root_component, _) = self.analyze_element(root, 0, 0);
+        // The longest identifiers derived from a name are <namespace>_0_<name>_com and output_<name>_inst
+        let max_length = identifier::MAX_IDENTIFIER_LENGTH - (self.gen_params.comp_namespace.len() + 7).max(12);
+        self.name_reg = NameReg::new(self.gen_params.naming, max_length).with_collisions(collisions);
+
+        // Analyze a single sample which covers the structure of all samples
+        let root = samples::merge_samples(samples);
+
+        // Average size of a document, used to derive the EPC of fields from their expected size
+        self.document_size = samples.iter().map(|sample| sample.dump().len()).sum::<usize>() / samples.len().max(1);
+
+        // The values of all keys of a map are described by one path
+        let maps: HashSet<String> = self.gen_params.map_patterns.keys().cloned().collect();
+        self.optional_paths = samples::optional_paths(samples, &maps);
+        self.string_values = samples::string_values(samples, &maps);
+        self.array_lengths = samples::array_lengths(samples, &maps);
+        self.key_lengths = samples::key_lengths(samples, &maps);
+        self.value_kinds = samples::value_kinds(samples, &maps);
+
+        // Types declared by the user come first
+        for logical_type in self.gen_params.logical_types.clone() {
+            self.type_manager.register_logical_type(&logical_type);
+        }
+
+        let (root_component, _) = self.analyze_element(&root, "", self.gen_params.epc, 0, 0);
         self.top_component = root_component;
+
+        self.insert_width_converters();
     }
 
     pub fn get_root(&self) -> Result<&JsonComponent, AnalyzerError> {
@@ -55,20 +124,181 @@ impl Analyzer {
         }
     }
 
-    pub fn get_definitions(&self) -> (Vec<StreamType>, &Vec<TilStreamlet>) {   
+    pub fn get_definitions(&self) -> (Vec<StreamTypeDecl>, &Vec<TilStreamlet>) {   
         let stream_types = self.type_manager.get_stream_types();
         let til_components = &self.entity_list;
         
         (stream_types, til_components)
     }
 
+    // Named element types, declared before the stream types
+    pub fn get_logical_types(&self) -> &Vec<LogicalType> {
+        self.type_manager.get_logical_types()
+    }
+
+    pub fn get_instances(&self) -> &Vec<TilInstance> {
+        &self.instances
+    }
+
     pub fn get_file_manager(&self) -> &FileManager {
         &self.file_manager
     }
+
+    // Check that the configured complexities keep the physical ports the templates are written for
+    pub fn validate_stream_configs(&self) -> Result<(), AnalyzerError> {
+        for streamlet in &self.entity_list {
+            for stream in streamlet.get_streams().get_streams() {
+                let stream_type = stream.get_type();
+                let template_type = stream_type.clone().with_config(Some(stream_type.get_stream_type().get_default_config()));
+
+                let configured = PhysicalStream::new(stream_type, &self.gen_params);
+                let template = PhysicalStream::new(&template_type, &self.gen_params);
+
+                if configured.get_signals() != template.get_signals() {
+                    return Err(AnalyzerError::IncompatibleStreamConfig(format!("{}.{}", streamlet.get_name(), stream.get_name())));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Check that every filter is attached to a value of the type of its predicate and has a unique name
+    pub fn validate_filters(&self) -> Result<(), AnalyzerError> {
+        let mut filters = Vec::new();
+        if let Some(root) = &self.top_component {
+            collect_filters(root, &mut filters);
+        }
+
+        let mut names = HashSet::new();
+        for filter in &filters {
+            if !names.insert(filter.get_selected_name()) {
+                return Err(AnalyzerError::InvalidFilter(format!("{}: the name {} is already used", filter.get_path(), filter.get_filter_name())));
+            }
+        }
+
+        for (path, (_, predicate)) in &self.gen_params.filters {
+            if !filters.iter().any(|filter| filter.get_path() == path) {
+                return Err(AnalyzerError::InvalidFilter(format!("{}: no {:?} value to test {}", path, predicate.get_data_type(), predicate)));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Check that the key parser of every map runs at an EPC of 1, it emits one key name per transfer
+    pub fn validate_maps(&self) -> Result<(), AnalyzerError> {
+        let mut records = Vec::new();
+        if let Some(root) = &self.top_component {
+            collect_records(root, &mut records);
+        }
+
+        for record in &records {
+            for key in record.get_keys() {
+                if key.is_map_key() && key.get_epc() > 1 {
+                    return Err(AnalyzerError::InvalidMap(format!("the keys of the map at \"{}\" are parsed at an EPC of {}, set the EPC of the map to 1", record.get_path(), key.get_epc())));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Check that every assembled key is an integer or boolean key of the records at its path
+    pub fn validate_assemblies(&self) -> Result<(), AnalyzerError> {
+        let mut records = Vec::new();
+        if let Some(root) = &self.top_component {
+            collect_records(root, &mut records);
+        }
+
+        for (path, keys) in &self.gen_params.record_assemblies {
+            let record = records.iter().find(|record| record.get_path() == path)
+                .ok_or_else(|| AnalyzerError::InvalidAssembly(format!("no record at \"{}\" to assemble", path)))?;
+
+            for key in keys {
+                let assembled = record.get_assembler().is_some_and(|assembler| assembler.get_fields().iter().any(|(field_key, _, _)| field_key == key));
+
+                if !assembled {
+                    return Err(AnalyzerError::InvalidAssembly(format!("{} is not an integer or boolean key of the record at \"{}\"", key, path)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Check that every type name is used for one definition and every declared field type has the width
+    // of an integer or boolean at its path
+    pub fn validate_types(&self) -> Result<(), AnalyzerError> {
+        if let Some(name) = self.type_manager.get_conflicting_types().first() {
+            return Err(AnalyzerError::InvalidType(format!("{} is declared with different definitions", name)));
+        }
+
+        for (path, logical_type) in &self.gen_params.field_types {
+            let widths: Vec<usize> = self.value_kinds.get(path).into_iter().flatten()
+                .filter_map(|(kind, _)| match kind {
+                    VariantKind::Integer => Some(self.gen_params.int_width),
+                    VariantKind::Boolean => Some(1),
+                    _ => None,
+                })
+                .collect();
+
+            if widths.is_empty() {
+                return Err(AnalyzerError::InvalidType(format!("{}: no integer or boolean value to emit as {}", path, logical_type)));
+            }
+
+            if !widths.contains(&logical_type.get_bits()) {
+                return Err(AnalyzerError::InvalidType(format!("{}: {} has {} bits, the value has {}", path, logical_type, logical_type.get_bits(), widths[0])));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Collect the records in the component tree
+fn collect_records(component: &JsonComponent, records: &mut Vec<Record>) {
+    if let JsonComponent::Record(record) = component {
+        records.push(record.clone());
+    }
+
+    for child in component.get_children() {
+        collect_records(&child, records);
+    }
+}
+
+// Collect the filters in the component tree
+fn collect_filters(component: &JsonComponent, filters: &mut Vec<Filter>) {
+    if let JsonComponent::Filter(filter) = component {
+        filters.push(filter.clone());
+    }
+
+    for child in component.get_children() {
+        collect_filters(&child, filters);
+    }
 }
 
 #[derive(Debug)]
 pub enum AnalyzerError {
     NoTop,
     PythonError(String),
+    IncompatibleStreamConfig(String),
+    InvalidFilter(String),
+    InvalidMap(String),
+    InvalidAssembly(String),
+    InvalidType(String),
+}
+
+impl std::fmt::Display for AnalyzerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalyzerError::NoTop => write!(f, "no samples analyzed"),
+            AnalyzerError::PythonError(message) => write!(f, "matcher generation failed: {}", message),
+            AnalyzerError::IncompatibleStreamConfig(stream) => write!(f, "stream {} cannot be driven with its configuration", stream),
+            AnalyzerError::InvalidFilter(message) => write!(f, "invalid filter: {}", message),
+            AnalyzerError::InvalidMap(message) => write!(f, "invalid map: {}", message),
+            AnalyzerError::InvalidAssembly(message) => write!(f, "invalid record assembly: {}", message),
+            AnalyzerError::InvalidType(message) => write!(f, "invalid type: {}", message),
+        }
+    }
 }
\ No newline at end of file
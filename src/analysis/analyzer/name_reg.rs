@@ -1,18 +1,140 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use super::identifier::{self, IdentifierSet};
+
+// How the names of the components are chosen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NamingStrategy {
+    // Kind, nesting level and a counter in order of registration (int_parser_L4_01)
+    #[default]
+    Sequential,
+    // JSON path and kind (temperature_voltage_int_parser), stable when keys are added or reordered
+    Path,
+}
+
+// Names which were registered for different origins, these always get the hash of their origin
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Collisions {
+    // Path derived names of components at different paths
+    paths: HashSet<String>,
+    // Entity kinds with different implementations
+    entities: HashSet<String>,
+}
+
+impl Collisions {
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.entities.is_empty()
+    }
+}
 
 pub struct NameReg {
     name_map: HashMap<String, usize>,
+    strategy: NamingStrategy,
+    // Maximum length of a name so the derived entity and port names are valid identifiers
+    max_length: usize,
+    identifiers: IdentifierSet,
+    // Entities have their own names, which do not take the names of the components
+    entity_identifiers: IdentifierSet,
+    // Path derived names and the path they were first registered for
+    path_names: HashMap<String, String>,
+    // Entity kinds and the hash of the signature they were first registered for
+    entity_signatures: HashMap<String, u32>,
+    collisions: Collisions,
 }
 
 impl NameReg {
-    pub fn new() -> NameReg {
+    pub fn new(strategy: NamingStrategy, max_length: usize) -> NameReg {
         NameReg {
             name_map: HashMap::new(),
+            strategy,
+            max_length,
+            identifiers: IdentifierSet::default(),
+            entity_identifiers: IdentifierSet::default(),
+            path_names: HashMap::new(),
+            entity_signatures: HashMap::new(),
+            collisions: Collisions::default(),
         }
     }
 
-    pub fn register(&mut self, name: &str, nesting_level: usize) -> String {
-        let mut pref_name = String::from(name);
+    // Suffix the names which collided in an earlier registration of the same components
+    pub fn with_collisions(mut self, collisions: Collisions) -> NameReg {
+        self.collisions = collisions;
+        self
+    }
+
+    // Names which were registered for different origins
+    pub fn get_collisions(&self) -> &Collisions {
+        &self.collisions
+    }
+
+    // Register a component of the given kind for the JSON path
+    pub fn register(&mut self, name: &str, path: &str, nesting_level: usize) -> String {
+        match self.strategy {
+            NamingStrategy::Sequential => self.register_sequential(name, nesting_level),
+            NamingStrategy::Path => self.register_path(name, path),
+        }
+    }
+
+    // Register the matcher of a key, the key is part of the path so it is only used for sequential names
+    pub fn register_matcher(&mut self, key: &str, path: &str, nesting_level: usize) -> String {
+        match self.strategy {
+            NamingStrategy::Sequential => self.register_sequential(&format!("{}_matcher", key), nesting_level),
+            NamingStrategy::Path => self.register_path("matcher", path),
+        }
+    }
+
+    // Register the name of an entity which is shared by the components with the signature. Sequential
+    // variants of a kind are numbered, path derived ones get the hash of their signature.
+    pub fn register_entity(&mut self, kind: &str, signature: &str) -> String {
+        match self.strategy {
+            NamingStrategy::Sequential => self.register_sequential_entity(kind),
+            NamingStrategy::Path => self.register_path_entity(kind, signature),
+        }
+    }
+
+    fn register_sequential_entity(&mut self, kind: &str) -> String {
+        // Leave room for the variant number
+        let base = identifier::shorten(&identifier::sanitize(kind), self.max_length.saturating_sub(3), kind);
+
+        let mut name = base.clone();
+        let mut count = 1;
+        while self.entity_identifiers.contains(&name) {
+            name = format!("{}_{}", base, count);
+            count += 1;
+        }
+
+        self.entity_identifiers.reserve(name.clone(), &name)
+    }
+
+    fn register_path_entity(&mut self, kind: &str, signature: &str) -> String {
+        // Leave room for the hash
+        let base = identifier::shorten(&identifier::sanitize(kind), self.max_length.saturating_sub(9), kind);
+        let hash = identifier::fnv_hash(signature);
+        let origin = format!("{}#{:08x}", kind, hash);
+
+        // A kind with different implementations gets the hash of the signature for every variant,
+        // so the names do not depend on which variant is registered first
+        let key = base.to_ascii_lowercase();
+        match self.entity_signatures.get(&key) {
+            Some(registered) if *registered != hash => {
+                self.collisions.entities.insert(key.clone());
+            },
+            Some(_) => (),
+            None => {
+                self.entity_signatures.insert(key.clone(), hash);
+            },
+        }
+
+        if self.collisions.entities.contains(&key) {
+            return self.entity_identifiers.reserve(format!("{}_{:08x}", base, hash), &origin);
+        }
+
+        self.entity_identifiers.reserve(base, &origin)
+    }
+
+    fn register_sequential(&mut self, name: &str, nesting_level: usize) -> String {
+        // Leave room for the nesting level and counter
+        let mut pref_name = identifier::shorten(&identifier::sanitize(name), self.max_length.saturating_sub(7), name);
 
         // Add nesting level to prefered name
         pref_name.push_str(&format!("_L{}_", nesting_level)); 
@@ -34,6 +156,151 @@ impl NameReg {
             self.name_map.insert(String::from(&pref_name), 1);
         }
 
-        registered_name
+        self.identifiers.reserve(registered_name.clone(), &registered_name)
     }
-}
\ No newline at end of file
+
+    fn register_path(&mut self, kind: &str, path: &str) -> String {
+        let origin = format!("{}#{}", path, kind);
+
+        // Shorten long paths and keep them unique with a hash of the full path, the kind is always kept
+        let name = if path.is_empty() {
+            kind.to_string()
+        } else {
+            let path_name = identifier::sanitize(&path.to_ascii_lowercase());
+            let max_path_length = self.max_length.saturating_sub(kind.len() + 1);
+
+            format!("{}_{}", identifier::shorten(&path_name, max_path_length, path), kind)
+        };
+
+        // Different paths can map to the same name (e.g. "a_b.c" and "a.b_c"), all of them get the hash of
+        // their path so the names do not depend on which path is registered first
+        let key = name.to_ascii_lowercase();
+        match self.path_names.get(&key) {
+            Some(registered) if *registered != origin => {
+                self.collisions.paths.insert(key.clone());
+            },
+            Some(_) => (),
+            None => {
+                self.path_names.insert(key.clone(), origin.clone());
+            },
+        }
+
+        if self.collisions.paths.contains(&key) {
+            let name = format!("{}_{:08x}", name, identifier::fnv_hash(&origin));
+            return self.identifiers.reserve(name, &origin);
+        }
+
+        self.identifiers.reserve(name, &origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Register the components twice like the analyzer, the second time with the collisions of the first
+    fn register_paths(registrations: &[(&str, &str)]) -> HashMap<String, String> {
+        let mut name_reg = NameReg::new(NamingStrategy::Path, 64);
+        for (kind, path) in registrations {
+            name_reg.register(kind, path, 0);
+        }
+
+        let mut name_reg = NameReg::new(NamingStrategy::Path, 64).with_collisions(name_reg.get_collisions().clone());
+        registrations.iter()
+            .map(|(kind, path)| (format!("{}#{}", path, kind), name_reg.register(kind, path, 0)))
+            .collect()
+    }
+
+    #[test]
+    fn sequential_names() {
+        let mut name_reg = NameReg::new(NamingStrategy::Sequential, 64);
+
+        assert_eq!(name_reg.register("int_parser", "a", 2), "int_parser_L2_00");
+        assert_eq!(name_reg.register("int_parser", "b", 2), "int_parser_L2_01");
+        assert_eq!(name_reg.register("int_parser", "c", 3), "int_parser_L3_00");
+        assert_eq!(name_reg.register_matcher("a", "a", 2), "a_matcher_L2_00");
+    }
+
+    #[test]
+    fn path_names() {
+        let mut name_reg = NameReg::new(NamingStrategy::Path, 64);
+
+        assert_eq!(name_reg.register("int_parser", "temperature[].voltage", 2), "temperature_voltage_int_parser");
+        assert_eq!(name_reg.register_matcher("voltage", "temperature[].voltage", 2), "temperature_voltage_matcher");
+        assert_eq!(name_reg.register("record_parser", "", 1), "record_parser");
+        // The same component registered again keeps its name
+        assert_eq!(name_reg.register("int_parser", "temperature[].voltage", 2), "temperature_voltage_int_parser");
+        assert!(name_reg.get_collisions().is_empty());
+    }
+
+    #[test]
+    fn colliding_paths_are_all_suffixed() {
+        let names = register_paths(&[("int_parser", "a_b.c"), ("int_parser", "a.b_c"), ("int_parser", "d")]);
+
+        assert_ne!(names["a_b.c#int_parser"], names["a.b_c#int_parser"]);
+        assert!(names["a_b.c#int_parser"].starts_with("a_b_c_int_parser_"));
+        assert!(names["a.b_c#int_parser"].starts_with("a_b_c_int_parser_"));
+        assert_eq!(names["d#int_parser"], "d_int_parser");
+    }
+
+    #[test]
+    fn entities_have_their_own_names() {
+        for strategy in [NamingStrategy::Sequential, NamingStrategy::Path] {
+            let mut name_reg = NameReg::new(strategy, 64);
+
+            // Path derived names of the components equal the entity kinds
+            name_reg.register("record_parser", "", 1);
+            name_reg.register_matcher("a", "a", 2);
+            assert_eq!(name_reg.register_entity("record_parser", "record"), "record_parser");
+            assert_eq!(name_reg.register_entity("a_matcher", "matcher"), "a_matcher");
+        }
+    }
+
+    #[test]
+    fn entity_variants_are_named_by_their_signature() {
+        let register = |signatures: &[&str]| {
+            let mut name_reg = NameReg::new(NamingStrategy::Path, 64);
+            for signature in signatures {
+                name_reg.register_entity("array_parser", signature);
+            }
+
+            let mut name_reg = NameReg::new(NamingStrategy::Path, 64).with_collisions(name_reg.get_collisions().clone());
+            let mut names: Vec<(String, String)> = signatures.iter()
+                .map(|signature| (signature.to_string(), name_reg.register_entity("array_parser", signature)))
+                .collect();
+            names.sort();
+            names
+        };
+
+        // A sole variant keeps the kind
+        assert_eq!(register(&["count", "count"]), vec![("count".to_string(), "array_parser".to_string()); 2]);
+
+        let names = register(&["count", "plain"]);
+        assert_ne!(names[0].1, names[1].1);
+        assert!(names.iter().all(|(_, name)| name.starts_with("array_parser_")));
+        assert_eq!(register(&["plain", "count"]), names);
+    }
+
+    #[test]
+    fn path_names_do_not_depend_on_the_order() {
+        let registrations = [
+            ("int_parser", "a_b.c"),
+            ("int_parser", "a.b_c"),
+            ("int_parser", "A.b.c"),
+            ("filter_matcher", "a"),
+            ("matcher", "a.filter"),
+            ("bool_parser", "x[].y"),
+        ];
+        let expected = register_paths(&registrations);
+
+        // Every rotation and the reverse of the registrations
+        for shift in 0..registrations.len() {
+            let mut permuted = registrations.to_vec();
+            permuted.rotate_left(shift);
+            assert_eq!(register_paths(&permuted), expected);
+
+            permuted.reverse();
+            assert_eq!(register_paths(&permuted), expected);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+
+use json::JsonValue;
+
+use crate::analysis::components::VariantKind;
+
+/**********************************************************************************
+ * Set of functions to merge multiple JSON samples into a single representative   *
+ * sample which covers the structure of all of them.                              *
+ **********************************************************************************/
+
+// Merge all samples into one
+pub fn merge_samples(samples: &[JsonValue]) -> JsonValue {
+    let mut merged = JsonValue::Null;
+
+    for sample in samples {
+        merged = merge_values(merged, sample);
+    }
+
+    merged
+}
+
+// Paths of the keys which are missing from at least one object they could appear in
+pub fn optional_paths(samples: &[JsonValue], maps: &HashSet<String>) -> HashSet<String> {
+    let mut object_counts = HashMap::new();
+    let mut key_counts = HashMap::new();
+
+    for sample in samples {
+        count_keys(sample, "", maps, &mut object_counts, &mut key_counts);
+    }
+
+    key_counts.into_iter()
+        .filter(|(_, (parent, count))| object_counts.get(parent).is_some_and(|objects| count < objects))
+        .map(|(path, _)| path)
+        .collect()
+}
+
+// Count the objects at every path and how often every key appears in them (by key path, with the path of the object)
+fn count_keys(value: &JsonValue, path: &str, maps: &HashSet<String>, object_counts: &mut HashMap<String, usize>, key_counts: &mut HashMap<String, (String, usize)>) {
+    match value {
+        JsonValue::Object(object) => {
+            *object_counts.entry(path.to_string()).or_insert(0) += 1;
+
+            for (key, value) in object.iter() {
+                let key_path = key_path(path, key, maps);
+                key_counts.entry(key_path.clone()).or_insert((path.to_string(), 0)).1 += 1;
+
+                count_keys(value, &key_path, maps, object_counts, key_counts);
+            }
+        },
+        JsonValue::Array(array) => {
+            for element in array {
+                count_keys(element, &format!("{}[]", path), maps, object_counts, key_counts);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Distinct string values at every path in order of appearance, with the number of times a string was seen
+pub fn string_values(samples: &[JsonValue], maps: &HashSet<String>) -> HashMap<String, (Vec<String>, usize)> {
+    let mut values = HashMap::new();
+
+    for sample in samples {
+        collect_strings(sample, "", maps, &mut values);
+    }
+
+    values
+}
+
+fn collect_strings(value: &JsonValue, path: &str, maps: &HashSet<String>, values: &mut HashMap<String, (Vec<String>, usize)>) {
+    match value {
+        JsonValue::Short(_) | JsonValue::String(_) => {
+            let (distinct, count) = values.entry(path.to_string()).or_insert((Vec::new(), 0));
+            let text = value.as_str().unwrap().to_string();
+
+            if !distinct.contains(&text) {
+                distinct.push(text);
+            }
+            *count += 1;
+        },
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter() {
+                let key_path = key_path(path, key, maps);
+                collect_strings(value, &key_path, maps, values);
+            }
+        },
+        JsonValue::Array(array) => {
+            for element in array {
+                collect_strings(element, &format!("{}[]", path), maps, values);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Kinds of the values at every path in order of the kind, with a merged sample of the values of every kind
+pub fn value_kinds(samples: &[JsonValue], maps: &HashSet<String>) -> HashMap<String, Vec<(VariantKind, JsonValue)>> {
+    let mut kinds = HashMap::new();
+
+    for sample in samples {
+        collect_kinds(sample, "", maps, &mut kinds);
+    }
+
+    // Arrays which are always empty have no parser
+    for values in kinds.values_mut() {
+        values.retain(|(_, merged)| !(merged.is_array() && merged.is_empty()));
+        values.sort_by_key(|(kind, _)| *kind);
+    }
+
+    kinds
+}
+
+fn collect_kinds(value: &JsonValue, path: &str, maps: &HashSet<String>, kinds: &mut HashMap<String, Vec<(VariantKind, JsonValue)>>) {
+    let kind = match VariantKind::of(value) {
+        Some(kind) => kind,
+        None => return,
+    };
+
+    let values = kinds.entry(path.to_string()).or_default();
+    match values.iter_mut().find(|(seen, _)| *seen == kind) {
+        Some((_, merged)) => *merged = merge_values(merged.take(), value),
+        None => values.push((kind, merge_values(JsonValue::Null, value))),
+    }
+
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter() {
+                let key_path = key_path(path, key, maps);
+                collect_kinds(value, &key_path, maps, kinds);
+            }
+        },
+        JsonValue::Array(array) => {
+            for element in array {
+                collect_kinds(element, &format!("{}[]", path), maps, kinds);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Length of the longest array at every path
+pub fn array_lengths(samples: &[JsonValue], maps: &HashSet<String>) -> HashMap<String, usize> {
+    let mut lengths = HashMap::new();
+
+    for sample in samples {
+        collect_array_lengths(sample, "", maps, &mut lengths);
+    }
+
+    lengths
+}
+
+fn collect_array_lengths(value: &JsonValue, path: &str, maps: &HashSet<String>, lengths: &mut HashMap<String, usize>) {
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter() {
+                let key_path = key_path(path, key, maps);
+                collect_array_lengths(value, &key_path, maps, lengths);
+            }
+        },
+        JsonValue::Array(array) => {
+            let length = lengths.entry(path.to_string()).or_insert(0);
+            *length = (*length).max(array.len());
+
+            for element in array {
+                collect_array_lengths(element, &format!("{}[]", path), maps, lengths);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Length of the longest key of the maps at every path, as the raw characters between the quotes
+pub fn key_lengths(samples: &[JsonValue], maps: &HashSet<String>) -> HashMap<String, usize> {
+    let mut lengths = HashMap::new();
+
+    for sample in samples {
+        collect_key_lengths(sample, "", maps, &mut lengths);
+    }
+
+    lengths
+}
+
+fn collect_key_lengths(value: &JsonValue, path: &str, maps: &HashSet<String>, lengths: &mut HashMap<String, usize>) {
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object.iter() {
+                if maps.contains(path) {
+                    let length = lengths.entry(path.to_string()).or_insert(0);
+                    *length = (*length).max(JsonValue::from(key).dump().len() - 2);
+                }
+
+                collect_key_lengths(value, &key_path(path, key, maps), maps, lengths);
+            }
+        },
+        JsonValue::Array(array) => {
+            for element in array {
+                collect_key_lengths(element, &format!("{}[]", path), maps, lengths);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Path of the value of a key, the values of all keys of a map share the path <map>.*
+pub fn key_path(path: &str, key: &str, maps: &HashSet<String>) -> String {
+    let key = if maps.contains(path) { "*" } else { key };
+
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+// String values listed by the enum keywords of a JSON Schema, by the path of the field they describe
+pub fn schema_enums(schema: &JsonValue) -> HashMap<String, Vec<String>> {
+    let mut enums = HashMap::new();
+
+    collect_schema_enums(schema, "", &mut enums);
+
+    enums
+}
+
+fn collect_schema_enums(schema: &JsonValue, path: &str, enums: &mut HashMap<String, Vec<String>>) {
+    let values: Vec<String> = schema["enum"].members().filter_map(|value| value.as_str().map(|text| text.to_string())).collect();
+    if !values.is_empty() {
+        enums.insert(path.to_string(), values);
+    }
+
+    for (key, property) in schema["properties"].entries() {
+        let key_path = if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+        collect_schema_enums(property, &key_path, enums);
+    }
+
+    if schema["items"].is_object() {
+        collect_schema_enums(&schema["items"], &format!("{}[]", path), enums);
+    }
+}
+
+fn merge_values(merged: JsonValue, sample: &JsonValue) -> JsonValue {
+    match (merged, sample) {
+        // Nothing known yet, start from an empty container or take the value
+        (JsonValue::Null, JsonValue::Object(_)) => merge_values(JsonValue::new_object(), sample),
+        (JsonValue::Null, JsonValue::Array(_)) => merge_values(JsonValue::new_array(), sample),
+        (JsonValue::Null, sample) => sample.clone(),
+        // Objects contain the union of all keys in order of appearance
+        (JsonValue::Object(mut merged), JsonValue::Object(sample)) => {
+            for (key, value) in sample.iter() {
+                match merged.get_mut(key) {
+                    Some(merged_value) => *merged_value = merge_values(merged_value.take(), value),
+                    None => merged.insert(key, merge_values(JsonValue::Null, value)),
+                }
+            }
+
+            JsonValue::Object(merged)
+        },
+        // Arrays are reduced to a single element covering all elements
+        (JsonValue::Array(merged), JsonValue::Array(sample)) => {
+            let mut element = merged.into_iter().next().unwrap_or(JsonValue::Null);
+
+            for value in sample {
+                element = merge_values(element, value);
+            }
+
+            if element.is_null() {
+                JsonValue::Array(Vec::new())
+            } else {
+                JsonValue::Array(vec![element])
+            }
+        },
+        // For everything else the first seen type is kept
+        (merged, _) => merged,
+    }
+}
@@ -25,6 +25,15 @@ impl SignalManager {
         }
     }
 
+    // Remove the top-level outputs driven by an instance, e.g. when its output goes to another component instead
+    pub fn remove_output_signals(&mut self, inst_name: &str) {
+        self.output_signals.retain(|signal| !matches!(signal, TilSignal::Output { source_inst_name, .. } if source_inst_name == inst_name));
+    }
+
+    pub fn take_intermediate_signals(&mut self) -> Vec<TilSignal> {
+        std::mem::take(&mut self.intermediate_signals)
+    }
+
     pub fn get_intermediate_signals(&self) -> &Vec<TilSignal> {
         &self.intermediate_signals
     }
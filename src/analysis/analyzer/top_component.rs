@@ -3,17 +3,20 @@ use crate::analysis::types::{TilStreamlet, streaming_interface::TilStreamDirecti
 use super::{Analyzer, AnalyzerError, type_manager::StreamType};
 
 impl Analyzer {
+    // Type of the JSON input stream of the top component
+    pub fn get_input_stream_type(&self) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Json,
+            Some(StreamDim::new(None, 2, 0))
+        )
+    }
+
     pub fn assemble_top_component(&mut self) -> Result<TilStreamlet, AnalyzerError> {
         let mut top_component = TilStreamlet::new("top");
         
         let input_stream_name = "input";
-        top_component.get_streams_mut().add_stream(input_stream_name, TilStreamDirection::Input, 
-            StreamTypeDecl::new(
-                StreamType::Json,
-                Some(StreamDim::new(None, 2, 0))
-            )
-        );
-        self.type_manager.register(StreamType::Json);
+        top_component.get_streams_mut().add_stream(input_stream_name, TilStreamDirection::Input, self.get_input_stream_type());
+        self.type_manager.register(self.get_input_stream_type());
 
         let mut implementation = TilInlineImplementation::default(); 
 
@@ -43,8 +46,8 @@ impl Analyzer {
             implementation.add_signal(signal.clone());
         }
 
-        for inst in &self.entity_list {
-            implementation.add_instance(inst.get_name().to_string());
+        for inst in &self.instances {
+            implementation.add_instance(inst.clone());
         }
         
         top_component.set_implementation(TilImplementationType::Inline(implementation));
@@ -1,34 +1,72 @@
 use std::fmt::{Display, Formatter};
-use enumset::{EnumSetType, EnumSet};
+use enum_map::Enum;
+use enumset::EnumSetType;
 use indoc::writedoc;
 
-use crate::analysis::{GeneratorParams, components::Generatable};
+use crate::analysis::{GeneratorParams, components::Generatable, types::{stream_types::StreamTypeDecl, logical_type::LogicalType}};
 
 pub(super) struct TypeManager {
-    type_list: EnumSet<StreamType>,
+    type_list: Vec<StreamTypeDecl>,
+    // Named element types, declared before the stream types which use them
+    logical_types: Vec<LogicalType>,
+    // Names which are used for different types
+    conflicting_types: Vec<String>,
 }
 
 impl TypeManager {
     pub fn new() -> TypeManager {
         TypeManager {
-            type_list: EnumSet::new(),
+            type_list: Vec::new(),
+            logical_types: Vec::new(),
+            conflicting_types: Vec::new(),
         }
     }
 
     /// Register a new data type
-    pub fn register(&mut self, stream_type: StreamType) {
-        self.type_list.insert(stream_type);
+    pub fn register(&mut self, stream_type: StreamTypeDecl) {
+        if let Some(data) = stream_type.get_data_type() {
+            self.register_logical_type(data);
+        }
+
+        // Types are identified by their name, the dimension is given on use
+        if !self.type_list.iter().any(|registered| registered.get_name() == stream_type.get_name()) {
+            self.type_list.push(stream_type);
+        }
+    }
+
+    /// Register the named element types used by a type, the ones it depends on first
+    pub fn register_logical_type(&mut self, logical_type: &LogicalType) {
+        for named_type in logical_type.get_named_types() {
+            match self.logical_types.iter().find(|registered| registered.get_name() == named_type.get_name()) {
+                Some(registered) if *registered != named_type => self.conflicting_types.extend(named_type.get_name().map(str::to_string)),
+                Some(_) => (),
+                None => self.logical_types.push(named_type),
+            }
+        }
+    }
+
+    // Names of the types which are registered with different definitions
+    pub fn get_conflicting_types(&self) -> &Vec<String> {
+        &self.conflicting_types
+    }
+
+    // Get the named element type definitions in order of registration
+    pub fn get_logical_types(&self) -> &Vec<LogicalType> {
+        &self.logical_types
     }
 
-    pub fn register_from_component(&mut self, component: &dyn Generatable) {
-        for stream_type in component.get_streaming_types() {
-            self.register(stream_type);
+    pub fn register_from_component(&mut self, component: &dyn Generatable, gen_params: &GeneratorParams) {
+        for stream in component.get_streaming_interface(gen_params).get_streams() {
+            self.register(stream.get_type().clone());
         }
     }
 
     // Get stream type definitions
-    pub fn get_stream_types(&self) -> Vec<StreamType> {
-        self.type_list.iter().collect()
+    pub fn get_stream_types(&self) -> Vec<StreamTypeDecl> {
+        let mut stream_types = self.type_list.clone();
+        stream_types.sort_by_key(|t| (t.get_stream_type() as usize, t.get_name().to_owned()));
+
+        stream_types
     }
 }
 
@@ -38,7 +76,7 @@ impl Default for TypeManager {
     }
 }
 
-#[derive(EnumSetType)]
+#[derive(EnumSetType, Enum)]
 pub enum StreamType {
     Json,
     Int,
@@ -46,6 +84,13 @@ pub enum StreamType {
     Record,
     MatcherMatch,
     MatcherStr,
+    DecodedString,
+    Enum,
+    Filter,
+    Counter,
+    Presence,
+    Assembled,
+    Variant,
 }
 
 impl StreamType {
@@ -57,35 +102,90 @@ impl StreamType {
             StreamType::Record => "RecordParserStream",
             StreamType::MatcherMatch => "MatcherMatchStream",
             StreamType::MatcherStr => "MatcherStrStream",
+            StreamType::DecodedString => "DecodedStringStream",
+            StreamType::Enum => "EnumStream",
+            StreamType::Filter => "FilterStream",
+            StreamType::Counter => "CounterStream",
+            StreamType::Presence => "KeyPresenceStream",
+            StreamType::Assembled => "AssembledRecordStream",
+            StreamType::Variant => "VariantStream",
         }
     }
 
-    fn get_type_params(&self, gen_params: &GeneratorParams) -> StreamParams {
+    // Complexity and synchronicity the VHDL templates are written for
+    pub fn get_default_config(&self) -> StreamConfig {
+        match self {
+            StreamType::Json => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Int => StreamConfig::new(2, Synchronicity::Sync),
+            StreamType::Bool => StreamConfig::new(2, Synchronicity::Sync),
+            StreamType::Record => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::MatcherMatch => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::MatcherStr => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::DecodedString => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Enum => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Filter => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Counter => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Presence => StreamConfig::new(8, Synchronicity::Sync),
+            StreamType::Assembled => StreamConfig::new(2, Synchronicity::Sync),
+            StreamType::Variant => StreamConfig::new(2, Synchronicity::Sync),
+        }
+    }
+
+    // Configuration of the type after applying the project wide settings
+    pub fn get_config(&self, gen_params: &GeneratorParams) -> StreamConfig {
+        gen_params.stream_configs[*self].unwrap_or_else(|| self.get_default_config())
+    }
+
+    pub fn get_type_params(&self, gen_params: &GeneratorParams) -> StreamParams {
+        let config = self.get_config(gen_params);
+
         match self {
-            StreamType::Json =>  StreamParams::new(gen_params.bit_width, gen_params.epc, Dimensionality::Generic, Synchronicity::Sync, 8),
-            StreamType::Int => StreamParams::new(gen_params.int_width, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
-            StreamType::Bool => StreamParams::new(1, 1, Dimensionality::Generic, Synchronicity::Sync, 2),
-            StreamType::Record => StreamParams::new(gen_params.bit_width + 1, gen_params.epc, Dimensionality::Generic, Synchronicity::Sync, 8),
-            StreamType::MatcherMatch => StreamParams::new(1, gen_params.epc, Dimensionality::Fixed(1), Synchronicity::Sync, 8),
-            StreamType::MatcherStr => StreamParams::new(gen_params.bit_width, gen_params.epc, Dimensionality::Fixed(1), Synchronicity::Sync, 8),
+            StreamType::Json =>  StreamParams::new(LogicalType::Bits(gen_params.bit_width), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            StreamType::Int => StreamParams::new(LogicalType::Bits(gen_params.int_width), 1, Dimensionality::Generic, config.synchronicity, config.complexity),
+            StreamType::Bool => StreamParams::new(LogicalType::Bits(1), 1, Dimensionality::Generic, config.synchronicity, config.complexity),
+            StreamType::Record => StreamParams::new(LogicalType::Bits(gen_params.bit_width + 1), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            StreamType::MatcherMatch => StreamParams::new(LogicalType::Bits(1), gen_params.epc, Dimensionality::Fixed(1), config.synchronicity, config.complexity),
+            StreamType::MatcherStr => StreamParams::new(LogicalType::Bits(gen_params.bit_width), gen_params.epc, Dimensionality::Fixed(1), config.synchronicity, config.complexity),
+            StreamType::DecodedString => StreamParams::new(LogicalType::Bits(8), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Code and unknown flag, the width depends on the number of values
+            StreamType::Enum => StreamParams::new(LogicalType::Bits(2), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Selected bit of every value, string filters have a lane for every string which can close in a transfer
+            StreamType::Filter => StreamParams::new(LogicalType::Bits(1), 1, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Element count or index, the width depends on the length of the arrays
+            StreamType::Counter => StreamParams::new(LogicalType::Bits(4), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Bit for every key of a record, the width depends on the number of keys
+            StreamType::Presence => StreamParams::new(LogicalType::Bits(1), gen_params.epc, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Group of the fields of a record, set by the assembler
+            StreamType::Assembled => StreamParams::new(LogicalType::Null, 1, Dimensionality::Generic, config.synchronicity, config.complexity),
+            // Union of the kinds of a value, set by the variant
+            StreamType::Variant => StreamParams::new(LogicalType::Null, 1, Dimensionality::Generic, config.synchronicity, config.complexity),
         }
     }
+}
 
-    pub fn get_type_def_string(&self, gen_params: &GeneratorParams) -> String {
-        let type_params = self.get_type_params(gen_params);
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct StreamConfig {
+    pub complexity: u8,
+    pub synchronicity: Synchronicity,
+}
 
-        let dim_str = match type_params.dimensionality {
-            Dimensionality::Fixed(_) => "".to_string(),
-            Dimensionality::Generic => format!("<{}: dimensionality = 2>", Dimensionality::Generic),
-        };
+impl StreamConfig {
+    pub fn new(complexity: u8, synchronicity: Synchronicity) -> Self {
+        StreamConfig {
+            complexity,
+            synchronicity,
+        }
+    }
 
-        format!("type {}{} = {};\n\n", self.get_name(), dim_str, type_params)
+    // Suffix for the name of types which deviate from the project wide configuration
+    pub fn get_name_suffix(&self) -> String {
+        format!("C{}{:?}", self.complexity, self.synchronicity)
     }
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-struct StreamParams {
-    pub data_bits: usize,
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StreamParams {
+    pub data: LogicalType,
     pub throughput: usize,
     pub dimensionality: Dimensionality,
     pub synchronicity: Synchronicity,
@@ -93,15 +193,20 @@ struct StreamParams {
 }
 
 impl StreamParams {
-    fn new(data_bits: usize, throughput: usize, dimensionality: Dimensionality, synchronicity: Synchronicity, complexity: u8) -> Self {
+    pub fn new(data: LogicalType, throughput: usize, dimensionality: Dimensionality, synchronicity: Synchronicity, complexity: u8) -> Self {
         StreamParams {
-            data_bits,
+            data,
             throughput,
             dimensionality,
             synchronicity,
             complexity,
         }
     }
+
+    // Width of the data of an element
+    pub fn get_data_bits(&self) -> usize {
+        self.data.get_bits()
+    }
 }
 
 impl Display for StreamParams {
@@ -109,13 +214,13 @@ impl Display for StreamParams {
         writedoc!(
             f,
             "Stream (
-                data: Bits({}),
+                data: {},
                 throughput: {},
                 dimensionality: {},
                 synchronicity: {:?},
                 complexity: {},
             )",
-            self.data_bits,
+            self.data,
             self.throughput,
             self.dimensionality,
             self.synchronicity,
@@ -124,8 +229,8 @@ impl Display for StreamParams {
     }
 }
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-enum Dimensionality {
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Dimensionality {
     Fixed(usize),
     Generic
 }
@@ -139,9 +244,8 @@ impl std::fmt::Display for Dimensionality {
     }
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Synchronicity {
+pub enum Synchronicity {
     Sync,
     Flatten,
     Desync,
@@ -227,3 +331,24 @@ enum Synchronicity {
 //     }
 // }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_types_are_registered_once() {
+        let mut type_manager = TypeManager::new();
+        let flag = LogicalType::named("flag", LogicalType::Bits(1));
+        let reading = LogicalType::named("reading", LogicalType::Group(vec![("valid".to_string(), flag.clone())]));
+
+        type_manager.register_logical_type(&reading);
+        type_manager.register_logical_type(&flag);
+        assert_eq!(type_manager.get_logical_types(), &vec![flag, reading]);
+        assert!(type_manager.get_conflicting_types().is_empty());
+
+        type_manager.register_logical_type(&LogicalType::named("flag", LogicalType::Bits(2)));
+        assert_eq!(type_manager.get_logical_types().len(), 2);
+        assert_eq!(type_manager.get_conflicting_types(), &vec!["flag".to_string()]);
+    }
+}
@@ -1,34 +1,90 @@
-use crate::analysis::{types::{TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, TilSignal}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, TilSignal}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
 
-use super::{Array, JsonComponent, Generatable, JsonComponentValue};
+use super::{Array, ArrayCounters, ArraySlice, ArraySlicer, JsonComponent, Generatable, JsonComponentValue};
 
 impl Array {
-    pub fn new(name: &str, outer_nested: usize, inner_nested: usize, value: Option<Box<JsonComponent>>) -> Array {
+    pub fn new(name: &str, path: &str, epc: usize, outer_nested: usize, inner_nested: usize, value: Option<Box<JsonComponent>>) -> Array {
         Array {
             name: name.to_string(),
+            path: path.to_string(),
+            epc,
             outer_nested,
             inner_nested,
+            counters: ArrayCounters::default(),
+            counter_width: 4,
+            slicer: None,
             value,
         }
     }
+
+    // Emit the element count and index of the arrays with counters of the given width
+    pub fn with_counters(mut self, counters: ArrayCounters, counter_width: usize) -> Array {
+        self.counters = counters;
+        self.counter_width = counter_width;
+        self
+    }
+
+    // Drop the elements outside of the slice before they reach the element parser
+    pub fn with_slice(mut self, slicer_name: &str, slice: ArraySlice) -> Array {
+        let dest_inst_name = self.value.as_ref().map(|child| child.get_generatable().get_instance_name());
+        let output_name = self.get_output_name();
+
+        self.slicer = Some(ArraySlicer::new(slicer_name, &self.path, self.epc, self.outer_nested, slice, dest_inst_name, &output_name));
+        self
+    }
+
+    pub fn get_slicer(&self) -> Option<&ArraySlicer> {
+        self.slicer.as_ref()
+    }
+
+    pub fn get_value(&self) -> Option<&JsonComponent> {
+        self.value.as_deref()
+    }
+
+    pub fn get_counters(&self) -> ArrayCounters {
+        self.counters
+    }
+
+    // Name of the top-level stream with the elements if there is no element parser
+    pub fn get_output_name(&self) -> String {
+        format!("output_{}", self.get_instance_name())
+    }
+
+    // Name of the top-level stream with the number of elements of every array
+    pub fn get_count_name(&self) -> String {
+        format!("count_{}", self.get_instance_name())
+    }
+
+    // Name of the top-level stream with the index of every element
+    pub fn get_index_name(&self) -> String {
+        format!("index_{}", self.get_instance_name())
+    }
+
+    // A count for every array, or an index for every element of the arrays
+    fn get_counter_type(&self, dim_name: Option<String>, additive: isize, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Counter,
+            Some(StreamDim::new(dim_name, self.outer_nested, additive))
+        ).with_throughput(gen_params.get_throughput(self.epc)).with_data_bits(Some(self.counter_width))
+    }
 }
 
 impl Generatable for Array {
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {       
         let mut interface = TilStreamingInterface::default();
 
-        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
         let dim_name = "OUTER_NESTING_LEVEL";
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
         interface.add_generic(Generic::new("INNER_NESTING_LEVEL", GenericType::Natural(self.inner_nested)));
-        
+        interface.add_generic(Generic::new("ELEMENT_COUNTER_BW", GenericType::Natural(self.counter_width)));
 
         // Input type
         interface.add_stream("input", TilStreamDirection::Input,
             StreamTypeDecl::new( 
                 StreamType::Json,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         // Output type
@@ -36,24 +92,36 @@ impl Generatable for Array {
             StreamTypeDecl::new( 
                 StreamType::Json,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 2))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
-        interface
-    }
+        // Counter types
+        if self.counters.count {
+            interface.add_stream("count", TilStreamDirection::Output, self.get_counter_type(Some(dim_name.to_string()), 0, gen_params));
+        }
+        if self.counters.index {
+            interface.add_stream("index", TilStreamDirection::Output, self.get_counter_type(Some(dim_name.to_string()), 1, gen_params));
+        }
 
-    fn get_streaming_types(&self) -> Vec<StreamType> {
-        vec![StreamType::Json]
+        interface
     }
 
     fn get_nesting_level(&self) -> usize {
         self.outer_nested
     }
 
-    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
-        // First check if there is a child
-        match &self.value {
-            Some(child) => {
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        // First check if there is a slicer or a child
+        let mut signals = match (&self.slicer, &self.value) {
+            (Some(slicer), _) => vec![
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: slicer.get_instance_name(),
+                    dest_stream_name: "input".to_owned()
+                }
+            ],
+            (None, Some(child)) => {
                 // Convert child to generatable
                 let child = Box::<dyn Generatable>::from(child.clone());
 
@@ -66,8 +134,8 @@ impl Generatable for Array {
                     }
                 ]
             },
-            None => {
-                let output_name = format!("output_{}", self.get_instance_name());
+            (None, None) => {
+                let output_name = self.get_output_name();
 
                 vec![
                     TilSignal::Output { 
@@ -78,12 +146,34 @@ impl Generatable for Array {
                             StreamTypeDecl::new( 
                                 StreamType::Json,
                                 Some(StreamDim::new(None, self.outer_nested, 2))
-                            ) 
+                            ).with_throughput(gen_params.get_throughput(self.epc)).with_config(gen_params.get_field_config(&self.path))
                         )
                     }
                 ]
             },
-        }       
+        };
+
+        if self.counters.count {
+            let count_name = self.get_count_name();
+            signals.push(TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "count".to_owned(),
+                dest_stream_name: count_name.clone(),
+                output_stream: TilStream::new(&count_name, TilStreamDirection::Output, self.get_counter_type(None, 0, gen_params))
+            });
+        }
+
+        if self.counters.index {
+            let index_name = self.get_index_name();
+            signals.push(TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "index".to_owned(),
+                dest_stream_name: index_name.clone(),
+                output_stream: TilStream::new(&index_name, TilStreamDirection::Output, self.get_counter_type(None, 1, gen_params))
+            });
+        }
+
+        signals
     }
 
     fn num_outgoing_signals(&self) -> usize {
@@ -94,27 +184,53 @@ impl Generatable for Array {
         &self.name
     }
 
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
     fn get_file_type(&self) -> TemplateType {
         TemplateType::Array
     }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+        context.insert("inner_nesting", self.inner_nested);
+        context.insert("count", self.counters.count);
+        context.insert("index", self.counters.index);
+
+        context
+    }
 }
 
 impl JsonComponentValue for Array {
     fn to_graph_node(&self) -> String {
-        format!("Array parser\nO: {}, I: {}", self.outer_nested, self.inner_nested)
+        let mut node = format!("Array parser\nO: {}, I: {}", self.outer_nested, self.inner_nested);
+        if self.counters.count || self.counters.index {
+            node.push_str(&format!("\nCounter: {} bits", self.counter_width));
+        }
+
+        node
     }
 
     fn get_children(&self) -> Vec<JsonComponent> {
-        match &self.value {
-            Some(child) => vec![*child.clone()],
-            None => vec![],
+        let mut children = Vec::new();
+
+        if let Some(slicer) = &self.slicer {
+            children.push(JsonComponent::Slicer(slicer.clone()));
+        }
+        if let Some(child) = &self.value {
+            children.push(*child.clone());
         }
+
+        children
     }
 
     fn num_children(&self) -> usize {
-        match &self.value {
-            Some(_) => 1,
-            None => 0,
-        }
+        self.get_children().len()
     }
 }
\ No newline at end of file
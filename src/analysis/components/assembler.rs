@@ -0,0 +1,139 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, logical_type::LogicalType}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{RecordAssembler, Generatable, JsonComponent, JsonComponentValue, JsonType, Value};
+
+impl RecordAssembler {
+    pub fn new(name: &str, path: &str, epc: usize, group_name: &str, fields: Vec<(String, String, Value)>, outer_nested: usize, output_name: &str) -> RecordAssembler {
+        RecordAssembler {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            group_name: group_name.to_string(),
+            fields,
+            outer_nested,
+            output_name: output_name.to_string(),
+        }
+    }
+
+    pub fn get_fields(&self) -> &Vec<(String, String, Value)> {
+        &self.fields
+    }
+
+    pub fn get_output_name(&self) -> &str {
+        &self.output_name
+    }
+
+    // Group with the element type of every value as field
+    pub fn get_group(&self, gen_params: &GeneratorParams) -> LogicalType {
+        let fields = self.fields.iter()
+            .map(|(_, name, value)| (name.clone(), value.get_output_type(None, gen_params).get_type_params(gen_params).data))
+            .collect();
+
+        LogicalType::named(&self.group_name, LogicalType::Group(fields))
+    }
+
+    // One element for every record
+    fn get_output_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Assembled,
+            Some(StreamDim::new(dim_name, self.outer_nested, -1))
+        ).with_data_type(self.get_group(gen_params))
+    }
+}
+
+impl Generatable for RecordAssembler {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "OUTER_NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input types, the outputs of the value parsers
+        for (_, name, value) in &self.fields {
+            interface.add_stream(&format!("field_{}", name), TilStreamDirection::Input, value.get_output_type(Some(dim_name.to_string()), gen_params));
+        }
+
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output, self.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        vec![
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "output".to_owned(),
+                dest_stream_name: self.output_name.clone(),
+                output_stream: TilStream::new(&self.output_name, TilStreamDirection::Output, self.get_output_type(None, gen_params))
+            }
+        ]
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Assembler
+    }
+
+    fn get_template_values(&self, gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+
+        let group = self.get_group(gen_params);
+        context.insert("group_bits", group.get_bits());
+
+        // Position of every field in the group
+        let mut fields = Vec::new();
+        let mut low = 0;
+        for (index, ((key, name, value), (_, field))) in self.fields.iter().zip(group.get_fields()).enumerate() {
+            let bits = field.get_bits();
+            let mut field_context = TemplateContext::new();
+            field_context.insert("index", index);
+            field_context.insert("name", name.as_str());
+            field_context.insert("key", key.as_str());
+            field_context.insert("boolean", *value.get_data_type() == JsonType::Boolean);
+            field_context.insert("bits", bits);
+            field_context.insert("low", low);
+            field_context.insert("high", low + bits - 1);
+            fields.push(field_context);
+            low += bits;
+        }
+        context.insert("num_fields", self.fields.len());
+        context.insert("fields", fields);
+
+        context
+    }
+}
+
+impl JsonComponentValue for RecordAssembler {
+    fn to_graph_node(&self) -> String {
+        format!("Record assembler\nO: {}, fields: {}", self.outer_nested, self.fields.len())
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        Vec::new()
+    }
+
+    fn num_children(&self) -> usize {
+        0
+    }
+}
@@ -0,0 +1,82 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{WidthConverter, Generatable};
+
+impl WidthConverter {
+    pub fn new(name: &str, path: &str, epc_in: usize, epc_out: usize, nesting: usize) -> WidthConverter {
+        WidthConverter {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc_in,
+            epc_out,
+            nesting,
+        }
+    }
+}
+
+impl Generatable for WidthConverter {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC_IN", GenericType::Positive(self.epc_in)));
+        interface.add_generic(Generic::new("EPC_OUT", GenericType::Positive(self.epc_out)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.nesting)));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.nesting, 0))
+            ).with_throughput(gen_params.get_throughput(self.epc_in))
+        );
+
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.nesting, 0))
+            ).with_throughput(gen_params.get_throughput(self.epc_out))
+        );
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.nesting
+    }
+
+    // The converter is wired in by the analyzer
+    fn get_outgoing_signals(&self, _gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        Vec::new()
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        0
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc_out
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::WidthConverter
+    }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("epc_in", self.epc_in);
+        context.insert("epc_out", self.epc_out);
+        context.insert("nesting", self.nesting);
+
+        context
+    }
+}
@@ -0,0 +1,212 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{EnumEncoder, Generatable, JsonComponent, Matcher, JsonComponentValue};
+
+// Characters with a meaning in a regex
+const REGEX_SPECIAL: &str = "\\.^$|?*+()[]{}";
+
+impl EnumEncoder {
+    pub fn new(name: &str, path: &str, epc: usize, matcher_name: &str, values: Vec<String>, outer_nested: usize) -> EnumEncoder {
+        // The matcher sees the characters as they are in the JSON, so the values are matched in their escaped form
+        let patterns = values.iter()
+            .map(|value| {
+                // Only the enclosing quotes are removed, a value can end with an escaped quote
+                let json = json::stringify(value.as_str());
+                escape_regex(&json[1..json.len() - 1])
+            })
+            .collect();
+
+        EnumEncoder {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            matcher: Matcher::new_set(matcher_name, path, epc, name, patterns, outer_nested + 1),
+            values,
+            outer_nested,
+        }
+    }
+
+    pub fn get_matcher(&self) -> &Matcher {
+        &self.matcher
+    }
+
+    pub fn get_values(&self) -> &Vec<String> {
+        &self.values
+    }
+
+    // Code of a value, None if it is not one of the values
+    pub fn get_code(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|known| known == value)
+    }
+
+    // Number of bits of the code, without the unknown flag
+    pub fn get_code_width(&self) -> usize {
+        let mut bits = 1;
+        while (1 << bits) < self.values.len() {
+            bits += 1;
+        }
+
+        bits
+    }
+
+    fn get_output_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Enum,
+            Some(StreamDim::new(dim_name, self.outer_nested, 0))
+        ).with_throughput(gen_params.get_throughput(self.epc)).with_data_bits(Some(self.get_code_width() + 1)).with_config(gen_params.get_field_config(&self.path))
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if REGEX_SPECIAL.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+impl Generatable for EnumEncoder {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+        interface.add_generic(Generic::new("CODE_WIDTH", GenericType::Positive(self.get_code_width())));
+        interface.add_generic(Generic::new("NUM_VALUES", GenericType::Positive(self.values.len())));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            ).with_throughput(gen_params.get_throughput(self.epc))
+        );
+
+        // Matcher type
+        interface.add_stream("matcher_str", TilStreamDirection::Output,
+            StreamTypeDecl::new(
+                StreamType::MatcherStr,
+                None
+            ).with_throughput(gen_params.get_throughput(self.epc))
+        );
+        interface.add_stream("matcher_match", TilStreamDirection::Input,
+            self.matcher.get_streaming_interface(gen_params).get_output_streams()[0].get_type().clone()
+        );
+
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output, self.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        let output_name = format!("output_{}", self.get_instance_name());
+
+        vec![
+            TilSignal::Intermediate {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "matcher_str".to_owned(),
+                dest_inst_name: self.matcher.get_instance_name(),
+                dest_stream_name: "input".to_owned()
+            },
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "output".to_owned(),
+                dest_stream_name: output_name.clone(),
+                output_stream: TilStream::new(&output_name, TilStreamDirection::Output, self.get_output_type(None, gen_params))
+            }
+        ]
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        2
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Enum
+    }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+        context.insert("code_width", self.get_code_width());
+
+        let mut values = Vec::new();
+        for (code, value) in self.values.iter().enumerate() {
+            let mut value_context = TemplateContext::new();
+            value_context.insert("code", code);
+            value_context.insert("value", json::stringify(value.as_str()));
+            values.push(value_context);
+        }
+        context.insert("values", values);
+
+        context
+    }
+}
+
+impl JsonComponentValue for EnumEncoder {
+    fn to_graph_node(&self) -> String {
+        format!("Enum encoder\nO: {}\n{} values", self.outer_nested, self.values.len())
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        vec![JsonComponent::Matcher(self.matcher.clone())]
+    }
+
+    fn num_children(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regex_special_characters_are_escaped() {
+        assert_eq!(escape_regex("celsius"), "celsius");
+        assert_eq!(escape_regex("a.b"), "a\\.b");
+        assert_eq!(escape_regex("^(x|y)*+?$"), "\\^\\(x\\|y\\)\\*\\+\\?\\$");
+        assert_eq!(escape_regex("[0-9]{2}"), "\\[0-9\\]\\{2\\}");
+        assert_eq!(escape_regex("\\"), "\\\\");
+    }
+
+    #[test]
+    fn values_are_matched_as_they_are_in_the_json() {
+        let values = vec!["m/s".to_string(), "say \"hi\"".to_string(), "a\\b".to_string(), "1.5".to_string()];
+        let encoder = EnumEncoder::new("unit_encoder", "unit", 4, "unit_matcher", values, 1);
+
+        // Quotes and backslashes are escaped in the JSON, the backslashes of the escape sequences are escaped for the regex
+        assert_eq!(encoder.get_matcher().get_matchers(), &vec![
+            "m/s".to_string(),
+            "say \\\\\"hi\\\\\"".to_string(),
+            "a\\\\\\\\b".to_string(),
+            "1\\.5".to_string(),
+        ]);
+        assert_eq!(encoder.get_code("1.5"), Some(3));
+        assert_eq!(encoder.get_code("1x5"), None);
+        assert_eq!(encoder.get_code_width(), 2);
+    }
+}
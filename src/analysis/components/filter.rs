@@ -0,0 +1,297 @@
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{CompareOp, Filter, FilterPredicate, Generatable, JsonComponent, JsonComponentValue, JsonType, Matcher, Value};
+
+impl CompareOp {
+    pub fn compare(&self, left: u64, right: u64) -> bool {
+        match self {
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+        }
+    }
+
+    // Operator written as in Rust (e.g. ">=")
+    pub fn from_symbol(symbol: &str) -> Option<CompareOp> {
+        [CompareOp::Eq, CompareOp::Ne, CompareOp::Lt, CompareOp::Le, CompareOp::Gt, CompareOp::Ge].into_iter()
+            .find(|op| op.get_symbol() == symbol)
+    }
+
+    fn get_symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
+    // Operator in VHDL
+    fn get_vhdl_symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "/=",
+            op => op.get_symbol(),
+        }
+    }
+}
+
+impl FilterPredicate {
+    // Type of the values the predicate can be tested on
+    pub fn get_data_type(&self) -> JsonType {
+        match self {
+            FilterPredicate::Compare(..) => JsonType::Integer,
+            FilterPredicate::Bool(_) => JsonType::Boolean,
+            FilterPredicate::Regex(_) => JsonType::String,
+        }
+    }
+}
+
+impl Display for FilterPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterPredicate::Compare(op, value) => write!(f, "{} {}", op.get_symbol(), value),
+            FilterPredicate::Bool(value) => write!(f, "is {}", value),
+            FilterPredicate::Regex(regex) => write!(f, "matches /{}/", regex),
+        }
+    }
+}
+
+impl Filter {
+    pub fn new(name: &str, path: &str, filter_name: &str, predicate: FilterPredicate, value: Value, matcher_name: &str) -> Filter {
+        let epc = value.get_epc();
+
+        // The matcher sees the raw characters of the string
+        let matcher = match &predicate {
+            FilterPredicate::Regex(regex) => Some(Matcher::new(matcher_name, path, epc, name, regex.clone(), value.get_nesting_level() + 1)),
+            _ => None,
+        };
+
+        Filter {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            filter_name: filter_name.to_string(),
+            predicate,
+            value: Box::new(value),
+            matcher,
+        }
+    }
+
+    pub fn get_filter_name(&self) -> &str {
+        &self.filter_name
+    }
+
+    pub fn get_predicate(&self) -> &FilterPredicate {
+        &self.predicate
+    }
+
+    pub fn get_matcher(&self) -> Option<&Matcher> {
+        self.matcher.as_ref()
+    }
+
+    // Name of the top-level stream with the value passed through
+    pub fn get_output_name(&self) -> String {
+        format!("output_{}", self.value.get_instance_name())
+    }
+
+    // Name of the top-level stream with the selected bits
+    pub fn get_selected_name(&self) -> String {
+        format!("selected_{}", self.filter_name)
+    }
+
+    // One bit for every value, a string filter has a lane for every string which can close in a transfer
+    fn get_selected_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        let throughput = match self.predicate {
+            FilterPredicate::Regex(_) => Some(self.epc),
+            _ => None,
+        };
+
+        StreamTypeDecl::new(
+            StreamType::Filter,
+            Some(StreamDim::new(dim_name, self.value.get_nesting_level(), 0))
+        ).with_throughput(throughput).with_config(gen_params.get_field_config(&self.path))
+    }
+}
+
+impl Generatable for Filter {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        let dim_name = "NESTING_LEVEL";
+        match self.predicate {
+            FilterPredicate::Compare(..) => {
+                interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.value.get_nesting_level())));
+                interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(gen_params.int_width)));
+            },
+            FilterPredicate::Bool(_) => {
+                interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.value.get_nesting_level())));
+            },
+            FilterPredicate::Regex(_) => {
+                interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
+                interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.value.get_nesting_level())));
+            },
+        }
+
+        // The value is passed through unchanged
+        interface.add_stream("input", TilStreamDirection::Input, self.value.get_output_type(Some(dim_name.to_string()), gen_params));
+        interface.add_stream("output", TilStreamDirection::Output, self.value.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        // Matcher type
+        if let Some(matcher) = &self.matcher {
+            interface.add_stream("matcher_str", TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::MatcherStr,
+                    None
+                ).with_throughput(gen_params.get_throughput(self.epc))
+            );
+            interface.add_stream("matcher_match", TilStreamDirection::Input,
+                matcher.get_streaming_interface(gen_params).get_output_streams()[0].get_type().clone()
+            );
+        }
+
+        interface.add_stream("selected", TilStreamDirection::Output, self.get_selected_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.value.get_nesting_level()
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        let output_name = self.get_output_name();
+        let selected_name = self.get_selected_name();
+
+        let mut signals = vec![
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "output".to_owned(),
+                dest_stream_name: output_name.clone(),
+                output_stream: TilStream::new(&output_name, TilStreamDirection::Output, self.value.get_output_type(None, gen_params))
+            },
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "selected".to_owned(),
+                dest_stream_name: selected_name.clone(),
+                output_stream: TilStream::new(&selected_name, TilStreamDirection::Output, self.get_selected_type(None, gen_params))
+            },
+        ];
+
+        if let Some(matcher) = &self.matcher {
+            signals.push(TilSignal::Intermediate {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "matcher_str".to_owned(),
+                dest_inst_name: matcher.get_instance_name(),
+                dest_stream_name: "input".to_owned()
+            });
+        }
+
+        signals
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        match self.matcher {
+            Some(_) => 3,
+            None => 2,
+        }
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Filter
+    }
+
+    fn get_template_values(&self, gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.value.get_nesting_level());
+        context.insert("predicate", self.predicate.to_string());
+        context.insert("compare", matches!(self.predicate, FilterPredicate::Compare(..)));
+        context.insert("bool", matches!(self.predicate, FilterPredicate::Bool(_)));
+        context.insert("regex", matches!(self.predicate, FilterPredicate::Regex(_)));
+
+        match &self.predicate {
+            // The constant as a bit string, so it is not limited to the range of a VHDL integer
+            FilterPredicate::Compare(op, value) => {
+                let width = gen_params.int_width.min(64);
+                let masked = if width == 64 { *value } else { value & ((1 << width) - 1) };
+                context.insert("operator", op.get_vhdl_symbol());
+                context.insert("constant", format!("{:0width$b}", masked, width = width));
+            },
+            FilterPredicate::Bool(value) => {
+                context.insert("expected", if *value { "'1'" } else { "'0'" });
+            },
+            FilterPredicate::Regex(_) => (),
+        }
+
+        context
+    }
+}
+
+impl JsonComponentValue for Filter {
+    fn to_graph_node(&self) -> String {
+        format!("Filter {}\nO: {}\n{}", self.filter_name, self.value.get_nesting_level(), self.predicate)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        match &self.matcher {
+            Some(matcher) => vec![JsonComponent::Matcher(matcher.clone())],
+            None => Vec::new(),
+        }
+    }
+
+    fn num_children(&self) -> usize {
+        self.get_children().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_ops() {
+        let cases = [
+            (CompareOp::Eq, [false, true, false]),
+            (CompareOp::Ne, [true, false, true]),
+            (CompareOp::Lt, [true, false, false]),
+            (CompareOp::Le, [true, true, false]),
+            (CompareOp::Gt, [false, false, true]),
+            (CompareOp::Ge, [false, true, true]),
+        ];
+
+        // Values below, equal to and above the constant, which are compared unsigned
+        for (op, expected) in cases {
+            assert_eq!([0, 1000, u64::MAX].map(|value| op.compare(value, 1000)), expected, "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn compare_op_symbols() {
+        for op in [CompareOp::Eq, CompareOp::Ne, CompareOp::Lt, CompareOp::Le, CompareOp::Gt, CompareOp::Ge] {
+            assert_eq!(CompareOp::from_symbol(op.get_symbol()), Some(op));
+        }
+
+        assert_eq!(CompareOp::from_symbol("="), None);
+        assert_eq!(CompareOp::Ne.get_vhdl_symbol(), "/=");
+        assert_eq!(FilterPredicate::Compare(CompareOp::Ge, 5).to_string(), ">= 5");
+    }
+}
@@ -1,32 +1,88 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
 
 use super::{Key, Generatable, JsonComponent, Matcher, JsonComponentValue};
 
 impl Key {
-    pub fn new(name: &str, matcher: Matcher, outer_nested: usize, value: Option<Box<JsonComponent>>) -> Key {
+    pub fn new(name: &str, path: &str, epc: usize, matcher: Matcher, outer_nested: usize, optional: bool, value: Option<Box<JsonComponent>>) -> Key {
         Key {
             name: name.to_string(),
+            path: path.to_string(),
+            epc,
             matcher,
             outer_nested,
+            optional,
+            key_name_length: None,
+            presence: None,
             value
         }
     }
+
+    // Emit the name of every matched key, zero padded or truncated to the given number of bytes
+    pub fn with_key_name(mut self, length: usize) -> Key {
+        self.key_name_length = Some(length);
+        self
+    }
+
+    // Send the match results of the key to the key presence component of the record
+    pub fn with_presence(mut self, presence_inst_name: &str, bit: usize) -> Key {
+        self.presence = Some((presence_inst_name.to_string(), bit));
+        self
+    }
+
+    // Whether the key matches all keys of a map by a pattern
+    pub fn is_map_key(&self) -> bool {
+        self.key_name_length.is_some()
+    }
+
+    pub fn get_key_name_length(&self) -> Option<usize> {
+        self.key_name_length
+    }
+
+    // Name of the top-level stream with the key names
+    pub fn get_key_name_output_name(&self) -> String {
+        format!("key_name_{}", self.get_instance_name())
+    }
+
+    // Whether the key is missing in some of the samples
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn get_key(&self) -> &str {
+        self.matcher.get_matcher()
+    }
+
+    pub fn get_value(&self) -> Option<&JsonComponent> {
+        self.value.as_deref()
+    }
+
+    // One element with all bytes of the key for every matched key
+    fn get_key_name_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> Option<StreamTypeDecl> {
+        self.key_name_length.map(|length| StreamTypeDecl::new(
+                StreamType::DecodedString,
+                Some(StreamDim::new(dim_name, self.outer_nested, 0))
+            ).with_throughput(gen_params.get_throughput(1)).with_data_bits(Some(8 * length))
+        )
+    }
 }
 
 impl Generatable for Key {
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
         let mut interface = TilStreamingInterface::default();
 
-        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
         let dim_name = "OUTER_NESTING_LEVEL";
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+        if let Some(length) = self.key_name_length {
+            interface.add_generic(Generic::new("KEY_NAME_LENGTH", GenericType::Positive(length)));
+        }
 
         // Input type
         interface.add_stream("input", TilStreamDirection::Input, 
             StreamTypeDecl::new(
                 StreamType::Record,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         // Matcher type
@@ -34,34 +90,47 @@ impl Generatable for Key {
             StreamTypeDecl::new(
                 StreamType::MatcherStr,
                 None
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
         interface.add_stream("matcher_match", TilStreamDirection::Input, 
             StreamTypeDecl::new(
                 StreamType::MatcherMatch,
                 None
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
-        // Output type
+        // Copy of the match results for the key presence component
+        if self.presence.is_some() {
+            interface.add_stream("presence", TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::MatcherMatch,
+                    None
+                ).with_throughput(gen_params.get_throughput(self.epc))
+            );
+        }
+        // Key name type
+        if let Some(key_name_type) = self.get_key_name_type(Some(dim_name.to_string()), gen_params) {
+            interface.add_stream("key_name", TilStreamDirection::Output, key_name_type);
+        }
+        // Output type, only configurable per field if the key is a leaf
+        let output_config = match self.value {
+            Some(_) => None,
+            None => gen_params.get_field_config(&self.path),
+        };
         interface.add_stream("output", TilStreamDirection::Output, 
             StreamTypeDecl::new(
                 StreamType::Json,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc)).with_config(output_config)
         );      
 
         interface
     }
 
-    fn get_streaming_types(&self) -> Vec<StreamType> {
-        vec![StreamType::Record, StreamType::MatcherStr, StreamType::MatcherMatch, StreamType::Json]
-    }
-
     fn get_nesting_level(&self) -> usize {
         self.outer_nested
     }
 
-    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
         let mut signals = vec![
             TilSignal::Intermediate { 
                 source_inst_name: self.get_instance_name(), 
@@ -98,32 +167,78 @@ impl Generatable for Key {
                             StreamTypeDecl::new( 
                                 StreamType::Json,
                                 Some(StreamDim::new(None, self.outer_nested, 1))
-                            ) 
+                            ).with_throughput(gen_params.get_throughput(self.epc)).with_config(gen_params.get_field_config(&self.path))
                         )
                     }
                 );
             }
         };
+
+        if let Some(key_name_type) = self.get_key_name_type(None, gen_params) {
+            let key_name_output = self.get_key_name_output_name();
+
+            signals.push(
+                TilSignal::Output {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "key_name".to_owned(),
+                    dest_stream_name: key_name_output.clone(),
+                    output_stream: TilStream::new(&key_name_output, TilStreamDirection::Output, key_name_type)
+                }
+            );
+        }
+
+        if let Some((presence_inst_name, bit)) = &self.presence {
+            signals.push(
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "presence".to_owned(),
+                    dest_inst_name: presence_inst_name.clone(),
+                    dest_stream_name: format!("match_{}", bit)
+                }
+            );
+        }
         
         signals
     }
 
     fn num_outgoing_signals(&self) -> usize {
-        2
+        2 + usize::from(self.key_name_length.is_some()) + usize::from(self.presence.is_some())
     }
 
     fn get_name(&self) -> &str {
         &self.name
     }
 
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
     fn get_file_type(&self) -> TemplateType {
         TemplateType::Key
     }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("key", self.get_key());
+        context.insert("outer_nesting", self.outer_nested);
+        context.insert("optional", self.optional);
+        context.insert("key_name", self.key_name_length.is_some());
+        context.insert("presence", self.presence.is_some());
+
+        context
+    }
 }
 
 impl JsonComponentValue for Key {
     fn to_graph_node(&self) -> String {
-        format!("Key filter\nO: {}", self.outer_nested)
+        match self.key_name_length {
+            Some(_) => format!("Map key filter\nO: {}", self.outer_nested),
+            None => format!("Key filter\nO: {}", self.outer_nested),
+        }
     }
 
     fn get_children(&self) -> Vec<JsonComponent> {
@@ -1,19 +1,46 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::StreamTypeDecl}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::StreamTypeDecl}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
 
 use super::{JsonComponent, Matcher, Generatable, JsonComponentValue};
 
 impl Matcher {
-    pub fn new(name: &str, holder_name: &str, matcher: String, outer_nested: usize) -> Matcher {
+    pub fn new(name: &str, path: &str, epc: usize, holder_name: &str, matcher: String, outer_nested: usize) -> Matcher {
         Matcher {
             name: name.to_string(),
+            path: path.to_string(),
+            epc,
             holder_name: holder_name.to_string(),
-            matcher,
+            matchers: vec![matcher],
+            outer_nested
+        }
+    }
+
+    // Matcher for a set of strings, which reports for every string if it matched
+    pub fn new_set(name: &str, path: &str, epc: usize, holder_name: &str, matchers: Vec<String>, outer_nested: usize) -> Matcher {
+        Matcher {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            holder_name: holder_name.to_string(),
+            matchers,
             outer_nested
         }
     }
 
     pub fn get_matcher(&self) -> &str {
-        &self.matcher
+        &self.matchers[0]
+    }
+
+    pub fn get_matchers(&self) -> &Vec<String> {
+        &self.matchers
+    }
+
+    // Width of the match stream, one bit per string
+    fn get_match_bits(&self) -> Option<usize> {
+        if self.matchers.len() > 1 {
+            Some(self.matchers.len())
+        } else {
+            None
+        }
     }
 }
 
@@ -21,14 +48,14 @@ impl Generatable for Matcher {
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
         let mut interface = TilStreamingInterface::default();
 
-        interface.add_generic(Generic::new("BPC", GenericType::Positive(gen_params.epc)));
+        interface.add_generic(Generic::new("BPC", GenericType::Positive(self.epc)));
 
         // Input type
         interface.add_stream("input", TilStreamDirection::Input, 
             StreamTypeDecl::new(
                 StreamType::MatcherStr,
                 None
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         // Output type
@@ -36,21 +63,17 @@ impl Generatable for Matcher {
             StreamTypeDecl::new(
                 StreamType::MatcherMatch,
                 None
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc)).with_data_bits(self.get_match_bits())
         );
 
         interface
     }
 
-    fn get_streaming_types(&self) -> Vec<StreamType> {
-        vec![StreamType::MatcherStr, StreamType::MatcherMatch]
-    }
-
     fn get_nesting_level(&self) -> usize {
         self.outer_nested
     }
 
-    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+    fn get_outgoing_signals(&self, _gen_params: &GeneratorParams) -> Vec<TilSignal> {
         vec![
             TilSignal::Intermediate { 
                 source_inst_name: self.get_instance_name(), 
@@ -69,14 +92,37 @@ impl Generatable for Matcher {
         &self.name
     }
 
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
     fn get_file_type(&self) -> TemplateType {
-        TemplateType::Matcher(self.matcher.clone())
+        TemplateType::Matcher(self.matchers.clone())
+    }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("matcher", self.get_matcher());
+
+        let mut matchers = Vec::new();
+        for matcher in &self.matchers {
+            let mut matcher_context = TemplateContext::new();
+            matcher_context.insert("matcher", matcher.as_str());
+            matchers.push(matcher_context);
+        }
+        context.insert("matchers", matchers);
+
+        context
     }
 }
 
 impl JsonComponentValue for Matcher {
     fn to_graph_node(&self) -> String {
-        format!("Regex matcher\n\"{}\"", self.matcher)
+        format!("Regex matcher\n\"{}\"", self.matchers.join("\" | \""))
     }
 
     fn get_children(&self) -> Vec<JsonComponent> {
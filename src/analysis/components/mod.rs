@@ -1,11 +1,83 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JsonType {
     String,
     Integer,
     Boolean,
 }
 
-use super::{types::{TilStreamingInterface, TilSignal, TilStreamlet, til_streamlet::TilImplementationType}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+// How the string parser emits a string value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringMode {
+    // Characters between the quotes as they are in the JSON, escape sequences are kept
+    #[default]
+    Raw,
+    // Escape sequences decoded into UTF-8 bytes, one sequence per string
+    Decoded,
+    // Decoded bytes zero padded or truncated to the given number of bytes, one element per string
+    Fixed(usize),
+    // Decoded bytes preceded by their count as 2 bytes big-endian, truncated to the given maximum
+    LengthPrefixed(usize),
+}
+
+// Longest fixed string, all of its bytes are the data of one element
+pub const MAX_FIXED_LENGTH: usize = 1024;
+
+// Longest length prefixed string, the length has to fit in the 2 byte prefix
+pub const MAX_PREFIXED_LENGTH: usize = 65535;
+
+// Extra outputs of an array parser
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArrayCounters {
+    // Number of elements of every array
+    pub count: bool,
+    // Index of every element in its array
+    pub index: bool,
+    // Width of the counters, derived from the longest array in the samples if not set
+    pub width: Option<usize>,
+}
+
+// Elements of an array which are kept, by their index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArraySlice {
+    pub start: usize,
+    // First index which is not kept, all elements from the start are kept without an end
+    pub end: Option<usize>,
+    pub stride: usize,
+}
+
+// Comparison of an integer field with a constant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+// Condition a filter tests on every value of a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterPredicate {
+    // Integer compared with a constant, the int parser emits unsigned values
+    Compare(CompareOp, u64),
+    // Boolean equal to the given value
+    Bool(bool),
+    // Raw characters of a string fully matched by a regex
+    Regex(String),
+}
+
+// Kind of a JSON value, told apart by its first character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VariantKind {
+    Integer,
+    String,
+    Boolean,
+    Object,
+    Array,
+}
+
+use super::{types::{TilStreamingInterface, TilSignal, TilStreamlet, til_streamlet::TilImplementationType}, GeneratorParams, analyzer::file_manager::{TemplateType, template::TemplateContext}};
 
 pub trait Generatable {
     
@@ -17,11 +89,12 @@ pub trait Generatable {
     // fn get_input_type_params(&self, gen_params: &GeneratorParams) -> StreamType;
     // fn get_output_type_params(&self, gen_params: &GeneratorParams) -> StreamType;
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface;
-    fn get_streaming_types(&self) -> Vec<StreamType>;
-    fn get_outgoing_signals(&self) -> Vec<TilSignal>;
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal>;
     fn num_outgoing_signals(&self) -> usize;
     fn get_nesting_level(&self) -> usize;
     fn get_name(&self) -> &str;
+    fn get_path(&self) -> &str;
+    fn get_epc(&self) -> usize;
 
     fn to_til_streamlet(&self, gen_params: &GeneratorParams) -> TilStreamlet {
         // Create a new component
@@ -44,6 +117,54 @@ pub trait Generatable {
     }
 
     fn get_file_type(&self) -> TemplateType;
+
+    // Component specific values for the VHDL template
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        TemplateContext::new()
+    }
+
+    // All values the VHDL template of the component can use
+    fn get_template_context(&self, gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+
+        // Project wide values
+        context.insert("namespace", gen_params.comp_namespace.as_str());
+        context.insert("project_name", gen_params.project_name.as_str());
+        context.insert("bit_width", gen_params.bit_width);
+        context.insert("int_width", gen_params.int_width);
+
+        // Values of the component
+        context.insert("comp_name", self.get_name());
+        context.insert("path", self.get_path());
+        context.insert("epc", self.get_epc());
+        context.insert("nesting_level", self.get_nesting_level());
+
+        let interface = self.get_streaming_interface(gen_params);
+
+        let mut generics = Vec::new();
+        for generic in interface.get_generics() {
+            let mut generic_context = TemplateContext::new();
+            generic_context.insert("name", generic.get_name());
+            generic_context.insert("type", generic.get_type().get_type_name());
+            generic_context.insert("value", generic.get_type().get_value());
+            generics.push(generic_context);
+        }
+        context.insert("generics", generics);
+
+        let mut ports = Vec::new();
+        for stream in interface.get_streams() {
+            let mut port_context = TemplateContext::new();
+            port_context.insert("name", stream.get_name());
+            port_context.insert("direction", stream.get_direction().to_string());
+            port_context.insert("type", stream.get_type().get_resolved_string());
+            ports.push(port_context);
+        }
+        context.insert("ports", ports);
+
+        context.extend(self.get_template_values(gen_params));
+
+        context
+    }
 }
 
 pub trait JsonComponentValue {
@@ -60,52 +181,178 @@ pub enum JsonComponent {
     Record(Record),
     Key(Key),
     Matcher(Matcher),
+    Enum(EnumEncoder),
+    Filter(Filter),
+    Slicer(ArraySlicer),
+    Presence(KeyPresence),
+    Assembler(RecordAssembler),
+    Variant(Variant),
 }
 
 mod value;
 #[derive(Clone)]
 pub struct Value {
     name: String,
+    path: String,
+    epc: usize,
     data_type: JsonType,
-    outer_nested: usize
+    outer_nested: usize,
+    string_mode: StringMode,
+    filter: Option<Box<Filter>>,
+    // Instance and stream the output goes to instead of a top-level output
+    dest: Option<(String, String)>,
 }
 
 mod array;
 #[derive(Clone)]
 pub struct Array {
     name: String,
+    path: String,
+    epc: usize,
     outer_nested: usize,
     inner_nested: usize,
+    counters: ArrayCounters,
+    // Width of the element counter, arrays with more elements wrap around
+    counter_width: usize,
+    // Slicer between the array and its elements
+    slicer: Option<ArraySlicer>,
     value: Option<Box<JsonComponent>>
 }
 
+mod slicer;
+#[derive(Clone)]
+pub struct ArraySlicer {
+    name: String,
+    path: String,
+    epc: usize,
+    outer_nested: usize,
+    slice: ArraySlice,
+    // Instance the kept elements go to, the top-level output of the array if there is none
+    dest_inst_name: Option<String>,
+    output_name: String,
+}
+
 mod record;
 #[derive(Clone)]
 pub struct Record {
     name: String,
+    path: String,
+    epc: usize,
     outer_nested: usize,
     inner_nested: usize,
-    keys: Vec<Key>
+    keys: Vec<Key>,
+    // Emits which keys were present in every record
+    presence: Option<KeyPresence>,
+    // Joins the values of some of the keys into one element per record
+    assembler: Option<RecordAssembler>,
+}
+
+mod presence;
+#[derive(Clone)]
+pub struct KeyPresence {
+    name: String,
+    path: String,
+    epc: usize,
+    // Keys of the record, the key parsers send their match results in this order
+    keys: Vec<String>,
+    // Nesting level of the keys of the record
+    outer_nested: usize,
+    output_name: String,
+}
+
+mod assembler;
+#[derive(Clone)]
+pub struct RecordAssembler {
+    name: String,
+    path: String,
+    epc: usize,
+    // Name of the group type of the elements
+    group_name: String,
+    // Key, group field name and value of every assembled key, the first field is in the lowest bits
+    fields: Vec<(String, String, Value)>,
+    // Nesting level of the values of the fields
+    outer_nested: usize,
+    output_name: String,
+}
+
+mod variant;
+#[derive(Clone)]
+pub struct Variant {
+    name: String,
+    path: String,
+    epc: usize,
+    // Parser of every kind of value, in order of the tag of the union
+    branches: Vec<(VariantKind, Box<JsonComponent>)>,
+    // Nesting level of the values, the same as the one of an integer parser
+    outer_nested: usize,
+    output_name: String,
 }
 
 mod key;
 #[derive(Clone)]
 pub struct Key {
     name: String,
+    path: String,
+    epc: usize,
     matcher: Matcher,
     outer_nested: usize,
+    optional: bool,
+    // Length of the key names emitted for every matched key of a map, the matcher is a pattern for all keys
+    key_name_length: Option<usize>,
+    // Instance of the key presence component of the record and the bit of the key in its vector
+    presence: Option<(String, usize)>,
     value: Option<Box<JsonComponent>>
 }
 
+mod converter;
+#[derive(Clone)]
+pub struct WidthConverter {
+    name: String,
+    path: String,
+    epc_in: usize,
+    epc_out: usize,
+    nesting: usize,
+}
+
 mod matcher;
 #[derive(Clone)]
 pub struct Matcher {
     name: String,
+    path: String,
+    epc: usize,
     holder_name: String,
-    matcher: String,
+    // Strings to match, the match stream has one bit for every string
+    matchers: Vec<String>,
     outer_nested: usize
 }
 
+mod enum_encoder;
+#[derive(Clone)]
+pub struct EnumEncoder {
+    name: String,
+    path: String,
+    epc: usize,
+    matcher: Matcher,
+    // Values in order of their code
+    values: Vec<String>,
+    outer_nested: usize,
+}
+
+mod filter;
+#[derive(Clone)]
+pub struct Filter {
+    name: String,
+    path: String,
+    epc: usize,
+    // Name of the filter in the configuration, the selected stream is named after it
+    filter_name: String,
+    predicate: FilterPredicate,
+    // Parser of the filtered value, its output is passed through unchanged
+    value: Box<Value>,
+    // Matcher of the regex of a string filter
+    matcher: Option<Matcher>,
+}
+
 impl JsonComponent {
     pub fn get_generatable(&self) -> &dyn Generatable {
         match self {
@@ -113,7 +360,13 @@ impl JsonComponent {
             JsonComponent::Array(array) => array,
             JsonComponent::Record(record) => record,
             JsonComponent::Key(key) => key,
-            JsonComponent::Matcher(matcher) => matcher
+            JsonComponent::Matcher(matcher) => matcher,
+            JsonComponent::Enum(encoder) => encoder,
+            JsonComponent::Filter(filter) => filter,
+            JsonComponent::Slicer(slicer) => slicer,
+            JsonComponent::Presence(presence) => presence,
+            JsonComponent::Assembler(assembler) => assembler,
+            JsonComponent::Variant(variant) => variant
         }
     }
 }
@@ -126,7 +379,13 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.get_children(),
             JsonComponent::Record(record) => record.get_children(),
             JsonComponent::Key(key) => key.get_children(),
-            JsonComponent::Matcher(matcher) => matcher.get_children()
+            JsonComponent::Matcher(matcher) => matcher.get_children(),
+            JsonComponent::Enum(encoder) => encoder.get_children(),
+            JsonComponent::Filter(filter) => filter.get_children(),
+            JsonComponent::Slicer(slicer) => slicer.get_children(),
+            JsonComponent::Presence(presence) => presence.get_children(),
+            JsonComponent::Assembler(assembler) => assembler.get_children(),
+            JsonComponent::Variant(variant) => variant.get_children()
         }
     }
 
@@ -136,7 +395,13 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.num_children(),
             JsonComponent::Record(record) => record.num_children(),
             JsonComponent::Key(key) => key.num_children(),
-            JsonComponent::Matcher(matcher) => matcher.num_children()
+            JsonComponent::Matcher(matcher) => matcher.num_children(),
+            JsonComponent::Enum(encoder) => encoder.num_children(),
+            JsonComponent::Filter(filter) => filter.num_children(),
+            JsonComponent::Slicer(slicer) => slicer.num_children(),
+            JsonComponent::Presence(presence) => presence.num_children(),
+            JsonComponent::Assembler(assembler) => assembler.num_children(),
+            JsonComponent::Variant(variant) => variant.num_children()
         }
     }
 
@@ -146,7 +411,13 @@ impl JsonComponentValue for JsonComponent {
             JsonComponent::Array(array) => array.to_graph_node(),
             JsonComponent::Record(record) => record.to_graph_node(),
             JsonComponent::Key(key) => key.to_graph_node(),
-            JsonComponent::Matcher(matcher) => matcher.to_graph_node()
+            JsonComponent::Matcher(matcher) => matcher.to_graph_node(),
+            JsonComponent::Enum(encoder) => encoder.to_graph_node(),
+            JsonComponent::Filter(filter) => filter.to_graph_node(),
+            JsonComponent::Slicer(slicer) => slicer.to_graph_node(),
+            JsonComponent::Presence(presence) => presence.to_graph_node(),
+            JsonComponent::Assembler(assembler) => assembler.to_graph_node(),
+            JsonComponent::Variant(variant) => variant.to_graph_node()
         }
     }
 }
@@ -158,7 +429,13 @@ impl From<Box<JsonComponent>> for Box<dyn Generatable> {
             JsonComponent::Array(array) => Box::new(array),
             JsonComponent::Record(record) => Box::new(record),
             JsonComponent::Key(key) => Box::new(key),
-            JsonComponent::Matcher(matcher) => Box::new(matcher)
+            JsonComponent::Matcher(matcher) => Box::new(matcher),
+            JsonComponent::Enum(encoder) => Box::new(encoder),
+            JsonComponent::Filter(filter) => Box::new(filter),
+            JsonComponent::Slicer(slicer) => Box::new(slicer),
+            JsonComponent::Presence(presence) => Box::new(presence),
+            JsonComponent::Assembler(assembler) => Box::new(assembler),
+            JsonComponent::Variant(variant) => Box::new(variant)
         }
     }
 }
\ No newline at end of file
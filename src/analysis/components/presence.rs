@@ -0,0 +1,132 @@
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{KeyPresence, Generatable, JsonComponent, JsonComponentValue};
+
+impl KeyPresence {
+    pub fn new(name: &str, path: &str, epc: usize, keys: Vec<String>, outer_nested: usize, output_name: &str) -> KeyPresence {
+        KeyPresence {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            keys,
+            outer_nested,
+            output_name: output_name.to_string(),
+        }
+    }
+
+    // Keys in order of their bit in the presence vector
+    pub fn get_keys(&self) -> &Vec<String> {
+        &self.keys
+    }
+
+    pub fn get_output_name(&self) -> &str {
+        &self.output_name
+    }
+
+    // One element for every record, with a bit for every key
+    fn get_output_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Presence,
+            Some(StreamDim::new(dim_name, self.outer_nested, -1))
+        ).with_throughput(gen_params.get_throughput(self.epc)).with_data_bits(Some(self.get_keys().len()))
+    }
+}
+
+impl Generatable for KeyPresence {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
+        let dim_name = "OUTER_NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+        interface.add_generic(Generic::new("NUM_KEYS", GenericType::Positive(self.get_keys().len())));
+
+        // Input type, the same stream the key parsers get
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Record,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            ).with_throughput(gen_params.get_throughput(self.epc))
+        );
+
+        // Match results of the key parser of every key
+        for bit in 0..self.keys.len() {
+            interface.add_stream(&format!("match_{}", bit), TilStreamDirection::Input,
+                StreamTypeDecl::new(
+                    StreamType::MatcherMatch,
+                    None
+                ).with_throughput(gen_params.get_throughput(self.epc))
+            );
+        }
+
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output, self.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        vec![
+            TilSignal::Output {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: "output".to_owned(),
+                dest_stream_name: self.output_name.clone(),
+                output_stream: TilStream::new(&self.output_name, TilStreamDirection::Output, self.get_output_type(None, gen_params))
+            }
+        ]
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Presence
+    }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+
+        let mut keys = Vec::new();
+        for (bit, key) in self.get_keys().iter().enumerate() {
+            let mut key_context = TemplateContext::new();
+            key_context.insert("bit", bit);
+            key_context.insert("key", key.as_str());
+            keys.push(key_context);
+        }
+        context.insert("keys", keys);
+
+        context
+    }
+}
+
+impl JsonComponentValue for KeyPresence {
+    fn to_graph_node(&self) -> String {
+        format!("Key presence\nO: {}, keys: {}", self.outer_nested, self.get_keys().len())
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        Vec::new()
+    }
+
+    fn num_children(&self) -> usize {
+        0
+    }
+}
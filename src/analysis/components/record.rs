@@ -1,23 +1,84 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
 
-use super::{Record, JsonComponent, Generatable, Key, JsonComponentValue};
+use super::{Record, JsonComponent, Generatable, Key, KeyPresence, RecordAssembler, JsonComponentValue};
 
 impl Record {
-    pub fn new(name: &str, outer_nested: usize, inner_nested: usize, keys: Vec<Key>) -> Record {
+    pub fn new(name: &str, path: &str, epc: usize, outer_nested: usize, inner_nested: usize, keys: Vec<Key>) -> Record {
         Record {
             name: name.to_string(),
+            path: path.to_string(),
+            epc,
             outer_nested,
             inner_nested,
-            keys
+            keys,
+            presence: None,
+            assembler: None,
         }
     }
+
+    pub fn get_keys(&self) -> &Vec<Key> {
+        &self.keys
+    }
+
+    // Emit a vector with a bit for every key telling if it was present in the record
+    // The keys have to send their match results to the presence component, see Key::with_presence
+    pub fn with_presence(mut self, presence_name: &str) -> Record {
+        let keys = self.keys.iter().map(|key| key.get_key().to_string()).collect();
+        let output_name = format!("presence_{}", self.get_instance_name());
+
+        // The presence component sees the same stream as the keys
+        self.presence = Some(KeyPresence::new(presence_name, &self.path, self.epc, keys, self.outer_nested + 1, &output_name));
+        self
+    }
+
+    pub fn get_presence(&self) -> Option<&KeyPresence> {
+        self.presence.as_ref()
+    }
+
+    // Join the values of the given keys into one element per record, the keys are given with the name of their
+    // field in the group. Only integers and booleans without a filter are joined, the other keys are skipped.
+    pub fn with_assembler(mut self, assembler_name: &str, fields: &[(String, String)]) -> Record {
+        let assembler_inst_name = format!("{}_inst", assembler_name);
+        let mut assembled = Vec::new();
+
+        for (key_name, field_name) in fields {
+            let key = match self.keys.iter_mut().find(|key| key.get_key() == key_name) {
+                Some(key) => key,
+                None => continue,
+            };
+
+            if let Some(JsonComponent::Value(value)) = key.value.as_deref() {
+                if value.can_assemble() {
+                    // The value goes to the stream of its field instead of a top-level output
+                    let value = value.clone().with_dest(&assembler_inst_name, &format!("field_{}", field_name));
+                    key.value = Some(Box::new(JsonComponent::Value(value.clone())));
+                    assembled.push((key_name.clone(), field_name.clone(), value));
+                }
+            }
+        }
+
+        if assembled.is_empty() {
+            return self;
+        }
+
+        let output_name = format!("assembled_{}", self.get_instance_name());
+        let group_name = format!("{}_fields", self.name);
+
+        // The values of the keys are one level deeper than the record
+        self.assembler = Some(RecordAssembler::new(assembler_name, &self.path, self.epc, &group_name, assembled, self.outer_nested + 1, &output_name));
+        self
+    }
+
+    pub fn get_assembler(&self) -> Option<&RecordAssembler> {
+        self.assembler.as_ref()
+    }
 }
 
 impl Generatable for Record {
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
         let mut interface = TilStreamingInterface::default();
 
-        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
         let dim_name = "OUTER_NESTING_LEVEL";
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
         interface.add_generic(Generic::new("INNER_NESTING_LEVEL", GenericType::Natural(self.inner_nested)));
@@ -27,7 +88,7 @@ impl Generatable for Record {
             StreamTypeDecl::new(
                 StreamType::Json,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         // Output type
@@ -35,21 +96,17 @@ impl Generatable for Record {
             StreamTypeDecl::new(
                 StreamType::Record,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 2))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         interface
     }
 
-    fn get_streaming_types(&self) -> Vec<StreamType> {
-        vec![StreamType::Json, StreamType::Record]
-    }
-
     fn get_nesting_level(&self) -> usize {
         self.outer_nested
     }
 
-    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+    fn get_outgoing_signals(&self, _gen_params: &GeneratorParams) -> Vec<TilSignal> {
         let mut signals: Vec<TilSignal> = Vec::new();
 
         for key in &self.keys {
@@ -63,6 +120,17 @@ impl Generatable for Record {
             );
         }
 
+        if let Some(presence) = &self.presence {
+            signals.push(
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: presence.get_instance_name(),
+                    dest_stream_name: "input".to_owned()
+                }
+            );
+        }
+
         signals
     }
 
@@ -70,6 +138,14 @@ impl Generatable for Record {
         &self.name
     }
 
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
     fn num_outgoing_signals(&self) -> usize {
         1
     }
@@ -77,6 +153,26 @@ impl Generatable for Record {
     fn get_file_type(&self) -> TemplateType {
         TemplateType::Record
     }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+        context.insert("inner_nesting", self.inner_nested);
+        context.insert("has_optional_keys", self.keys.iter().any(|key| key.is_optional()));
+
+        let mut keys = Vec::new();
+        for key in &self.keys {
+            let mut key_context = TemplateContext::new();
+            key_context.insert("key", key.get_key());
+            key_context.insert("name", key.get_name());
+            key_context.insert("path", key.get_path());
+            key_context.insert("optional", key.is_optional());
+            keys.push(key_context);
+        }
+        context.insert("keys", keys);
+
+        context
+    }
 }
 
 impl JsonComponentValue for Record {
@@ -91,6 +187,14 @@ impl JsonComponentValue for Record {
             children.push(JsonComponent::Key(key.clone()));
         }
 
+        if let Some(presence) = &self.presence {
+            children.push(JsonComponent::Presence(presence.clone()));
+        }
+
+        if let Some(assembler) = &self.assembler {
+            children.push(JsonComponent::Assembler(assembler.clone()));
+        }
+
         children
     }
 
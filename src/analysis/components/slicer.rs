@@ -0,0 +1,205 @@
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{ArraySlice, ArraySlicer, Generatable, JsonComponent, JsonComponentValue};
+
+impl ArraySlice {
+    // Only the element at an index
+    pub fn index(index: usize) -> ArraySlice {
+        ArraySlice { start: index, end: Some(index + 1), stride: 1 }
+    }
+
+    // The elements from the start up to the end
+    pub fn range(start: usize, end: usize) -> ArraySlice {
+        ArraySlice { start, end: Some(end), stride: 1 }
+    }
+
+    // All elements from the start
+    pub fn from(start: usize) -> ArraySlice {
+        ArraySlice { start, end: None, stride: 1 }
+    }
+
+    // Keep every stride-th element of the slice
+    pub fn with_stride(mut self, stride: usize) -> ArraySlice {
+        self.stride = stride;
+        self
+    }
+
+    // A slice keeps at least one element
+    pub fn is_valid(&self) -> bool {
+        self.stride > 0 && self.end.is_none_or(|end| end > self.start)
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index >= self.start && self.end.is_none_or(|end| index < end) && (index - self.start).is_multiple_of(self.stride)
+    }
+}
+
+impl Display for ArraySlice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "[{}:{}:{}]", self.start, end, self.stride),
+            None => write!(f, "[{}::{}]", self.start, self.stride),
+        }
+    }
+}
+
+impl ArraySlicer {
+    pub fn new(name: &str, path: &str, epc: usize, outer_nested: usize, slice: ArraySlice, dest_inst_name: Option<String>, output_name: &str) -> ArraySlicer {
+        ArraySlicer {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            outer_nested,
+            slice,
+            dest_inst_name,
+            output_name: output_name.to_string(),
+        }
+    }
+
+    pub fn get_slice(&self) -> ArraySlice {
+        self.slice
+    }
+
+    // Width of the element index, large enough to count past the last kept element
+    fn get_index_width(&self) -> usize {
+        let max_index = self.slice.end.unwrap_or(self.slice.start) + 1;
+
+        let mut width = 1;
+        while (1 << width) <= max_index {
+            width += 1;
+        }
+
+        width
+    }
+
+    // The elements of the arrays, the same type as the output of the array parser
+    fn get_stream_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Json,
+            Some(StreamDim::new(dim_name, self.outer_nested, 2))
+        ).with_throughput(gen_params.get_throughput(self.epc))
+    }
+}
+
+impl Generatable for ArraySlicer {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
+        let dim_name = "OUTER_NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+        interface.add_generic(Generic::new("INDEX_BW", GenericType::Positive(self.get_index_width())));
+        interface.add_generic(Generic::new("START", GenericType::Natural(self.slice.start)));
+        // An end of 0 keeps all elements from the start
+        interface.add_generic(Generic::new("STOP", GenericType::Natural(self.slice.end.unwrap_or(0))));
+        interface.add_generic(Generic::new("STRIDE", GenericType::Positive(self.slice.stride)));
+
+        interface.add_stream("input", TilStreamDirection::Input, self.get_stream_type(Some(dim_name.to_string()), gen_params));
+        interface.add_stream("output", TilStreamDirection::Output, self.get_stream_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        match &self.dest_inst_name {
+            Some(dest_inst_name) => vec![
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: dest_inst_name.clone(),
+                    dest_stream_name: "input".to_owned()
+                }
+            ],
+            None => vec![
+                TilSignal::Output {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_stream_name: self.output_name.clone(),
+                    output_stream: TilStream::new(&self.output_name, TilStreamDirection::Output,
+                        self.get_stream_type(None, gen_params).with_config(gen_params.get_field_config(&self.path))
+                    )
+                }
+            ],
+        }
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Slicer
+    }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+
+        context
+    }
+}
+
+impl JsonComponentValue for ArraySlicer {
+    fn to_graph_node(&self) -> String {
+        format!("Array slicer\nO: {}\n{}", self.outer_nested, self.slice)
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        Vec::new()
+    }
+
+    fn num_children(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kept(slice: ArraySlice) -> Vec<usize> {
+        (0..12).filter(|index| slice.contains(*index)).collect()
+    }
+
+    #[test]
+    fn slices_contain_their_indices() {
+        assert_eq!(kept(ArraySlice::index(3)), vec![3]);
+        assert_eq!(kept(ArraySlice::range(2, 5)), vec![2, 3, 4]);
+        assert_eq!(kept(ArraySlice::from(9)), vec![9, 10, 11]);
+        assert_eq!(kept(ArraySlice::range(1, 8).with_stride(3)), vec![1, 4, 7]);
+        assert_eq!(kept(ArraySlice::from(0).with_stride(5)), vec![0, 5, 10]);
+    }
+
+    #[test]
+    fn slices_keep_at_least_one_element() {
+        assert!(ArraySlice::index(0).is_valid());
+        assert!(ArraySlice::from(4).with_stride(7).is_valid());
+        assert!(!ArraySlice::range(3, 3).is_valid());
+        assert!(!ArraySlice::range(4, 2).is_valid());
+        assert!(!ArraySlice::from(0).with_stride(0).is_valid());
+    }
+
+    #[test]
+    fn slices_are_displayed_as_ranges() {
+        assert_eq!(ArraySlice::range(1, 8).with_stride(3).to_string(), "[1:8:3]");
+        assert_eq!(ArraySlice::from(2).to_string(), "[2::1]");
+    }
+}
@@ -1,13 +1,88 @@
-use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::TemplateType}};
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
 
-use super::{JsonComponent, JsonType, Value, Generatable, JsonComponentValue};
+use super::{JsonComponent, JsonType, Value, Filter, Generatable, JsonComponentValue, StringMode};
 
 impl Value {
-    pub fn new(name: &str, data_type: JsonType, outer_nested: usize) -> Value {
+    pub fn new(name: &str, path: &str, epc: usize, data_type: JsonType, outer_nested: usize) -> Value {
         Value {
             name: name.to_string(),
+            path: path.to_string(),
+            epc,
             data_type,
             outer_nested,
+            string_mode: StringMode::Raw,
+            filter: None,
+            dest: None,
+        }
+    }
+
+    // Emit a string value in a different form than the raw characters
+    pub fn with_string_mode(mut self, string_mode: StringMode) -> Value {
+        self.string_mode = string_mode;
+        self
+    }
+
+    // Pass the output through a filter which also emits if every value is selected
+    pub fn with_filter(mut self, filter: Filter) -> Value {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    // Send the output to a stream of another component instead of a top-level output
+    pub fn with_dest(mut self, dest_inst_name: &str, dest_stream_name: &str) -> Value {
+        self.dest = Some((dest_inst_name.to_string(), dest_stream_name.to_string()));
+        self
+    }
+
+    // Values with a single fixed width element which can be a field of an assembled record
+    pub fn can_assemble(&self) -> bool {
+        matches!(self.data_type, JsonType::Integer | JsonType::Boolean) && self.filter.is_none()
+    }
+
+    pub fn get_dest(&self) -> Option<&(String, String)> {
+        self.dest.as_ref()
+    }
+
+    pub fn get_filter(&self) -> Option<&Filter> {
+        self.filter.as_deref()
+    }
+
+    pub fn get_data_type(&self) -> &JsonType {
+        &self.data_type
+    }
+
+    pub fn get_string_mode(&self) -> StringMode {
+        match self.data_type {
+            JsonType::String => self.string_mode,
+            _ => StringMode::Raw,
+        }
+    }
+
+    // Type of the output stream, the dimension is named inside the component and resolved outside of it
+    pub fn get_output_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        let output_type = match self.data_type {
+            JsonType::String => match self.string_mode {
+                StringMode::Raw => StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(dim_name, self.outer_nested, 1)))
+                    .with_throughput(gen_params.get_throughput(self.epc)),
+                // Every string is a sequence of bytes
+                StringMode::Decoded => StreamTypeDecl::new(StreamType::DecodedString, Some(StreamDim::new(dim_name, self.outer_nested, 1)))
+                    .with_throughput(gen_params.get_throughput(self.epc)),
+                // Every string is a single element holding all its bytes
+                StringMode::Fixed(length) => StreamTypeDecl::new(StreamType::DecodedString, Some(StreamDim::new(dim_name, self.outer_nested, 0)))
+                    .with_throughput(gen_params.get_throughput(1)).with_data_bits(Some(8 * length)),
+                // The length and the bytes of consecutive strings follow each other in one sequence
+                StringMode::LengthPrefixed(_) => StreamTypeDecl::new(StreamType::DecodedString, Some(StreamDim::new(dim_name, self.outer_nested, 0)))
+                    .with_throughput(gen_params.get_throughput(self.epc)),
+            },
+            JsonType::Integer => StreamTypeDecl::new(StreamType::Int, Some(StreamDim::new(dim_name, self.outer_nested, 0))),
+            JsonType::Boolean => StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(dim_name, self.outer_nested, 0))),
+        }.with_config(gen_params.get_field_config(&self.path));
+
+        // Integers and booleans can be emitted as a declared type of their width, other widths are reported by the analyzer
+        match gen_params.field_types.get(&self.path) {
+            Some(logical_type) if self.data_type != JsonType::String
+                && logical_type.get_bits() == output_type.get_type_params(gen_params).get_data_bits() => output_type.with_data_type(logical_type.clone()),
+            _ => output_type,
         }
     }
 }
@@ -15,7 +90,7 @@ impl Value {
 impl Generatable for Value {
     fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
         let mut interface = TilStreamingInterface::default();
-        interface.add_generic(Generic::new("EPC", GenericType::Positive(gen_params.epc)));
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
         let dim_name = "NESTING_LEVEL";
         // let dim = self.outer_nested + 1;
         interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
@@ -25,61 +100,52 @@ impl Generatable for Value {
             StreamTypeDecl::new(
                 StreamType::Json,
                 Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
-            )
+            ).with_throughput(gen_params.get_throughput(self.epc))
         );
 
         match self.data_type {
-            JsonType::String => {
-                // Output type
-                interface.add_stream("output", TilStreamDirection::Output,
-                    StreamTypeDecl::new(
-                        StreamType::Json, 
-                        Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 1))
-                    )
-                );
-
-                interface
-            },
-            JsonType::Integer => {
-                interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(gen_params.int_width)));
-
-                // Output type
-                interface.add_stream("output", TilStreamDirection::Output,
-                    StreamTypeDecl::new(
-                        StreamType::Int, 
-                        Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 0))
-                    )
-                );
-
-                interface
+            JsonType::Integer => interface.add_generic(Generic::new("BITWIDTH", GenericType::Positive(gen_params.int_width))),
+            JsonType::String => match self.string_mode {
+                StringMode::Fixed(length) | StringMode::LengthPrefixed(length) => interface.add_generic(Generic::new("LENGTH", GenericType::Positive(length))),
+                _ => (),
             },
-            JsonType::Boolean => {
-                // Output type
-                interface.add_stream("output", TilStreamDirection::Output,
-                    StreamTypeDecl::new(
-                        StreamType::Bool,
-                        Some(StreamDim::new(Some(dim_name.to_string()),  self.outer_nested, 0))
-                    )
-                );
-
-                interface
-            }
+            JsonType::Boolean => (),
         }
-    }
 
-    fn get_streaming_types(&self) -> Vec<StreamType> {
-        match self.data_type {
-            JsonType::String => vec![StreamType::Json],
-            JsonType::Integer => vec![StreamType::Json, StreamType::Int],
-            JsonType::Boolean => vec![StreamType::Json, StreamType::Bool],
-        }
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output, self.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        interface
     }
 
     fn get_nesting_level(&self) -> usize {
         self.outer_nested
     }
 
-    fn get_outgoing_signals(&self) -> Vec<TilSignal> {
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        // The filter drives the output instead
+        if let Some(filter) = &self.filter {
+            return vec![
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: filter.get_instance_name(),
+                    dest_stream_name: "input".to_owned()
+                }
+            ];
+        }
+
+        if let Some((dest_inst_name, dest_stream_name)) = &self.dest {
+            return vec![
+                TilSignal::Intermediate {
+                    source_inst_name: self.get_instance_name(),
+                    source_stream_name: "output".to_owned(),
+                    dest_inst_name: dest_inst_name.clone(),
+                    dest_stream_name: dest_stream_name.clone()
+                }
+            ];
+        }
+
         let output_name = format!("output_{}", self.get_instance_name());
 
         vec![
@@ -87,13 +153,7 @@ impl Generatable for Value {
                 source_inst_name: self.get_instance_name(), 
                 source_stream_name: "output".to_owned(), 
                 dest_stream_name: output_name.clone(),
-                output_stream: TilStream::new(&output_name, TilStreamDirection::Output, 
-                    match self.data_type {
-                        JsonType::String => StreamTypeDecl::new(StreamType::Json, Some(StreamDim::new(None, self.outer_nested, 1))),
-                        JsonType::Integer => StreamTypeDecl::new(StreamType::Int, Some(StreamDim::new(None, self.outer_nested, 0))),
-                        JsonType::Boolean => StreamTypeDecl::new(StreamType::Bool, Some(StreamDim::new(None, self.outer_nested, 0))),
-                    }
-                )
+                output_stream: TilStream::new(&output_name, TilStreamDirection::Output, self.get_output_type(None, gen_params))
             }
         ]
     }
@@ -102,29 +162,66 @@ impl Generatable for Value {
         &self.name
     }
 
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
     fn num_outgoing_signals(&self) -> usize {
         0
     }
 
     fn get_file_type(&self) -> TemplateType {
         match self.data_type {
+            JsonType::String if self.string_mode != StringMode::Raw => TemplateType::DecodedString,
             JsonType::String => TemplateType::String,
             JsonType::Integer => TemplateType::Int,
             JsonType::Boolean => TemplateType::Bool,
         }
     }
+
+    fn get_template_values(&self, _gen_params: &GeneratorParams) -> TemplateContext {
+        let data_type = match self.data_type {
+            JsonType::String => "string",
+            JsonType::Integer => "integer",
+            JsonType::Boolean => "boolean",
+        };
+
+        let mut context = TemplateContext::new();
+        context.insert("data_type", data_type);
+        context.insert("outer_nesting", self.outer_nested);
+
+        let string_mode = self.get_string_mode();
+        context.insert("decoded", string_mode == StringMode::Decoded);
+        context.insert("fixed", matches!(string_mode, StringMode::Fixed(_)));
+        context.insert("length_prefixed", matches!(string_mode, StringMode::LengthPrefixed(_)));
+
+        context
+    }
 }
 
 impl JsonComponentValue for Value {
     fn to_graph_node(&self) -> String {
-        format!("{:?} parser\nO: {}", self.data_type, self.outer_nested)
+        match self.get_string_mode() {
+            StringMode::Raw => format!("{:?} parser\nO: {}", self.data_type, self.outer_nested),
+            string_mode => format!("{:?} parser\nO: {}\n{:?}", self.data_type, self.outer_nested, string_mode),
+        }
     }
 
     fn get_children(&self) -> Vec<JsonComponent> {
-        Vec::new()
+        match &self.filter {
+            Some(filter) => vec![JsonComponent::Filter(*filter.clone())],
+            None => Vec::new(),
+        }
     }
 
     fn num_children(&self) -> usize {
-        0
+        match &self.filter {
+            Some(_) => 1,
+            None => 0,
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,285 @@
+use json::JsonValue;
+
+use crate::analysis::{types::{TilStreamingInterface, TilSignal, streaming_interface::{Generic, GenericType, TilStreamDirection, TilStream}, stream_types::{StreamTypeDecl, StreamDim}, logical_type::LogicalType}, GeneratorParams, analyzer::{type_manager::StreamType, file_manager::{TemplateType, template::TemplateContext}}};
+
+use super::{Variant, VariantKind, Generatable, JsonComponent, JsonComponentValue, JsonType, Value};
+
+impl VariantKind {
+    // Kind of a sample value, null values are absent
+    pub fn of(value: &JsonValue) -> Option<VariantKind> {
+        match value {
+            JsonValue::Number(_) => Some(VariantKind::Integer),
+            JsonValue::Short(_) | JsonValue::String(_) => Some(VariantKind::String),
+            JsonValue::Boolean(_) => Some(VariantKind::Boolean),
+            JsonValue::Object(_) => Some(VariantKind::Object),
+            JsonValue::Array(_) => Some(VariantKind::Array),
+            JsonValue::Null => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            VariantKind::Integer => "integer",
+            VariantKind::String => "string",
+            VariantKind::Boolean => "boolean",
+            VariantKind::Object => "object",
+            VariantKind::Array => "array",
+        }
+    }
+
+    // Characters a value of the kind can start with
+    pub fn get_first_chars(&self) -> Vec<u8> {
+        match self {
+            VariantKind::Integer => b"-0123456789".to_vec(),
+            VariantKind::String => b"\"".to_vec(),
+            VariantKind::Boolean => b"tf".to_vec(),
+            VariantKind::Object => b"{".to_vec(),
+            VariantKind::Array => b"[".to_vec(),
+        }
+    }
+}
+
+impl Variant {
+    // Integers and booleans without a filter are sent back to the variant, which emits them in the union.
+    // The other kinds keep their own outputs and only their tag is in the union.
+    pub fn new(name: &str, path: &str, epc: usize, branches: Vec<(VariantKind, JsonComponent)>, outer_nested: usize, output_name: &str) -> Variant {
+        let inst_name = format!("{}_inst", name);
+
+        let branches = branches.into_iter()
+            .map(|(kind, component)| match component {
+                JsonComponent::Value(value) if value.can_assemble() => {
+                    let value = value.with_dest(&inst_name, &format!("value_{}", kind.get_name()));
+                    (kind, Box::new(JsonComponent::Value(value)))
+                },
+                component => (kind, Box::new(component)),
+            })
+            .collect();
+
+        Variant {
+            name: name.to_string(),
+            path: path.to_string(),
+            epc,
+            branches,
+            outer_nested,
+            output_name: output_name.to_string(),
+        }
+    }
+
+    pub fn get_branches(&self) -> &Vec<(VariantKind, Box<JsonComponent>)> {
+        &self.branches
+    }
+
+    pub fn get_output_name(&self) -> &str {
+        &self.output_name
+    }
+
+    // Value which is sent back to the variant by the parser of a kind
+    pub fn get_routed_value(&self, kind: VariantKind) -> Option<&Value> {
+        self.branches.iter().find_map(|(branch_kind, component)| match component.as_ref() {
+            JsonComponent::Value(value) if *branch_kind == kind && value.get_dest().is_some() => Some(value),
+            _ => None,
+        })
+    }
+
+    // Union with a field for every kind, the kinds which are not emitted by the variant carry no data
+    pub fn get_union(&self, gen_params: &GeneratorParams) -> LogicalType {
+        let fields = self.branches.iter()
+            .map(|(kind, _)| {
+                let data = match self.get_routed_value(*kind) {
+                    Some(value) => value.get_output_type(None, gen_params).get_type_params(gen_params).data,
+                    None => LogicalType::Null,
+                };
+
+                (kind.get_name().to_string(), data)
+            })
+            .collect();
+
+        LogicalType::named(&format!("{}_kinds", self.name), LogicalType::Union(fields))
+    }
+
+    // One element for every value
+    fn get_output_type(&self, dim_name: Option<String>, gen_params: &GeneratorParams) -> StreamTypeDecl {
+        StreamTypeDecl::new(
+            StreamType::Variant,
+            Some(StreamDim::new(dim_name, self.outer_nested, 0))
+        ).with_data_type(self.get_union(gen_params))
+    }
+
+    // Dimensionality of the input of the parser of a kind relative to the nesting level of the variant,
+    // strings take one dimension less than the other kinds
+    fn get_branch_additive(&self, component: &JsonComponent, gen_params: &GeneratorParams) -> isize {
+        let interface = component.get_generatable().get_streaming_interface(gen_params);
+        let dims = interface.get_streams().iter()
+            .find(|stream| stream.get_name() == "input")
+            .and_then(|stream| stream.get_type().get_stream_dim().as_ref().map(|dim| dim.get_true_value()))
+            .unwrap_or(self.outer_nested + 1);
+
+        dims as isize - self.outer_nested as isize
+    }
+}
+
+impl Generatable for Variant {
+    fn get_streaming_interface(&self, gen_params: &GeneratorParams) -> TilStreamingInterface {
+        let mut interface = TilStreamingInterface::default();
+
+        interface.add_generic(Generic::new("EPC", GenericType::Positive(self.epc)));
+        let dim_name = "NESTING_LEVEL";
+        interface.add_generic(Generic::new(dim_name, GenericType::Dimensionality(self.outer_nested)));
+
+        // Input type
+        interface.add_stream("input", TilStreamDirection::Input,
+            StreamTypeDecl::new(
+                StreamType::Json,
+                Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, 1))
+            ).with_throughput(gen_params.get_throughput(self.epc))
+        );
+
+        // The values of every kind go to their own parser
+        for (kind, component) in &self.branches {
+            interface.add_stream(&format!("branch_{}", kind.get_name()), TilStreamDirection::Output,
+                StreamTypeDecl::new(
+                    StreamType::Json,
+                    Some(StreamDim::new(Some(dim_name.to_string()), self.outer_nested, self.get_branch_additive(component, gen_params)))
+                ).with_throughput(gen_params.get_throughput(self.epc))
+            );
+        }
+
+        // Parsed integers and booleans come back to be put in the union
+        for (kind, _) in &self.branches {
+            if let Some(value) = self.get_routed_value(*kind) {
+                interface.add_stream(&format!("value_{}", kind.get_name()), TilStreamDirection::Input, value.get_output_type(Some(dim_name.to_string()), gen_params));
+            }
+        }
+
+        // Output type
+        interface.add_stream("output", TilStreamDirection::Output, self.get_output_type(Some(dim_name.to_string()), gen_params));
+
+        interface
+    }
+
+    fn get_nesting_level(&self) -> usize {
+        self.outer_nested
+    }
+
+    fn get_outgoing_signals(&self, gen_params: &GeneratorParams) -> Vec<TilSignal> {
+        let mut signals: Vec<TilSignal> = self.branches.iter()
+            .map(|(kind, component)| TilSignal::Intermediate {
+                source_inst_name: self.get_instance_name(),
+                source_stream_name: format!("branch_{}", kind.get_name()),
+                dest_inst_name: component.get_generatable().get_instance_name(),
+                dest_stream_name: "input".to_owned()
+            })
+            .collect();
+
+        signals.push(TilSignal::Output {
+            source_inst_name: self.get_instance_name(),
+            source_stream_name: "output".to_owned(),
+            dest_stream_name: self.output_name.clone(),
+            output_stream: TilStream::new(&self.output_name, TilStreamDirection::Output, self.get_output_type(None, gen_params))
+        });
+
+        signals
+    }
+
+    fn num_outgoing_signals(&self) -> usize {
+        self.branches.len() + 1
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    fn get_epc(&self) -> usize {
+        self.epc
+    }
+
+    fn get_file_type(&self) -> TemplateType {
+        TemplateType::Variant
+    }
+
+    fn get_template_values(&self, gen_params: &GeneratorParams) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        context.insert("outer_nesting", self.outer_nested);
+
+        let union = self.get_union(gen_params);
+        let tag_bits = union.get_tag_bits();
+        context.insert("tag_bits", tag_bits);
+        context.insert("union_bits", union.get_bits());
+        context.insert("num_branches", self.branches.len());
+
+        let mut branches = Vec::new();
+        for (index, ((kind, component), (_, field))) in self.branches.iter().zip(union.get_fields()).enumerate() {
+            let routed = self.get_routed_value(*kind);
+
+            // Test of the character c of a lane on the first characters of the kind
+            let condition: Vec<String> = kind.get_first_chars().iter().map(|c| format!("c = x\"{:02X}\"", c)).collect();
+
+            let mut branch_context = TemplateContext::new();
+            branch_context.insert("index", index);
+            branch_context.insert("name", kind.get_name());
+            branch_context.insert("additive", self.get_branch_additive(component, gen_params) as usize);
+            branch_context.insert("condition", condition.join(" or "));
+            branch_context.insert("routed", routed.is_some());
+            branch_context.insert("boolean", routed.is_some_and(|value| *value.get_data_type() == JsonType::Boolean));
+            branch_context.insert("bits", field.get_bits());
+            branch_context.insert("high", tag_bits + field.get_bits().max(1) - 1);
+            branches.push(branch_context);
+        }
+        context.insert("branches", branches);
+
+        context
+    }
+}
+
+impl JsonComponentValue for Variant {
+    fn to_graph_node(&self) -> String {
+        let kinds: Vec<&str> = self.branches.iter().map(|(kind, _)| kind.get_name()).collect();
+        format!("Variant\nO: {}, kinds: {}", self.outer_nested, kinds.join(", "))
+    }
+
+    fn get_children(&self) -> Vec<JsonComponent> {
+        self.branches.iter().map(|(_, component)| component.as_ref().clone()).collect()
+    }
+
+    fn num_children(&self) -> usize {
+        self.branches.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_of_values() {
+        let kinds: Vec<Option<VariantKind>> = json::parse(r#"[-12, 1.5, "text", false, {"a": 1}, [1], null]"#).unwrap()
+            .members()
+            .map(VariantKind::of)
+            .collect();
+
+        assert_eq!(kinds, vec![
+            Some(VariantKind::Integer),
+            Some(VariantKind::Integer),
+            Some(VariantKind::String),
+            Some(VariantKind::Boolean),
+            Some(VariantKind::Object),
+            Some(VariantKind::Array),
+            None,
+        ]);
+
+        // Short and long strings are stored differently
+        assert_eq!(VariantKind::of(&JsonValue::from("a".repeat(100))), Some(VariantKind::String));
+    }
+
+    #[test]
+    fn kinds_are_told_apart_by_their_first_character() {
+        for sample in ["-1", "0", "42", "\"\"", "true", "false", "{}", "[]"] {
+            let kind = VariantKind::of(&json::parse(sample).unwrap()).unwrap();
+            assert!(kind.get_first_chars().contains(&sample.as_bytes()[0]), "{}", sample);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use enumset::EnumSet;
+use json::JsonValue;
+
+use super::{Generator, GeneratorError, NamingStrategy, StringMode, FilterPredicate, ArraySlice, LogicalType, components::{CompareOp, MAX_FIXED_LENGTH, MAX_PREFIXED_LENGTH}, analyzer::type_manager::{StreamType, Synchronicity}};
+
+impl Generator {
+    // Apply a JSON configuration, every key maps to a setter of the generator:
+    // {
+    //     "naming": "path",
+    //     "template_dir": "templates",
+    //     "string_mode": "decoded",
+    //     "enum_detection": 8,
+    //     "schema": "schema.json",
+    //     "types": { "voltage": { "bits": 64 }, "reading": { "group": { "value": "voltage", "valid": { "bits": 1 } } } },
+    //     "streams": { "IntParserStream": { "complexity": 4, "synchronicity": "Sync" } },
+    //     "fields": { "temperature[].voltage": { "complexity": 4, "synchronicity": "Sync", "epc": 2, "size": 16, "type": "voltage" },
+    //                 "temperature[].unit": { "string_mode": { "fixed": 8 } },
+    //                 "status": { "enum": ["ok", "error"] },
+    //                 "humidity[].voltage": { "filter": { "name": "humid", "compare": ">=", "value": 1000 } },
+    //                 "temperature": { "counters": { "count": true, "index": false, "width": 8 },
+    //                                  "slice": { "start": 0, "end": 2, "stride": 1 } },
+    //                 "sensors": { "map": "sensor_[0-9]+", "epc": 1, "key_presence": true },
+    //                 "": { "assemble": ["valid"] } }
+    // }
+    pub fn configure(&mut self, config: &str) -> Result<(), GeneratorError> {
+        let config = json::parse(config).map_err(GeneratorError::JsonError)?;
+
+        match config["naming"].as_str() {
+            Some("sequential") => self.set_naming_strategy(NamingStrategy::Sequential),
+            Some("path") => self.set_naming_strategy(NamingStrategy::Path),
+            Some(other) => return Err(GeneratorError::InvalidConfig(format!("unknown naming strategy {}", other))),
+            None => (),
+        }
+
+        if let Some(dir) = config["template_dir"].as_str() {
+            self.set_template_dir(dir)?;
+        }
+
+        if !config["string_mode"].is_null() {
+            self.set_string_mode(parse_string_mode("string_mode", &config["string_mode"])?);
+        }
+
+        if !config["enum_detection"].is_null() {
+            let max_values = config["enum_detection"].as_usize()
+                .ok_or_else(|| GeneratorError::InvalidConfig("enum_detection has to be a number of values".to_string()))?;
+            self.set_enum_detection(max_values);
+        }
+
+        // Enums of a JSON Schema, the file name is relative to the working directory
+        if let Some(file_name) = config["schema"].as_str() {
+            let schema = std::fs::read_to_string(file_name).map_err(|e| GeneratorError::InvalidConfig(format!("{}: {}", file_name, e)))?;
+            self.set_schema_enums(&schema)?;
+        }
+
+        // Types can use the ones declared before them
+        for (name, definition) in config["types"].entries() {
+            let logical_type = self.parse_logical_type(name, definition)?;
+            self.add_type(name, logical_type)?;
+        }
+
+        for (name, stream) in config["streams"].entries() {
+            let stream_type = parse_stream_type(name)?;
+            let (complexity, synchronicity) = parse_stream_config(name, stream)?;
+            self.set_stream_config(stream_type, complexity, synchronicity)?;
+        }
+
+        for (path, field) in config["fields"].entries() {
+            self.configure_field(path, field)?;
+        }
+
+        Ok(())
+    }
+
+    // Apply the settings of the field at a JSON path
+    fn configure_field(&mut self, path: &str, field: &JsonValue) -> Result<(), GeneratorError> {
+        if !field["complexity"].is_null() || !field["synchronicity"].is_null() {
+            let (complexity, synchronicity) = parse_stream_config(path, field)?;
+            self.set_field_stream_config(path, complexity, synchronicity)?;
+        }
+
+        if !field["epc"].is_null() {
+            let epc = parse_positive(path, "epc", &field["epc"])?;
+            self.set_component_epc(path, epc);
+        }
+
+        if !field["size"].is_null() {
+            let bytes = parse_positive(path, "size", &field["size"])?;
+            self.set_field_size(path, bytes);
+        }
+
+        if !field["string_mode"].is_null() {
+            self.set_field_string_mode(path, parse_string_mode(path, &field["string_mode"])?);
+        }
+
+        if !field["enum"].is_null() {
+            let values: Option<Vec<&str>> = field["enum"].members().map(|value| value.as_str()).collect();
+            match values {
+                Some(values) if field["enum"].is_array() && !values.is_empty() => self.set_field_enum(path, &values),
+                _ => return Err(GeneratorError::InvalidConfig(format!("{}: enum has to be a list of strings", path))),
+            }
+        }
+
+        if !field["filter"].is_null() {
+            let name = field["filter"]["name"].as_str()
+                .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: the filter has no name", path)))?;
+            self.add_filter(name, path, parse_filter_predicate(path, &field["filter"])?);
+        }
+
+        // The counters of an array, the width is derived from the samples if it is not set
+        let counters = &field["counters"];
+        if !counters.is_null() {
+            let flag = |key: &str| match &counters[key] {
+                JsonValue::Null => Ok(false),
+                value => value.as_bool().ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: counters.{} has to be a boolean", path, key))),
+            };
+            self.set_array_counters(path, flag("count")?, flag("index")?);
+
+            if !counters["width"].is_null() {
+                self.set_array_counter_width(path, parse_positive(path, "counters.width", &counters["width"])?);
+            }
+        }
+
+        if !field["slice"].is_null() {
+            self.set_array_slice(path, parse_slice(path, &field["slice"])?)?;
+        }
+
+        if !field["map"].is_null() {
+            let pattern = field["map"].as_str()
+                .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: map has to be a regex of the keys", path)))?;
+            self.set_map(path, pattern);
+        }
+
+        if !field["key_presence"].is_null() {
+            let enabled = field["key_presence"].as_bool()
+                .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: key_presence has to be a boolean", path)))?;
+            self.set_key_presence(path, enabled);
+        }
+
+        if !field["assemble"].is_null() {
+            let keys: Option<Vec<&str>> = field["assemble"].members().map(|key| key.as_str()).collect();
+            match keys {
+                Some(keys) if field["assemble"].is_array() => self.set_record_assembly(path, &keys),
+                _ => return Err(GeneratorError::InvalidConfig(format!("{}: assemble has to be a list of keys", path))),
+            }
+        }
+
+        if !field["type"].is_null() {
+            let type_name = field["type"].as_str()
+                .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: type has to be the name of a declared type", path)))?;
+            self.set_field_type(path, type_name)?;
+        }
+
+        Ok(())
+    }
+
+    // "null", the name of a declared type, { "bits": <width> }, { "group": { <field>: <type>, .. } } or { "union": { .. } }
+    fn parse_logical_type(&self, name: &str, definition: &JsonValue) -> Result<LogicalType, GeneratorError> {
+        if let Some(type_name) = definition.as_str() {
+            if type_name == "null" {
+                return Ok(LogicalType::Null);
+            }
+
+            return self.gen_params.logical_types.iter()
+                .find(|declared| declared.get_name() == Some(type_name))
+                .cloned()
+                .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: {} is not declared", name, type_name)));
+        }
+
+        if !definition["bits"].is_null() {
+            return Ok(LogicalType::Bits(parse_positive(name, "bits", &definition["bits"])?));
+        }
+
+        for (kind, fields) in [("group", &definition["group"]), ("union", &definition["union"])] {
+            if !fields.is_object() {
+                continue;
+            }
+
+            let fields = fields.entries()
+                .map(|(field_name, field)| Ok((field_name.to_string(), self.parse_logical_type(&format!("{}.{}", name, field_name), field)?)))
+                .collect::<Result<Vec<_>, GeneratorError>>()?;
+
+            return Ok(if kind == "group" { LogicalType::Group(fields) } else { LogicalType::Union(fields) });
+        }
+
+        Err(GeneratorError::InvalidConfig(format!("{}: the type needs bits, group or union", name)))
+    }
+}
+
+fn parse_stream_type(name: &str) -> Result<StreamType, GeneratorError> {
+    EnumSet::<StreamType>::all().iter()
+        .find(|stream_type| stream_type.get_name() == name)
+        .ok_or_else(|| GeneratorError::InvalidConfig(format!("unknown stream type {}", name)))
+}
+
+// Complexity and synchronicity of a stream, the synchronicity defaults to Sync
+fn parse_stream_config(name: &str, value: &JsonValue) -> Result<(u8, Synchronicity), GeneratorError> {
+    let complexity = value["complexity"].as_u8()
+        .filter(|complexity| (1..=8).contains(complexity))
+        .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: complexity has to be between 1 and 8", name)))?;
+
+    let synchronicity = match value["synchronicity"].as_str().unwrap_or("Sync") {
+        "Sync" => Synchronicity::Sync,
+        "Flatten" => Synchronicity::Flatten,
+        "Desync" => Synchronicity::Desync,
+        "FlatDesync" => Synchronicity::FlatDesync,
+        other => return Err(GeneratorError::InvalidConfig(format!("{}: unknown synchronicity {}", name, other))),
+    };
+
+    Ok((complexity, synchronicity))
+}
+
+// "raw", "decoded", { "fixed": <bytes> } or { "length_prefixed": <bytes> }
+fn parse_string_mode(path: &str, value: &JsonValue) -> Result<StringMode, GeneratorError> {
+    match value.as_str() {
+        Some("raw") => return Ok(StringMode::Raw),
+        Some("decoded") => return Ok(StringMode::Decoded),
+        _ => (),
+    }
+
+    // The lengths are bounded by the width of an element and of the length prefix
+    for (key, max_length) in [("fixed", MAX_FIXED_LENGTH), ("length_prefixed", MAX_PREFIXED_LENGTH)] {
+        if value[key].is_null() {
+            continue;
+        }
+
+        let length = parse_positive(path, key, &value[key])?;
+        if length > max_length {
+            return Err(GeneratorError::InvalidConfig(format!("{}: {} can be at most {} bytes", path, key, max_length)));
+        }
+
+        return Ok(if key == "fixed" { StringMode::Fixed(length) } else { StringMode::LengthPrefixed(length) });
+    }
+
+    Err(GeneratorError::InvalidConfig(format!("{}: unknown string mode {}", path, value.dump())))
+}
+
+// { "compare": ">=", "value": 1000 }, { "is": true } or { "matches": "<regex>" }
+fn parse_filter_predicate(path: &str, filter: &JsonValue) -> Result<FilterPredicate, GeneratorError> {
+    if let Some(symbol) = filter["compare"].as_str() {
+        let op = CompareOp::from_symbol(symbol)
+            .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: unknown comparison {}", path, symbol)))?;
+        let value = filter["value"].as_u64()
+            .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: the comparison needs an unsigned value", path)))?;
+
+        return Ok(FilterPredicate::Compare(op, value));
+    }
+
+    if let Some(value) = filter["is"].as_bool() {
+        return Ok(FilterPredicate::Bool(value));
+    }
+
+    if let Some(regex) = filter["matches"].as_str() {
+        return Ok(FilterPredicate::Regex(regex.to_string()));
+    }
+
+    Err(GeneratorError::InvalidConfig(format!("{}: the filter needs compare, is or matches", path)))
+}
+
+// { "start": 0, "end": 2, "stride": 1 }, the slice starts at 0, has no end and a stride of 1 unless set
+fn parse_slice(path: &str, slice: &JsonValue) -> Result<ArraySlice, GeneratorError> {
+    let index = |key: &str| match &slice[key] {
+        JsonValue::Null => Ok(None),
+        value => value.as_usize().map(Some).ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: slice.{} has to be an index", path, key))),
+    };
+
+    let start = index("start")?.unwrap_or(0);
+    let stride = index("stride")?.unwrap_or(1);
+
+    let slice = match index("end")? {
+        Some(end) => ArraySlice::range(start, end),
+        None => ArraySlice::from(start),
+    };
+
+    Ok(slice.with_stride(stride))
+}
+
+fn parse_positive(path: &str, key: &str, value: &JsonValue) -> Result<usize, GeneratorError> {
+    value.as_usize()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| GeneratorError::InvalidConfig(format!("{}: {} has to be a positive integer", path, key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyzer::{AnalyzerError, file_manager::TemplateType};
+
+    #[test]
+    fn stream_types_by_name() {
+        assert!(parse_stream_type("IntParserStream").unwrap() == StreamType::Int);
+        assert!(matches!(parse_stream_type("IntStream"), Err(GeneratorError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn stream_configs() {
+        let config = json::parse(r#"{ "complexity": 4, "synchronicity": "Desync" }"#).unwrap();
+        assert_eq!(parse_stream_config("field", &config).unwrap(), (4, Synchronicity::Desync));
+
+        let config = json::parse(r#"{ "complexity": 2 }"#).unwrap();
+        assert_eq!(parse_stream_config("field", &config).unwrap(), (2, Synchronicity::Sync));
+
+        for config in [r#"{ "complexity": 9 }"#, r#"{ "synchronicity": "Sync" }"#, r#"{ "complexity": 2, "synchronicity": "Async" }"#] {
+            let config = json::parse(config).unwrap();
+            assert!(matches!(parse_stream_config("field", &config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn stream_configs_keep_the_ports() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "a": 1 }"#).unwrap();
+
+        // A single lane integer stream has the same ports at every complexity
+        generator.configure(r#"{ "streams": { "IntParserStream": { "complexity": 4 } } }"#).unwrap();
+        let til = generator.generate_til();
+        assert!(til.contains("complexity: 4,"));
+
+        // Without per lane last bits the JSON stream does not fit the templates, the configuration is not applied
+        let result = generator.configure(r#"{ "streams": { "JSONStream": { "complexity": 7 } } }"#);
+        assert!(matches!(result, Err(GeneratorError::AnalyzerError(AnalyzerError::IncompatibleStreamConfig(_)))));
+        assert!(generator.gen_params.stream_configs[StreamType::Json].is_none());
+        assert!(generator.analyzer.validate_stream_configs().is_ok());
+        assert_eq!(generator.generate_til(), til);
+    }
+
+    #[test]
+    fn positive_integers() {
+        assert_eq!(parse_positive("field", "epc", &JsonValue::from(4)).unwrap(), 4);
+
+        for value in [JsonValue::from(0), JsonValue::from(-1), JsonValue::from("4"), JsonValue::from(1.5)] {
+            assert!(matches!(parse_positive("field", "epc", &value), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn string_modes() {
+        let mode = |config: &str| parse_string_mode("field", &json::parse(config).unwrap());
+
+        assert_eq!(mode(r#""raw""#).unwrap(), StringMode::Raw);
+        assert_eq!(mode(r#""decoded""#).unwrap(), StringMode::Decoded);
+        assert_eq!(mode(r#"{ "fixed": 16 }"#).unwrap(), StringMode::Fixed(16));
+        assert_eq!(mode(r#"{ "length_prefixed": 64 }"#).unwrap(), StringMode::LengthPrefixed(64));
+
+        assert_eq!(mode(r#"{ "length_prefixed": 65535 }"#).unwrap(), StringMode::LengthPrefixed(65535));
+
+        for config in [r#""utf8""#, r#"{ "fixed": 0 }"#, r#"{ "fixed": 100000 }"#, r#"{ "length_prefixed": 65536 }"#, r#"{ "prefixed": 4 }"#, "8"] {
+            assert!(matches!(mode(config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn field_enums_are_lists_of_strings() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.configure(r#"{ "fields": { "unit": { "enum": ["V", "mV"] } } }"#).unwrap();
+
+        for config in [r#"{ "fields": { "unit": { "enum": "V" } } }"#, r#"{ "fields": { "unit": { "enum": [] } } }"#, r#"{ "fields": { "unit": { "enum": ["V", 1] } } }"#] {
+            assert!(matches!(generator.configure(config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn filter_predicates() {
+        let predicate = |config: &str| parse_filter_predicate("field", &json::parse(config).unwrap());
+
+        assert_eq!(predicate(r#"{ "compare": ">=", "value": 1000 }"#).unwrap(), FilterPredicate::Compare(CompareOp::Ge, 1000));
+        assert_eq!(predicate(r#"{ "is": false }"#).unwrap(), FilterPredicate::Bool(false));
+        assert_eq!(predicate(r#"{ "matches": "m?V" }"#).unwrap(), FilterPredicate::Regex("m?V".to_string()));
+
+        for config in [r#"{ "compare": "=>", "value": 1 }"#, r#"{ "compare": "<", "value": -1 }"#, r#"{ "compare": "<" }"#, "{}"] {
+            assert!(matches!(predicate(config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn array_counters() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "values": [1, 2, 3] }"#).unwrap();
+
+        generator.configure(r#"{ "fields": { "values": { "counters": { "count": true, "width": 6 } } } }"#).unwrap();
+        let til = generator.generate_til();
+        assert!(til.contains("count_array_parser"));
+        assert!(!til.contains("index_array_parser"));
+
+        for config in [r#"{ "fields": { "values": { "counters": { "count": 1 } } } }"#, r#"{ "fields": { "values": { "counters": { "width": 0 } } } }"#] {
+            assert!(matches!(generator.configure(config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn array_slices() {
+        let slice = |config: &str| parse_slice("values", &json::parse(config).unwrap());
+
+        assert_eq!(slice(r#"{ "start": 1, "end": 4, "stride": 2 }"#).unwrap(), ArraySlice::range(1, 4).with_stride(2));
+        assert_eq!(slice("{}").unwrap(), ArraySlice::from(0));
+        assert!(matches!(slice(r#"{ "end": -1 }"#), Err(GeneratorError::InvalidConfig(_))));
+
+        // Slices without elements are rejected by the generator
+        let mut generator = Generator::new("test", 4, 64);
+        let result = generator.configure(r#"{ "fields": { "values": { "slice": { "start": 2, "end": 2 } } } }"#);
+        assert!(matches!(result, Err(GeneratorError::InvalidSlice(_))));
+    }
+
+    #[test]
+    fn maps() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "sensors": { "sensor_1": 10, "sensor_2": 20, "sensor_3": 30 } }"#).unwrap();
+
+        // One key parser for all keys of the map
+        generator.configure(r#"{ "fields": { "sensors": { "map": "sensor_[0-9]+" } } }"#).unwrap();
+        assert_eq!(generator.generate_til().matches("= int_parser<").count(), 1);
+
+        // Several keys can close in a transfer of more than one element
+        assert!(matches!(generator.analyzer.validate_maps(), Err(AnalyzerError::InvalidMap(_))));
+        generator.configure(r#"{ "fields": { "sensors": { "epc": 1 } } }"#).unwrap();
+        assert!(generator.analyzer.validate_maps().is_ok());
+
+        assert!(matches!(generator.configure(r#"{ "fields": { "sensors": { "map": true } } }"#), Err(GeneratorError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn key_presence() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "a": 1, "b": true }"#).unwrap();
+
+        generator.configure(r#"{ "fields": { "": { "key_presence": true } } }"#).unwrap();
+        let til = generator.generate_til();
+        assert!(til.contains("KeyPresenceStream"));
+
+        // The key parsers send their match results, there is no matcher besides theirs
+        assert!(til.contains("key_parser_L2_00_inst.presence -- key_presence_L2_00_inst.match_0;"));
+        assert!(til.contains("key_parser_L2_01_inst.presence -- key_presence_L2_00_inst.match_1;"));
+        assert_eq!(til.matches(".matcher_match;").count(), 2);
+
+        generator.configure(r#"{ "fields": { "": { "key_presence": false } } }"#).unwrap();
+        assert!(!generator.generate_til().contains("KeyPresenceStream"));
+
+        assert!(matches!(generator.configure(r#"{ "fields": { "": { "key_presence": "yes" } } }"#), Err(GeneratorError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn record_assembly() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "a": 1, "b": true, "c": "text" }"#).unwrap();
+
+        generator.configure(r#"{ "fields": { "": { "assemble": ["a", "b"] } } }"#).unwrap();
+        assert!(generator.analyzer.validate_assemblies().is_ok());
+        assert!(generator.generate_til().contains("AssembledRecordStream"));
+
+        // The error names the record and the key which cannot be assembled
+        generator.configure(r#"{ "fields": { "": { "assemble": ["a", "c"] } } }"#).unwrap();
+        let error = GeneratorError::AnalyzerError(generator.analyzer.validate_assemblies().unwrap_err()).to_string();
+        assert_eq!(error, "analysis failed: invalid record assembly: c is not an integer or boolean key of the record at \"\"");
+
+        assert!(matches!(generator.configure(r#"{ "fields": { "": { "assemble": "a" } } }"#), Err(GeneratorError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn logical_types() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.configure(r#"{ "types": { "flag": { "bits": 1 }, "reading": { "group": { "value": { "bits": 64 }, "valid": "flag" } } } }"#).unwrap();
+
+        let flag = LogicalType::named("flag", LogicalType::Bits(1));
+        let reading = LogicalType::Group(vec![("value".to_string(), LogicalType::Bits(64)), ("valid".to_string(), flag.clone())]);
+        assert_eq!(generator.gen_params.logical_types, vec![flag, LogicalType::named("reading", reading)]);
+
+        let union = generator.parse_logical_type("kinds", &json::parse(r#"{ "union": { "none": "null", "flag": "flag" } }"#).unwrap()).unwrap();
+        assert_eq!(union.get_bits(), 2);
+
+        for config in [r#"{ "types": { "a": "missing" } }"#, r#"{ "types": { "a": { "bits": 0 } } }"#, r#"{ "types": { "a": {} } }"#] {
+            assert!(matches!(generator.configure(config), Err(GeneratorError::InvalidConfig(_))));
+        }
+    }
+
+    #[test]
+    fn types_are_declared_once() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.add_type("flag", LogicalType::Bits(1)).unwrap();
+        generator.add_type("flag", LogicalType::Bits(1)).unwrap();
+        assert_eq!(generator.gen_params.logical_types.len(), 1);
+
+        assert!(matches!(generator.add_type("flag", LogicalType::Bits(2)), Err(GeneratorError::InvalidType(_))));
+    }
+
+    #[test]
+    fn field_types() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "voltage": 1128, "valid": true }"#).unwrap();
+
+        generator.configure(r#"{ "types": { "flag": { "bits": 1 } }, "fields": { "valid": { "type": "flag" } } }"#).unwrap();
+        assert!(generator.analyzer.validate_types().is_ok());
+        let til = generator.generate_til();
+        assert!(til.contains("type flag = Bits(1);"));
+        assert!(til.contains("data: flag,"));
+
+        // The integer has 64 bits
+        generator.configure(r#"{ "fields": { "voltage": { "type": "flag" } } }"#).unwrap();
+        assert!(matches!(generator.analyzer.validate_types(), Err(AnalyzerError::InvalidType(_))));
+
+        assert!(matches!(generator.set_field_type("valid", "missing"), Err(GeneratorError::InvalidType(_))));
+    }
+
+    #[test]
+    fn path_names_do_not_depend_on_the_key_order() {
+        let til_with = |config: &str, json: &str| {
+            let mut generator = Generator::new("test", 4, 64);
+            generator.configure(config).unwrap();
+            generator.analyze(json).unwrap();
+
+            let mut lines: Vec<String> = generator.generate_til().lines().map(|line| line.trim().to_string()).collect();
+            lines.sort();
+            lines
+        };
+        let til = |json: &str| til_with(r#"{ "naming": "path" }"#, json);
+
+        let til_a = til(r#"{ "a_b": { "c": 1 }, "a": { "b_c": 2 } }"#);
+        let til_b = til(r#"{ "a": { "b_c": 2 }, "a_b": { "c": 1 } }"#);
+        assert_eq!(til_a, til_b);
+
+        // Both colliding paths are suffixed
+        assert!(!til_a.iter().any(|line| line.contains("a_b_c_int_parser_inst ")));
+
+        // The sole record parser entity keeps its name although the root record is named record_parser too
+        assert!(til_a.iter().any(|line| line.starts_with("streamlet record_parser ")));
+
+        // Counters make two variants of the array parser
+        let config = r#"{ "naming": "path", "fields": { "temperature": { "counters": { "count": true } } } }"#;
+        let til_a = til_with(config, r#"{ "temperature": [1, 2], "humidity": [3, 4] }"#);
+        let til_b = til_with(config, r#"{ "humidity": [3, 4], "temperature": [1, 2] }"#);
+        assert_eq!(til_a, til_b);
+        assert_eq!(til_a.iter().filter(|line| line.starts_with("streamlet array_parser_")).count(), 2);
+    }
+
+    #[test]
+    fn template_overrides_decide_the_shared_entities() {
+        let streamlets = |generator: &mut Generator| generator.generate_til().matches("streamlet key_parser").count();
+
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "a": 1, "b": 2, "c": 3 }"#).unwrap();
+        assert_eq!(streamlets(&mut generator), 1);
+
+        // A key parser which depends on its key cannot be shared
+        let dir = std::env::temp_dir().join(format!("json_hierachy_templates_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let template = TemplateType::Key.get_default_template().unwrap();
+        std::fs::write(dir.join("key_parser.vhd"), format!("-- key ${{key}}\n{}", template)).unwrap();
+
+        let config = json::object! { template_dir: dir.to_str().unwrap() };
+        let result = generator.configure(&config.dump());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(streamlets(&mut generator), 3);
+    }
+
+    #[test]
+    fn configure_rejects_invalid_templates() {
+        let dir = std::env::temp_dir().join(format!("json_hierachy_invalid_templates_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("key_parser.vhd"), "${if optional}").unwrap();
+
+        let mut generator = Generator::new("test", 4, 64);
+        let config = json::object! { template_dir: dir.to_str().unwrap() };
+        let result = generator.configure(&config.dump());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(result, Err(GeneratorError::InvalidTemplate(_))));
+    }
+
+    #[test]
+    fn configure_rejects_unknown_streams() {
+        let mut generator = Generator::new("test", 4, 64);
+        let result = generator.configure(r#"{ "streams": { "FooStream": { "complexity": 2 } } }"#);
+
+        assert!(matches!(result, Err(GeneratorError::InvalidConfig(_))));
+    }
+}
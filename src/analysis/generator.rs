@@ -1,39 +1,309 @@
-use super::{visualization, Generator, GeneratorParams, analyzer::Analyzer, GeneratorError};
+use super::{visualization::{self, DiagramFormat}, Generator, NamingStrategy, StringMode, FilterPredicate, ArraySlice, LogicalType, GeneratorParams, analyzer::{Analyzer, file_manager, samples, type_manager::{StreamType, StreamConfig, Synchronicity}}, GeneratorError, types::physical_stream::{PhysicalStream, Transfer}};
 
 impl Generator {
     pub fn new(project_name: &str, epc: usize, int_width: usize) -> Generator {
         Generator {
             analyzer: Analyzer::new(),
             gen_params: GeneratorParams::new(epc, 8, int_width, "", project_name),
+            samples: Vec::new(),
         }
     }
 
-    // Analyze a JSON string
+    // Analyze a JSON string, replacing the samples analyzed before
     pub fn analyze(&mut self, json: &str) -> Result<(), GeneratorError> {
-        // Deserialize the JSON string
-        let parsed = json::parse(json)
-        // In case of error, return the error
-        .map_err(GeneratorError::JsonError)?; 
+        self.analyze_all(&[json])
+    }
+
+    // Analyze multiple JSON strings as samples of one schema, the components cover the structure of all of them
+    pub fn analyze_all(&mut self, jsons: &[&str]) -> Result<(), GeneratorError> {
+        // Deserialize the JSON strings
+        let parsed = jsons.iter()
+            .map(|json| json::parse(json))
+            .collect::<Result<Vec<_>, _>>()
+            // In case of error, return the error
+            .map_err(GeneratorError::JsonError)?;
+
+        self.samples = parsed;
+
+        self.reanalyze();
 
-        self.analyzer.analyze(&parsed, self.gen_params.clone());
+        // The configured streams have to keep their ports for the new samples too
+        self.analyzer.validate_stream_configs().map_err(GeneratorError::AnalyzerError)
+    }
+
+    // Set the complexity and synchronicity of all streams of a type
+    pub fn set_stream_config(&mut self, stream_type: StreamType, complexity: u8, synchronicity: Synchronicity) -> Result<(), GeneratorError> {
+        self.apply_stream_config(|gen_params| gen_params.stream_configs[stream_type] = Some(StreamConfig::new(complexity, synchronicity)))
+    }
+
+    // Set the complexity and synchronicity of the output stream of the field at a JSON path (e.g. "temperature[].voltage")
+    pub fn set_field_stream_config(&mut self, path: &str, complexity: u8, synchronicity: Synchronicity) -> Result<(), GeneratorError> {
+        self.apply_stream_config(|gen_params| { gen_params.field_configs.insert(path.to_string(), StreamConfig::new(complexity, synchronicity)); })
+    }
+
+    // The templates are written for the ports of the default configurations, a configuration which changes
+    // the ports of a stream of the analyzed samples is rejected and the previous one is kept
+    fn apply_stream_config(&mut self, apply: impl FnOnce(&mut GeneratorParams)) -> Result<(), GeneratorError> {
+        let previous = self.gen_params.clone();
+        apply(&mut self.gen_params);
+
+        self.reanalyze();
+
+        if let Err(error) = self.analyzer.validate_stream_configs() {
+            self.gen_params = previous;
+            self.reanalyze();
+
+            return Err(GeneratorError::AnalyzerError(error));
+        }
 
         Ok(())
     }
 
+    // Set the EPC of the components at a JSON path (e.g. "temperature[].voltage")
+    pub fn set_component_epc(&mut self, path: &str, epc: usize) {
+        self.gen_params.epc_overrides.insert(path.to_string(), epc);
+
+        self.reanalyze();
+    }
+
+    // Set the expected number of bytes per document of the field at a JSON path, from which its EPC is derived
+    pub fn set_field_size(&mut self, path: &str, bytes: usize) {
+        self.gen_params.field_sizes.insert(path.to_string(), bytes);
+
+        self.reanalyze();
+    }
+
+    // Set how all string values are emitted
+    pub fn set_string_mode(&mut self, mode: StringMode) {
+        self.gen_params.string_mode = mode;
+
+        self.reanalyze();
+    }
+
+    // Set how the string at a JSON path is emitted (e.g. "temperature[].unit")
+    pub fn set_field_string_mode(&mut self, path: &str, mode: StringMode) {
+        self.gen_params.string_modes.insert(path.to_string(), mode);
+
+        self.reanalyze();
+    }
+
+    // Encode string fields with at most this many distinct values in the samples as enums, 0 disables it
+    // A value set is only considered closed if the samples contain every value twice on average
+    pub fn set_enum_detection(&mut self, max_values: usize) {
+        self.gen_params.enum_max_values = max_values;
+
+        self.reanalyze();
+    }
+
+    // Encode the string at a JSON path as an enum of the given values, in order of their code
+    pub fn set_field_enum(&mut self, path: &str, values: &[&str]) {
+        self.gen_params.enum_values.insert(path.to_string(), values.iter().map(|value| value.to_string()).collect());
+
+        self.reanalyze();
+    }
+
+    // Encode the string fields with an enum in a JSON Schema as enums
+    pub fn set_schema_enums(&mut self, schema: &str) -> Result<(), GeneratorError> {
+        let schema = json::parse(schema).map_err(GeneratorError::JsonError)?;
+
+        self.gen_params.enum_values.extend(samples::schema_enums(&schema));
+
+        self.reanalyze();
+
+        Ok(())
+    }
+
+    // Test the value at a JSON path (e.g. "temperature[].voltage") with a predicate, the value is passed through
+    // and the stream selected_<name> has a bit for every value telling if it is selected
+    pub fn add_filter(&mut self, name: &str, path: &str, predicate: FilterPredicate) {
+        self.gen_params.filters.insert(path.to_string(), (name.to_string(), predicate));
+
+        self.reanalyze();
+    }
+
+    // Emit the number of elements of every array at a JSON path (count_<array>) and the index of every element (index_<array>)
+    pub fn set_array_counters(&mut self, path: &str, count: bool, index: bool) {
+        let counters = self.gen_params.array_counters.entry(path.to_string()).or_default();
+        counters.count = count;
+        counters.index = index;
+
+        self.reanalyze();
+    }
+
+    // Set the width of the element counter of the arrays at a JSON path instead of deriving it from the samples
+    pub fn set_array_counter_width(&mut self, path: &str, width: usize) {
+        self.gen_params.array_counters.entry(path.to_string()).or_default().width = Some(width);
+
+        self.reanalyze();
+    }
+
+    // Keep only the elements in the slice of the arrays at a JSON path, the others are dropped before they reach
+    // the element parsers. The count and index outputs of the array still cover all elements.
+    pub fn set_array_slice(&mut self, path: &str, slice: ArraySlice) -> Result<(), GeneratorError> {
+        if !slice.is_valid() {
+            return Err(GeneratorError::InvalidSlice(format!("{}: {}", path, slice)));
+        }
+
+        self.gen_params.array_slices.insert(path.to_string(), slice);
+
+        self.reanalyze();
+
+        Ok(())
+    }
+
+    // Parse the objects at a JSON path as maps, a single key parser handles every key matching the pattern (a regex)
+    // and emits the key names on key_name_<key>. The values of all keys are described by the path <path>.*
+    // The map has to run at an EPC of 1 (set_component_epc), so every transfer closes at most one key.
+    pub fn set_map(&mut self, path: &str, pattern: &str) {
+        self.gen_params.map_patterns.insert(path.to_string(), pattern.to_string());
+
+        self.reanalyze();
+    }
+
+    // Emit a vector for every record at a JSON path with a bit for every key telling if it was present (presence_<record>),
+    // bit 0 is the first key of the record
+    pub fn set_key_presence(&mut self, path: &str, enabled: bool) {
+        if enabled {
+            self.gen_params.key_presence.insert(path.to_string());
+        } else {
+            self.gen_params.key_presence.remove(path);
+        }
+
+        self.reanalyze();
+    }
+
+    // Join the values of keys of the records at a JSON path into one element per record (assembled_<record>), a group
+    // with a field for every key. The keys have to be integers or booleans, their values are zero in records without them.
+    // An empty list of keys disables it.
+    pub fn set_record_assembly(&mut self, path: &str, keys: &[&str]) {
+        if keys.is_empty() {
+            self.gen_params.record_assemblies.remove(path);
+        } else {
+            self.gen_params.record_assemblies.insert(path.to_string(), keys.iter().map(|key| key.to_string()).collect());
+        }
+
+        self.reanalyze();
+    }
+
+    // Declare a named element type in the TIL, e.g. a group or union of the outputs for the components around the parser.
+    // The types it uses are declared before it.
+    pub fn add_type(&mut self, name: &str, logical_type: LogicalType) -> Result<(), GeneratorError> {
+        let named_type = LogicalType::named(name, logical_type);
+
+        // A type can only be declared again with the same definition
+        match self.gen_params.logical_types.iter().find(|declared| declared.get_name() == Some(name)) {
+            Some(declared) if *declared != named_type => {
+                return Err(GeneratorError::InvalidType(format!("{} is already declared as {}", name, declared.resolve())));
+            },
+            Some(_) => return Ok(()),
+            None => self.gen_params.logical_types.push(named_type),
+        }
+
+        self.reanalyze();
+
+        Ok(())
+    }
+
+    // Emit the integer or boolean at a JSON path as a declared type instead of bits, the type needs the width of the value
+    pub fn set_field_type(&mut self, path: &str, type_name: &str) -> Result<(), GeneratorError> {
+        let logical_type = self.gen_params.logical_types.iter()
+            .find(|declared| declared.get_name() == Some(type_name))
+            .ok_or_else(|| GeneratorError::InvalidType(format!("{}: {} is not declared", path, type_name)))?;
+
+        self.gen_params.field_types.insert(path.to_string(), logical_type.clone());
+
+        self.reanalyze();
+
+        Ok(())
+    }
+
+    // Choose how the components are named, path derived names are stable when the schema changes
+    pub fn set_naming_strategy(&mut self, strategy: NamingStrategy) {
+        self.gen_params.naming = strategy;
+
+        self.reanalyze();
+    }
+
+    // Use the templates in a directory instead of the built-in ones, templates are overridden by file name (e.g. key_parser.vhd)
+    pub fn set_template_dir(&mut self, dir: &str) -> Result<Vec<String>, GeneratorError> {
+        let overridden = file_manager::validate_template_dir(dir).map_err(GeneratorError::InvalidTemplate)?;
+
+        self.gen_params.template_dir = Some(dir.to_string());
+
+        // The templates are part of the signatures which decide which components share an entity
+        self.reanalyze();
+
+        Ok(overridden)
+    }
+
+    // Analyze all samples again with the current parameters
+    fn reanalyze(&mut self) {
+        // Start from a clean analyzer so the components cover all samples
+        self.analyzer = Analyzer::new();
+
+        if !self.samples.is_empty() {
+            self.analyzer.analyze(&self.samples, self.gen_params.clone());
+        }
+    }
+
     // Visualize the component tree as a dot file
     pub fn visualize(&self, path: &str) -> Result<(), GeneratorError> {
+        self.visualize_as(path, DiagramFormat::Dot)
+    }
+
+    // Render the component tree in the given diagram language
+    pub fn visualize_as(&self, path: &str, format: DiagramFormat) -> Result<(), GeneratorError> {
         let root = self.analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
 
-        visualization::generate_dot(root, path);
+        visualization::generate_tree_diagram(root, path, format);
 
         Ok(())
     }
 
+    // Render the instance netlist of the top component with the streams connecting the instances
+    pub fn visualize_netlist(&mut self, path: &str) -> Result<(), GeneratorError> {
+        self.visualize_netlist_as(path, DiagramFormat::Dot)
+    }
+
+    // Render the instance netlist in the given diagram language
+    pub fn visualize_netlist_as(&mut self, path: &str, format: DiagramFormat) -> Result<(), GeneratorError> {
+        let top_component = self.analyzer.assemble_top_component().map_err(GeneratorError::AnalyzerError)?;
+        let (_, streamlets) = self.analyzer.get_definitions();
+        let root = self.analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
+
+        let netlist = visualization::build_netlist(&top_component, streamlets, root);
+        visualization::generate_netlist_diagram(&netlist, path, format);
+
+        Ok(())
+    }
+
+    // Convert a buffer of JSON documents into transfers on the input stream of the top component
+    pub fn serialize_input(&self, json: &[u8]) -> Result<Vec<Transfer>, GeneratorError> {
+        let stream = PhysicalStream::new(&self.analyzer.get_input_stream_type(), &self.gen_params);
+
+        stream.serialize_json(json).map_err(GeneratorError::TransferError)
+    }
+
     pub fn generate(&mut self, path: &str) -> Result<(), GeneratorError> {
         // Set the output directory
         self.gen_params.output_dir = format!("{}/{}", path, self.gen_params.project_name);
         let proj_dir = &self.gen_params.output_dir;
 
+        // Make sure the configured streams can still be driven by the templates
+        self.analyzer.validate_stream_configs().map_err(GeneratorError::AnalyzerError)?;
+
+        // Every filter needs a value of the type of its predicate
+        self.analyzer.validate_filters().map_err(GeneratorError::AnalyzerError)?;
+
+        // Every map key parser can only emit one key name per transfer
+        self.analyzer.validate_maps().map_err(GeneratorError::AnalyzerError)?;
+
+        // Every assembled key needs a value which fits in the group
+        self.analyzer.validate_assemblies().map_err(GeneratorError::AnalyzerError)?;
+
+        // Every type name has one definition and the field types fit their values
+        self.analyzer.validate_types().map_err(GeneratorError::AnalyzerError)?;
+
         // Check if directory exists
         if std::path::Path::new(proj_dir).exists() {
             // If it does, delete it
@@ -54,10 +324,46 @@ impl Generator {
 
         // Generate the files
         let file_manager = self.analyzer.get_file_manager();
-        file_manager.generate_files(&self.gen_params.output_dir, &self.gen_params);
+        file_manager.generate_files(&self.gen_params.output_dir, &self.gen_params)?;
+        file_manager.generate_support_files(&self.gen_params.output_dir);
+        file_manager.generate_compile_order(&self.gen_params.output_dir, &self.gen_params);
+        file_manager.generate_project_files(&self.gen_params.output_dir, &self.gen_params);
 
         file_manager.generate_toml(&self.gen_params.output_dir, &self.gen_params);
 
+        // Generate the cocotb testbench
+        self.generate_testbench()?;
+
+        // Estimate the resources of the generated components
+        let proj_dir = self.gen_params.output_dir.clone();
+        self.generate_report(&proj_dir)?;
+
+        // Render the HTML report for reviewing the schema
+        self.generate_html(&format!("{}/{}.html", proj_dir, self.gen_params.project_name))?;
+
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze_replaces_the_samples() {
+        let mut generator = Generator::new("test", 4, 64);
+        generator.analyze(r#"{ "a": 1 }"#).unwrap();
+        generator.analyze(r#"{ "b": true }"#).unwrap();
+
+        let til = generator.generate_til();
+        assert!(til.contains("bool_parser"));
+        assert!(!til.contains("int_parser"));
+
+        generator.analyze_all(&[r#"{ "a": 1 }"#, r#"{ "b": true }"#]).unwrap();
+        let til = generator.generate_til();
+        assert!(til.contains("bool_parser"));
+        assert!(til.contains("int_parser"));
+
+        assert!(matches!(generator.analyze_all(&["{}", "{"]), Err(GeneratorError::JsonError(_))));
+    }
+}
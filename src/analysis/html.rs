@@ -0,0 +1,219 @@
+use std::{collections::HashMap, fs::File, io::Write};
+
+use super::{Generator, GeneratorError, StringMode, components::{Generatable, JsonComponent, JsonComponentValue}, types::{TilSignal, physical_stream::PhysicalStream, til_streamlet::TilImplementationType}};
+
+/**********************************************************************************
+ * Set of functions to render a self-contained HTML report of the analyzed schema *
+ * which can be reviewed without any additional tooling                           *
+ **********************************************************************************/
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+th { background: #eee; }
+details { margin-left: 1.2em; }
+summary { cursor: pointer; }
+code { color: #555; }
+pre { background: #f6f6f6; padding: 1em; overflow-x: auto; }
+";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        "(root)".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+// Render a component and its children as nested collapsible elements
+fn render_tree(component: &JsonComponent, html: &mut String) {
+    let label = escape_html(&component.to_graph_node().replace('\n', ", "));
+    let path = escape_html(&display_path(component.get_generatable().get_path()));
+    let children = component.get_children();
+
+    if children.is_empty() {
+        html.push_str(&format!("<div style=\"margin-left: 1.2em\">{} <code>{}</code></div>\n", label, path));
+    } else {
+        html.push_str(&format!("<details open>\n<summary>{} <code>{}</code></summary>\n", label, path));
+        for child in &children {
+            render_tree(child, html);
+        }
+        html.push_str("</details>\n");
+    }
+}
+
+// Collect the JSON path of every instance in the component tree
+fn collect_paths(component: &JsonComponent, paths: &mut HashMap<String, String>) {
+    let gen_com = component.get_generatable();
+    paths.insert(gen_com.get_instance_name(), gen_com.get_path().to_string());
+
+    for child in component.get_children() {
+        collect_paths(&child, paths);
+    }
+}
+
+// Code tables of the enum encoders in the component tree
+fn collect_enum_codes(component: &JsonComponent, rows: &mut Vec<Vec<String>>) {
+    if let JsonComponent::Enum(encoder) = component {
+        let path = format!("<code>{}</code>", escape_html(&display_path(encoder.get_path())));
+        let output_name = escape_html(&format!("output_{}", encoder.get_instance_name()));
+
+        for (code, value) in encoder.get_values().iter().enumerate() {
+            rows.push(vec![path.clone(), output_name.clone(), code.to_string(), format!("<code>{}</code>", escape_html(value))]);
+        }
+
+        // The flag above the code marks values which are not in the table
+        rows.push(vec![path, output_name, format!("bit {} set", encoder.get_code_width()), "(unknown)".to_string()]);
+    }
+
+    for child in component.get_children() {
+        collect_enum_codes(&child, rows);
+    }
+}
+
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for column in header {
+        html.push_str(&format!("<th>{}</th>", column));
+    }
+    html.push_str("</tr>\n");
+
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</table>\n");
+    html
+}
+
+impl Generator {
+    pub fn generate_html(&mut self, path: &str) -> Result<(), GeneratorError> {
+        let til = self.generate_til();
+        let top_component = self.analyzer.assemble_top_component().map_err(GeneratorError::AnalyzerError)?;
+        let root = self.analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
+        let params = &self.gen_params;
+
+        // Generator parameters
+        let mut param_rows = vec![
+            vec!["Project name".to_string(), escape_html(&params.project_name)],
+            vec!["TIL namespace".to_string(), escape_html(&params.namespace)],
+            vec!["Elements per cycle".to_string(), params.epc.to_string()],
+            vec!["Bit width".to_string(), params.bit_width.to_string()],
+            vec!["Integer width".to_string(), params.int_width.to_string()],
+        ];
+
+        if params.enum_max_values > 0 {
+            param_rows.push(vec!["Enum detection".to_string(), format!("Up to {} values", params.enum_max_values)]);
+        }
+
+        if params.string_mode != StringMode::Raw {
+            param_rows.push(vec!["String mode".to_string(), format!("{:?}", params.string_mode)]);
+        }
+
+        for (stream_type, config) in &params.stream_configs {
+            if let Some(config) = config {
+                param_rows.push(vec![format!("Stream config {}", stream_type.get_name()), config.get_name_suffix()]);
+            }
+        }
+
+        let mut overrides: Vec<(&String, String)> = params.field_configs.iter().map(|(field, config)| (field, format!("Stream config {}", config.get_name_suffix()))).collect();
+        overrides.extend(params.epc_overrides.iter().map(|(field, epc)| (field, format!("EPC {}", epc))));
+        overrides.extend(params.field_sizes.iter().map(|(field, size)| (field, format!("Size {} bytes", size))));
+        overrides.extend(params.string_modes.iter().map(|(field, mode)| (field, format!("String mode {:?}", mode))));
+        overrides.extend(params.enum_values.iter().map(|(field, values)| (field, format!("Enum of {} values", values.len()))));
+        overrides.extend(params.array_counters.iter().map(|(field, counters)| (field, format!("Array count: {}, index: {}, counter width: {}", counters.count, counters.index, counters.width.map_or("derived".to_string(), |width| width.to_string())))));
+        overrides.extend(params.array_slices.iter().map(|(field, slice)| (field, format!("Array slice {}", slice))));
+        overrides.extend(params.key_presence.iter().map(|field| (field, "Key presence".to_string())));
+        overrides.extend(params.record_assemblies.iter().map(|(field, keys)| (field, escape_html(&format!("Assembled keys: {}", keys.join(", "))))));
+        overrides.extend(params.map_patterns.iter().map(|(field, pattern)| (field, escape_html(&format!("Map of keys matching /{}/", pattern)))));
+        overrides.extend(params.field_types.iter().map(|(field, logical_type)| (field, escape_html(&format!("Type {}", logical_type)))));
+        overrides.extend(params.filters.iter().map(|(field, (name, predicate))| (field, escape_html(&format!("Filter {}: {}", name, predicate)))));
+        overrides.sort();
+
+        for (field, setting) in overrides {
+            param_rows.push(vec![format!("<code>{}</code>", escape_html(&display_path(field))), setting]);
+        }
+
+        // Output streams with the JSON path they are extracted from
+        let mut paths = HashMap::new();
+        collect_paths(root, &mut paths);
+
+        let signals = match top_component.get_implementation() {
+            Some(TilImplementationType::Inline(implementation)) => implementation.get_signals().clone(),
+            _ => Vec::new(),
+        };
+
+        let mut stream_rows = Vec::new();
+        for signal in &signals {
+            if let TilSignal::Output { source_inst_name, output_stream, .. } = signal {
+                let physical = PhysicalStream::new(output_stream.get_type(), params);
+                let json_path = paths.get(source_inst_name).map_or("".to_string(), |path| display_path(path));
+
+                stream_rows.push(vec![
+                    escape_html(output_stream.get_name()),
+                    format!("<code>{}</code>", escape_html(&json_path)),
+                    escape_html(&output_stream.get_type().get_resolved_string()),
+                    physical.get_data_bits().to_string(),
+                    physical.get_lanes().to_string(),
+                    physical.get_dimensionality().to_string(),
+                    physical.get_complexity().to_string(),
+                ]);
+            }
+        }
+
+        // VHDL files which are generated, in the order they have to be compiled
+        let mut files = String::from("<ul>\n");
+        for file_name in self.analyzer.get_file_manager().get_compile_order(params) {
+            files.push_str(&format!("<li><code>{}</code></li>\n", escape_html(&file_name)));
+        }
+        files.push_str("</ul>\n");
+
+        let mut enum_rows = Vec::new();
+        collect_enum_codes(root, &mut enum_rows);
+
+        let mut tree = String::new();
+        render_tree(root, &mut tree);
+
+        let mut html = String::new();
+        html.push_str(&format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", escape_html(&params.project_name), STYLE));
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(&params.project_name)));
+        html.push_str("<h2>Generator parameters</h2>\n");
+        html.push_str(&table(&["Parameter", "Value"], &param_rows));
+        html.push_str("<h2>JSON hierarchy</h2>\n");
+        html.push_str(&tree);
+        html.push_str("<h2>Output streams</h2>\n");
+        html.push_str(&table(&["Stream", "JSON path", "Type", "Data bits", "Lanes", "Dimensionality", "Complexity"], &stream_rows));
+        if !enum_rows.is_empty() {
+            html.push_str("<h2>Enum codes</h2>\n");
+            html.push_str(&table(&["JSON path", "Stream", "Code", "Value"], &enum_rows));
+        }
+        html.push_str("<h2>VHDL files</h2>\n");
+        html.push_str(&files);
+        html.push_str("<h2>TIL</h2>\n");
+        html.push_str(&format!("<pre>{}</pre>\n", escape_html(&til)));
+        html.push_str("</body>\n</html>\n");
+
+        // Separate output path into directory and file name
+        let (dir, _) = path.split_at(path.rfind('/').unwrap_or(0));
+        if !dir.is_empty() {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+
+        let mut file = File::create(path).unwrap();
+        file.write_all(html.as_bytes()).unwrap();
+
+        Ok(())
+    }
+}
@@ -1,16 +1,34 @@
-use self::analyzer::Analyzer;
+use std::collections::{HashMap, HashSet};
+
+use enum_map::EnumMap;
+use json::JsonValue;
+
+use self::{analyzer::{Analyzer, type_manager::{StreamType, StreamConfig}}, components::ArrayCounters};
+
+pub use self::analyzer::NamingStrategy;
+
+pub use self::visualization::DiagramFormat;
+
+pub use self::components::{StringMode, FilterPredicate, ArraySlice};
+
+pub use self::types::logical_type::LogicalType;
 
 mod components;
 mod visualization;
+mod testbench;
+mod report;
+mod html;
 pub mod generator;
 pub mod analyzer;
 pub mod types;
 mod til;
+mod config;
 
 
 pub struct Generator {
     analyzer: Analyzer,
     gen_params: GeneratorParams,
+    samples: Vec<JsonValue>,
 }
 
 #[derive(Default, Clone)]
@@ -22,6 +40,31 @@ pub struct GeneratorParams {
     project_name: String,
     namespace: String,
     comp_namespace: String,
+    stream_configs: EnumMap<StreamType, Option<StreamConfig>>,
+    field_configs: HashMap<String, StreamConfig>,
+    epc_overrides: HashMap<String, usize>,
+    field_sizes: HashMap<String, usize>,
+    naming: NamingStrategy,
+    template_dir: Option<String>,
+    string_mode: StringMode,
+    string_modes: HashMap<String, StringMode>,
+    // Largest number of distinct sample values a string field can have to be encoded as an enum, 0 disables it
+    enum_max_values: usize,
+    enum_values: HashMap<String, Vec<String>>,
+    // Name and predicate of the filter on the value at a JSON path
+    filters: HashMap<String, (String, FilterPredicate)>,
+    array_counters: HashMap<String, ArrayCounters>,
+    array_slices: HashMap<String, ArraySlice>,
+    // Pattern matching the keys of the objects at a JSON path which are used as maps
+    map_patterns: HashMap<String, String>,
+    // Paths of the records which emit the presence of their keys
+    key_presence: HashSet<String>,
+    // Keys of the records at a JSON path which are joined into one element per record
+    record_assemblies: HashMap<String, Vec<String>>,
+    // Named element types declared by the user, in order of declaration
+    logical_types: Vec<LogicalType>,
+    // Declared type the integer or boolean at a JSON path is emitted as
+    field_types: HashMap<String, LogicalType>,
 }
 
 impl GeneratorParams {
@@ -37,9 +80,46 @@ impl GeneratorParams {
             output_dir: output_dir.to_owned(),
             project_name,
             namespace: til_ns,
-            comp_namespace: comp_ns
+            comp_namespace: comp_ns,
+            stream_configs: EnumMap::default(),
+            field_configs: HashMap::new(),
+            epc_overrides: HashMap::new(),
+            field_sizes: HashMap::new(),
+            naming: NamingStrategy::default(),
+            template_dir: None,
+            string_mode: StringMode::default(),
+            string_modes: HashMap::new(),
+            enum_max_values: 0,
+            enum_values: HashMap::new(),
+            filters: HashMap::new(),
+            array_counters: HashMap::new(),
+            array_slices: HashMap::new(),
+            map_patterns: HashMap::new(),
+            key_presence: HashSet::new(),
+            record_assemblies: HashMap::new(),
+            logical_types: Vec::new(),
+            field_types: HashMap::new(),
+        }
+    }
+
+    // Throughput of a stream of a component with the given EPC, if it deviates from the project wide EPC
+    pub fn get_throughput(&self, epc: usize) -> Option<usize> {
+        if epc == self.epc {
+            None
+        } else {
+            Some(epc)
         }
     }
+
+    // How the string at the JSON path is emitted
+    pub fn get_string_mode(&self, path: &str) -> StringMode {
+        self.string_modes.get(path).copied().unwrap_or(self.string_mode)
+    }
+
+    // Configuration of the output stream of the field at the JSON path, if it deviates from its type
+    pub fn get_field_config(&self, path: &str) -> Option<StreamConfig> {
+        self.field_configs.get(path).copied()
+    }
 }
 
 fn validate_project_name(project_name: &str) -> Result<String, GeneratorError> {
@@ -102,5 +182,25 @@ fn namespace_from_project_name(project_name: &str) -> (String, String) {
 pub enum GeneratorError {
     AnalyzerError(analyzer::AnalyzerError),
     InvalidProjectName,
+    InvalidConfig(String),
+    InvalidTemplate(String),
+    InvalidSlice(String),
+    InvalidType(String),
     JsonError(json::JsonError),
+    TransferError(types::physical_stream::TransferError),
+}
+
+impl std::fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeneratorError::AnalyzerError(error) => write!(f, "analysis failed: {}", error),
+            GeneratorError::InvalidProjectName => write!(f, "invalid project name"),
+            GeneratorError::InvalidConfig(message) => write!(f, "invalid configuration: {}", message),
+            GeneratorError::InvalidTemplate(message) => write!(f, "invalid template: {}", message),
+            GeneratorError::InvalidSlice(message) => write!(f, "invalid array slice: {}", message),
+            GeneratorError::InvalidType(message) => write!(f, "invalid type: {}", message),
+            GeneratorError::JsonError(error) => write!(f, "invalid JSON: {}", error),
+            GeneratorError::TransferError(error) => write!(f, "invalid transfers: {}", error),
+        }
+    }
 }
\ No newline at end of file
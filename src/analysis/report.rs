@@ -0,0 +1,445 @@
+use std::{fs::File, io::Write};
+
+use json::JsonValue;
+
+use super::{Generator, GeneratorError, GeneratorParams, types::{TilStreamingInterface, physical_stream::PhysicalStream}, analyzer::file_manager::TemplateType};
+
+/**********************************************************************************
+ * Rough resource and timing estimation of the generated components, meant to     *
+ * compare schema designs and EPC choices before running synthesis.               *
+ * The figures are first order models for a 6-input LUT FPGA.                     *
+ **********************************************************************************/
+
+// Delay of a single logic level including routing
+const NS_PER_LOGIC_LEVEL: f64 = 0.45;
+// Clock to output and setup time of the registers
+const NS_REGISTER_OVERHEAD: f64 = 1.0;
+
+pub struct ComponentEstimate {
+    name: String,
+    kind: &'static str,
+    epc: usize,
+    luts: usize,
+    ffs: usize,
+    logic_levels: usize,
+    stream_bits: usize,
+    matcher_states: Option<usize>,
+}
+
+impl ComponentEstimate {
+    fn fmax_mhz(&self) -> f64 {
+        fmax_mhz(self.logic_levels)
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut value = json::object! {
+            name: self.name.clone(),
+            kind: self.kind,
+            epc: self.epc,
+            luts: self.luts,
+            ffs: self.ffs,
+            logic_levels: self.logic_levels,
+            fmax_mhz: self.fmax_mhz().round(),
+            stream_bits: self.stream_bits,
+        };
+
+        if let Some(states) = self.matcher_states {
+            value["matcher_states"] = states.into();
+        }
+
+        value
+    }
+}
+
+fn fmax_mhz(logic_levels: usize) -> f64 {
+    1000.0 / (NS_REGISTER_OVERHEAD + NS_PER_LOGIC_LEVEL * logic_levels as f64)
+}
+
+fn log2ceil(value: usize) -> usize {
+    let mut bits = 0;
+    while (1 << bits) < value {
+        bits += 1;
+    }
+
+    bits
+}
+
+// Number of states of the position automaton of a regex without its initial state, one for every
+// character, escape, character class or any character. Bounded repetitions are unrolled.
+fn regex_states(regex: &str) -> usize {
+    let chars: Vec<char> = regex.chars().collect();
+    // States in the open groups, the last one is the innermost
+    let mut groups = vec![0];
+    // States of the last atom, which a repetition applies to
+    let mut last_atom = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let atom = match chars[i] {
+            '\\' => {
+                i += 1;
+                1
+            },
+            '[' => {
+                // A ] directly after the opening bracket (and negation) is part of the class
+                i += 1;
+                if chars.get(i) == Some(&'^') {
+                    i += 1;
+                }
+                if chars.get(i) == Some(&']') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                1
+            },
+            '(' => {
+                // Non-capturing groups are the same as capturing ones
+                if chars.get(i + 1) == Some(&'?') && chars.get(i + 2) == Some(&':') {
+                    i += 2;
+                }
+                groups.push(0);
+                i += 1;
+                continue;
+            },
+            ')' => groups.pop().unwrap_or(0),
+            '{' => {
+                let end = chars[i..].iter().position(|c| *c == '}').map_or(chars.len(), |end| i + end);
+                let bounds: String = chars[i + 1..end].iter().collect();
+                let max = bounds.rsplit(',').next().and_then(|max| max.trim().parse::<usize>().ok()).unwrap_or(1);
+                i = end;
+
+                // The atom was already counted once
+                if let Some(group) = groups.last_mut() {
+                    *group += last_atom * max.saturating_sub(1);
+                }
+                i += 1;
+                continue;
+            },
+            '|' | '*' | '+' | '?' | '^' | '$' => {
+                i += 1;
+                continue;
+            },
+            _ => 1,
+        };
+
+        if groups.is_empty() {
+            groups.push(0);
+        }
+        if let Some(group) = groups.last_mut() {
+            *group += atom;
+        }
+        last_atom = atom;
+        i += 1;
+    }
+
+    groups.iter().sum()
+}
+
+// Estimate the resources of a single component from its template and generics
+fn estimate_component(name: &str, template_type: &TemplateType, interface: &TilStreamingInterface, gen_params: &GeneratorParams) -> ComponentEstimate {
+    let generic = |name: &str| interface.get_generic_value(name);
+
+    let epc = generic("EPC").or_else(|| generic("BPC")).or_else(|| generic("EPC_OUT")).unwrap_or(gen_params.epc);
+    let outer = generic("OUTER_NESTING_LEVEL").or_else(|| generic("NESTING_LEVEL")).unwrap_or(1);
+    let inner = generic("INNER_NESTING_LEVEL").unwrap_or(0);
+    let bw = gen_params.bit_width;
+
+    // Total width of the physical signals of all streams
+    let stream_bits = interface.get_streams().iter()
+        .map(|stream| PhysicalStream::new(stream.get_type(), gen_params).get_signals().iter().map(|(_, width)| width).sum::<usize>())
+        .sum();
+
+    let mut matcher_states = None;
+
+    let (kind, luts, ffs, logic_levels) = match template_type {
+        TemplateType::Record => (
+            "record_parser",
+            epc * (3 * bw + 4 * (outer + inner + 2)),
+            2 * epc * (bw + outer + 3) + 2 * (inner + 1),
+            3 + log2ceil(epc),
+        ),
+        TemplateType::Array => {
+            let counter_bw = generic("ELEMENT_COUNTER_BW").unwrap_or(4);
+            (
+                "array_parser",
+                epc * (3 * bw + 4 * (outer + inner + 2)) + 2 * counter_bw,
+                2 * epc * (bw + outer + 3) + counter_bw + inner + 1,
+                3 + log2ceil(epc),
+            )
+        },
+        TemplateType::Key => {
+            // Delay compensation buffer of 5 entries, map keys also hold the received, closed and emitted key name
+            let buff_width = epc * (2 + bw + outer + 1);
+            let key_name_bits = 8 * generic("KEY_NAME_LENGTH").unwrap_or(0);
+            (
+                "key_parser",
+                epc * (2 * bw + 6 * (outer + 1)) + 20 + key_name_bits,
+                5 * buff_width + epc * (bw + outer + 3) + 3 * key_name_bits,
+                4 + log2ceil(epc),
+            )
+        },
+        TemplateType::Int => {
+            // Reverse double dabble over the pipeline stages, the BCD register is shifted out in bcd_width
+            // iterations split evenly over the stages. An iteration corrects all digits in parallel and a
+            // 4 bit digit correction fits in a single LUT level.
+            let bitwidth = generic("BITWIDTH").unwrap_or(gen_params.int_width);
+            let stages = generic("PIPELINE_STAGES").unwrap_or(1).max(1);
+            let bcd_width = bitwidth + bitwidth.saturating_sub(4) / 3;
+            (
+                "int_parser",
+                bitwidth * bcd_width / 4 + 2 * epc * bw,
+                (bcd_width + bitwidth) * (stages + 1) + outer,
+                bcd_width.div_ceil(stages) + log2ceil(epc),
+            )
+        },
+        TemplateType::Bool => (
+            "bool_parser",
+            8 * epc + 16,
+            8 + outer,
+            2 + log2ceil(epc),
+        ),
+        TemplateType::String => (
+            "string_parser",
+            epc * (bw + 2 * (outer + 1)) + 10,
+            epc * (bw + outer + 2),
+            2 + log2ceil(epc),
+        ),
+        TemplateType::DecodedString => {
+            // Escape decoding and a pending queue of output bytes, the fixed length and length
+            // prefixed modes buffer the whole string
+            let length = generic("LENGTH").unwrap_or(0);
+            (
+                "string_decoder",
+                epc * (bw + 2 * (outer + 1)) + 16 * epc + 80 + 12 * length,
+                epc * (bw + outer + 2) + 9 * (epc + 8) + 8 * length + 40,
+                6 + log2ceil(epc) + log2ceil(length.max(1)),
+            )
+        },
+        TemplateType::Enum => {
+            // Marks the string characters for the matcher and turns its match bits into a code
+            let values = generic("NUM_VALUES").unwrap_or(1);
+            let code_width = generic("CODE_WIDTH").unwrap_or(1);
+            let buff_width = epc * (outer + 1);
+            (
+                "enum_encoder",
+                epc * (bw + 2 * (outer + 1) + values * code_width) + 10,
+                5 * buff_width + epc * (bw + outer + code_width + 4),
+                3 + log2ceil(epc) + log2ceil(values),
+            )
+        },
+        TemplateType::Filter => match (generic("BITWIDTH"), generic("EPC")) {
+            // Comparison of the value with the constant
+            (Some(bitwidth), _) => (
+                "filter",
+                bitwidth + 4,
+                2,
+                2 + log2ceil(bitwidth),
+            ),
+            // Match bits of the strings waiting in a delay compensation buffer of 5 entries
+            (None, Some(epc)) => {
+                let buff_width = epc * (outer + 1);
+                (
+                    "filter",
+                    epc * (outer + 4) + 6,
+                    5 * buff_width + epc * (outer + 2) + 4,
+                    3 + log2ceil(epc),
+                )
+            },
+            (None, None) => (
+                "filter",
+                6,
+                2,
+                2,
+            ),
+        },
+        TemplateType::Slicer => {
+            // Element index and stride counters
+            let index_bw = generic("INDEX_BW").unwrap_or(4);
+            (
+                "array_slicer",
+                epc * (3 * index_bw + outer + 4) + 4,
+                epc * (2 * bw + 2 * (outer + 2) + 2) + 2 * index_bw,
+                2 + log2ceil(epc) + log2ceil(index_bw),
+            )
+        },
+        TemplateType::Presence => {
+            // Closing lanes and lasts waiting in a delay compensation buffer of 5 entries, one bit per key
+            // and a sync over the match results of the key parsers
+            let keys = generic("NUM_KEYS").unwrap_or(1);
+            let buff_width = epc * (outer + 1);
+            (
+                "key_presence",
+                epc * (2 * (outer + 1) + 2 * keys) + keys + 10,
+                5 * buff_width + epc * (outer + keys + 4) + keys,
+                3 + log2ceil(epc) + log2ceil(keys),
+            )
+        },
+        TemplateType::Assembler => {
+            // A register for every field and for the group which is emitted
+            let fields = interface.get_streams().len() - 1;
+            let group_bits = interface.get_streams().iter()
+                .find(|stream| stream.get_name() == "output")
+                .map_or(0, |stream| stream.get_type().get_type_params(gen_params).get_data_bits());
+            (
+                "record_assembler",
+                group_bits + fields * (outer + 3) + 4,
+                2 * group_bits + fields * (outer + 1) + outer + 2,
+                2 + log2ceil(fields),
+            )
+        },
+        TemplateType::Variant => {
+            // A register for every branch and the closing lanes with their tags waiting in a delay compensation buffer of 5 entries
+            let branches = interface.get_streams().iter().filter(|stream| stream.get_name().starts_with("branch_")).count();
+            let union_bits = interface.get_streams().iter()
+                .find(|stream| stream.get_name() == "output")
+                .map_or(0, |stream| stream.get_type().get_type_params(gen_params).get_data_bits());
+            let buff_width = epc * (1 + log2ceil(branches) + outer);
+            (
+                "variant_dispatcher",
+                epc * (bw + branches * (outer + 3) + 4) + union_bits + 10,
+                5 * buff_width + epc * (2 * bw + branches * (outer + 2)) + 2 * union_bits + outer + 4,
+                3 + log2ceil(epc) + log2ceil(branches),
+            )
+        },
+        TemplateType::WidthConverter => {
+            let epc_in = generic("EPC_IN").unwrap_or(epc);
+            let elem_width = bw + outer + 1;
+            (
+                "width_converter",
+                2 * elem_width * epc_in.max(epc) + 10,
+                elem_width * (epc_in + epc) + 8,
+                2 + log2ceil(epc_in.max(epc)),
+            )
+        },
+        TemplateType::Matcher(matchers) => {
+            let states = matchers.iter().map(|matcher| regex_states(matcher)).sum::<usize>() + 1;
+            matcher_states = Some(states);
+            (
+                "matcher",
+                epc * (2 * states + 8),
+                states + epc + 4,
+                2 + 2 * epc,
+            )
+        },
+    };
+
+    ComponentEstimate {
+        name: name.to_string(),
+        kind,
+        epc,
+        luts,
+        ffs,
+        logic_levels,
+        stream_bits,
+        matcher_states,
+    }
+}
+
+impl Generator {
+    pub fn estimate_resources(&self) -> Vec<ComponentEstimate> {
+        let (_, streamlets) = self.analyzer.get_definitions();
+
+        let entities = self.analyzer.get_file_manager().get_entities();
+
+        // Every instance is estimated with its own generics, entities are shared between instances
+        self.analyzer.get_instances().iter()
+            .filter_map(|instance| {
+                let (template_type, _) = entities.iter().find(|(_, name)| *name == instance.get_component_name())?;
+                let streamlet = streamlets.iter().find(|streamlet| streamlet.get_name() == instance.get_component_name())?;
+
+                let mut interface = TilStreamingInterface::default();
+                for generic in instance.get_generics() {
+                    interface.add_generic(generic.clone());
+                }
+                for stream in streamlet.get_streams().get_streams() {
+                    interface.add_til_stream(stream.clone());
+                }
+
+                let name = instance.get_instance_name().trim_end_matches("_inst");
+
+                Some(estimate_component(name, template_type, &interface, &self.gen_params))
+            })
+            .collect()
+    }
+
+    // Write the estimation report as report.json and report.md to the given directory
+    pub fn generate_report(&self, dir: &str) -> Result<(), GeneratorError> {
+        let estimates = self.estimate_resources();
+
+        let total_luts: usize = estimates.iter().map(|est| est.luts).sum();
+        let total_ffs: usize = estimates.iter().map(|est| est.ffs).sum();
+        let max_levels = estimates.iter().map(|est| est.logic_levels).max().unwrap_or(0);
+
+        // JSON report
+        let report = json::object! {
+            project: self.gen_params.project_name.clone(),
+            epc: self.gen_params.epc,
+            bit_width: self.gen_params.bit_width,
+            int_width: self.gen_params.int_width,
+            components: JsonValue::Array(estimates.iter().map(|est| est.to_json()).collect()),
+            total: {
+                components: estimates.len(),
+                luts: total_luts,
+                ffs: total_ffs,
+                logic_levels: max_levels,
+                fmax_mhz: fmax_mhz(max_levels).round(),
+            },
+        };
+
+        // Markdown report
+        let mut markdown = String::new();
+        markdown.push_str(&format!("# Resource estimate for {}\n\n", self.gen_params.project_name));
+        markdown.push_str(&format!("EPC: {}, bit width: {}, int width: {}\n\n", self.gen_params.epc, self.gen_params.bit_width, self.gen_params.int_width));
+        markdown.push_str("| Component | Kind | EPC | LUTs | FFs | Logic levels | Fmax (MHz) | Stream bits | Matcher states |\n");
+        markdown.push_str("|---|---|---|---|---|---|---|---|---|\n");
+
+        for est in &estimates {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {:.0} | {} | {} |\n",
+                est.name, est.kind, est.epc, est.luts, est.ffs, est.logic_levels, est.fmax_mhz(), est.stream_bits,
+                est.matcher_states.map_or("-".to_string(), |states| states.to_string())
+            ));
+        }
+
+        markdown.push_str(&format!(
+            "| **Total** | | | **{}** | **{}** | **{}** | **{:.0}** | | |\n",
+            total_luts, total_ffs, max_levels, fmax_mhz(max_levels)
+        ));
+
+        std::fs::create_dir_all(dir).unwrap();
+
+        let mut file = File::create(format!("{}/report.json", dir)).unwrap();
+        file.write_all(report.pretty(4).as_bytes()).unwrap();
+
+        let mut file = File::create(format!("{}/report.md", dir)).unwrap();
+        file.write_all(markdown.as_bytes()).unwrap();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regex_states_count_symbols() {
+        assert_eq!(regex_states("voltage"), 7);
+        assert_eq!(regex_states("on|off"), 5);
+        assert_eq!(regex_states("[a-z]+_\\d*"), 3);
+        assert_eq!(regex_states("[]a]|."), 2);
+        assert_eq!(regex_states("(?:ab|c)?d"), 4);
+    }
+
+    #[test]
+    fn regex_states_unroll_repetitions() {
+        assert_eq!(regex_states("a{3}"), 3);
+        assert_eq!(regex_states("(ab){2,4}c"), 9);
+        assert_eq!(regex_states("x[0-9]{1,2}"), 3);
+    }
+}
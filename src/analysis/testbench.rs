@@ -0,0 +1,379 @@
+use std::{collections::HashMap, fs::File, io::Write};
+
+use json::JsonValue;
+
+use super::{Generator, GeneratorError, GeneratorParams, components::{JsonComponent, JsonComponentValue, JsonType, StringMode, Filter, FilterPredicate, VariantKind}, types::{TilSignal, physical_stream::PhysicalStream, logical_type::LogicalType}, analyzer::{type_manager::StreamType, file_manager::{matcher, template::{Template, TemplateContext}}}};
+
+/**********************************************************************************
+ * Set of functions to generate a cocotb testbench which pushes the analyzed      *
+ * samples through the generated design and checks the outputs                    *
+ **********************************************************************************/
+
+impl Generator {
+    pub fn generate_testbench(&mut self) -> Result<(), GeneratorError> {
+        let tb_dir = format!("{}/cocotb", self.gen_params.output_dir);
+        std::fs::create_dir_all(&tb_dir).unwrap();
+
+        let top_component = self.analyzer.assemble_top_component().map_err(GeneratorError::AnalyzerError)?;
+        let root = self.analyzer.get_root().map_err(GeneratorError::AnalyzerError)?;
+
+        // Parameters of every top-level stream so the drivers and monitors know the port layout
+        let mut kinds = HashMap::new();
+        collect_kinds(root, &self.gen_params, &mut kinds);
+        let mut groups = HashMap::new();
+        collect_groups(root, &self.gen_params, &mut groups);
+
+        let mut outputs = JsonValue::new_object();
+        for stream in top_component.get_streams().get_output_streams() {
+            let physical = PhysicalStream::new(stream.get_type(), &self.gen_params);
+            let kind = kinds.get(stream.get_name()).copied().unwrap_or_else(|| stream_kind(stream.get_type().get_stream_type()));
+            outputs[stream.get_name()] = stream_to_json(&physical, kind);
+
+            if let Some(fields) = groups.remove(stream.get_name()) {
+                outputs[stream.get_name()]["fields"] = fields;
+            }
+        }
+
+        let input_stream = PhysicalStream::new(&self.analyzer.get_input_stream_type(), &self.gen_params);
+        let streams = json::object! {
+            input: stream_to_json(&input_stream, stream_kind(StreamType::Json)),
+            outputs: outputs,
+        };
+
+        // Expected values of every output over all samples
+        let mut expected: HashMap<String, Vec<JsonValue>> = HashMap::new();
+        for sample in &self.samples {
+            collect_expected(root, sample, &self.gen_params, &mut expected);
+        }
+
+        let mut expected_json = JsonValue::new_object();
+        for stream in top_component.get_streams().get_output_streams() {
+            let values = expected.remove(stream.get_name()).unwrap_or_default();
+            expected_json[stream.get_name()] = JsonValue::Array(values);
+        }
+
+        // Serialize the samples into transfers on the input stream
+        let documents: Vec<String> = self.samples.iter().map(|sample| sample.dump()).collect();
+        let transfers = self.serialize_input(documents.join("\n").as_bytes())?;
+
+        // The transfers have to decode back into the documents the expected values are derived from
+        input_stream.deserialize_json(&transfers).map_err(GeneratorError::TransferError)?;
+
+        let mut transfer_lines = String::new();
+        for transfer in &transfers {
+            let ports: Vec<String> = transfer.to_port_vectors(&input_stream).into_iter().map(|(_, bits)| bits).collect();
+            transfer_lines.push_str(&format!("{}\n", ports.join(" ")));
+        }
+
+        // Makefile to run the testbench with GHDL
+        let template = Template::parse(include_str!("analyzer/file_manager/templates/cocotb/Makefile")).unwrap();
+        let mut context = TemplateContext::new();
+        context.insert("project_name", self.gen_params.project_name.as_str());
+        context.insert("top_entity", format!("{}_0_top_com", self.gen_params.comp_namespace));
+
+        let files = [
+            ("Makefile", template.render(&context).unwrap()),
+            ("tydi_stream.py", include_str!("analyzer/file_manager/templates/cocotb/tydi_stream.py").to_string()),
+            ("test_top.py", include_str!("analyzer/file_manager/templates/cocotb/test_top.py").to_string()),
+            ("streams.json", streams.pretty(4)),
+            ("expected.json", expected_json.pretty(4)),
+            ("input_transfers.txt", transfer_lines),
+        ];
+
+        for (file_name, text) in files {
+            let mut file = File::create(format!("{}/{}", tb_dir, file_name)).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        }
+
+        Ok(())
+    }
+}
+
+fn stream_kind(stream_type: StreamType) -> &'static str {
+    match stream_type {
+        StreamType::Int | StreamType::Counter | StreamType::Presence => "int",
+        StreamType::Bool => "bool",
+        _ => "json",
+    }
+}
+
+fn stream_to_json(stream: &PhysicalStream, kind: &str) -> JsonValue {
+    json::object! {
+        kind: kind,
+        data_bits: stream.get_data_bits(),
+        lanes: stream.get_lanes(),
+        dimensionality: stream.get_dimensionality(),
+        complexity: stream.get_complexity(),
+    }
+}
+
+// Name of the top-level output stream a component drives, if any
+fn output_name(component: &JsonComponent, gen_params: &GeneratorParams) -> Option<String> {
+    component.get_generatable().get_outgoing_signals(gen_params).into_iter().find_map(|signal| match signal {
+        TilSignal::Output { dest_stream_name, .. } => Some(dest_stream_name),
+        _ => None,
+    })
+}
+
+// Decoded strings are checked per string mode instead of per stream type
+fn collect_kinds(component: &JsonComponent, gen_params: &GeneratorParams, kinds: &mut HashMap<String, &'static str>) {
+    if let JsonComponent::Enum(_) = component {
+        if let Some(name) = output_name(component, gen_params) {
+            kinds.insert(name, "enum");
+        }
+    }
+
+    if let JsonComponent::Filter(filter) = component {
+        kinds.insert(filter.get_selected_name(), "bool");
+    }
+
+    if let JsonComponent::Assembler(assembler) = component {
+        kinds.insert(assembler.get_output_name().to_string(), "group");
+    }
+
+    if let JsonComponent::Variant(variant) = component {
+        kinds.insert(variant.get_output_name().to_string(), "union");
+    }
+
+    if let JsonComponent::Key(key) = component {
+        if key.is_map_key() {
+            kinds.insert(key.get_key_name_output_name(), "fixed_string");
+        }
+    }
+
+    if let JsonComponent::Value(val) = component {
+        let kind = match val.get_string_mode() {
+            StringMode::Raw => None,
+            StringMode::Decoded => Some("string"),
+            StringMode::Fixed(_) => Some("fixed_string"),
+            StringMode::LengthPrefixed(_) => Some("prefixed_string"),
+        };
+
+        if let (Some(name), Some(kind)) = (output_name(component, gen_params), kind) {
+            kinds.insert(name, kind);
+        }
+    }
+
+    for child in component.get_children() {
+        collect_kinds(&child, gen_params, kinds);
+    }
+}
+
+// Fields of the group outputs in order from the lowest bits, and of the union outputs in order of their tag
+fn collect_groups(component: &JsonComponent, gen_params: &GeneratorParams, groups: &mut HashMap<String, JsonValue>) {
+    if let JsonComponent::Assembler(assembler) = component {
+        let group = assembler.get_group(gen_params);
+
+        let mut fields = JsonValue::new_array();
+        for ((name, field), (_, _, value)) in group.get_fields().iter().zip(assembler.get_fields()) {
+            let kind = match value.get_data_type() {
+                JsonType::Boolean => "bool",
+                _ => "int",
+            };
+
+            fields.push(json::object! { name: name.as_str(), kind: kind, bits: field.get_bits() }).unwrap();
+        }
+
+        groups.insert(assembler.get_output_name().to_string(), fields);
+    }
+
+    if let JsonComponent::Variant(variant) = component {
+        let union = variant.get_union(gen_params);
+
+        let mut fields = JsonValue::new_array();
+        for (name, field) in union.get_fields() {
+            let kind = match field.resolve() {
+                LogicalType::Bits(1) => "bool",
+                LogicalType::Bits(_) => "int",
+                _ => "null",
+            };
+
+            fields.push(json::object! { name: name.as_str(), kind: kind, bits: field.get_bits() }).unwrap();
+        }
+
+        groups.insert(variant.get_output_name().to_string(), fields);
+    }
+
+    for child in component.get_children() {
+        collect_groups(&child, gen_params, groups);
+    }
+}
+
+// Expected value of a decoded string, truncated to the length of the mode
+fn decoded_string(text: &str, string_mode: StringMode) -> JsonValue {
+    let bytes = text.as_bytes();
+
+    let text = match string_mode {
+        StringMode::Fixed(length) => String::from_utf8_lossy(&bytes[..bytes.len().min(length)]).trim_end_matches('\0').to_string(),
+        StringMode::LengthPrefixed(length) => String::from_utf8_lossy(&bytes[..bytes.len().min(length)]).to_string(),
+        _ => text.to_string(),
+    };
+
+    JsonValue::String(text)
+}
+
+// Expected selected bit of a value, as the hardware sees it
+fn filter_selected(filter: &Filter, value: &JsonValue, gen_params: &GeneratorParams) -> Option<bool> {
+    match (filter.get_predicate(), value) {
+        // The int parser emits the value in the integer width
+        (FilterPredicate::Compare(op, constant), JsonValue::Number(_)) => value.as_i64().map(|number| {
+            let width = gen_params.int_width.min(64);
+            let mask = if width == 64 { u64::MAX } else { (1 << width) - 1 };
+            op.compare(number as u64 & mask, *constant)
+        }),
+        (FilterPredicate::Bool(expected), JsonValue::Boolean(b)) => Some(b == expected),
+        // The regex sees the raw characters between the quotes
+        (FilterPredicate::Regex(regex), JsonValue::Short(_) | JsonValue::String(_)) => {
+            let raw = value.dump();
+            Some(matcher::regex_matches(regex, &raw[1..raw.len() - 1]).unwrap_or(false))
+        },
+        _ => None,
+    }
+}
+
+// Walk the component tree along the sample and collect the values each output produces
+fn collect_expected(component: &JsonComponent, value: &JsonValue, gen_params: &GeneratorParams, expected: &mut HashMap<String, Vec<JsonValue>>) {
+    match component {
+        JsonComponent::Value(val) => {
+            let expected_value = match (val.get_data_type(), value) {
+                (JsonType::Integer, JsonValue::Number(_)) => value.as_i64().map(JsonValue::from),
+                (JsonType::Boolean, JsonValue::Boolean(b)) => Some(JsonValue::Boolean(*b)),
+                // Strings are passed through as raw JSON unless they are decoded
+                (JsonType::String, JsonValue::Short(_) | JsonValue::String(_)) => match val.get_string_mode() {
+                    StringMode::Raw => Some(JsonValue::String(value.dump())),
+                    string_mode => value.as_str().map(|text| decoded_string(text, string_mode)),
+                },
+                _ => None,
+            };
+
+            // A filter passes the value through to the output
+            let name = match val.get_filter() {
+                Some(filter) => Some(filter.get_output_name()),
+                None => output_name(component, gen_params),
+            };
+
+            if let (Some(name), Some(expected_value)) = (name, expected_value) {
+                expected.entry(name).or_default().push(expected_value);
+            }
+
+            if let Some(filter) = val.get_filter() {
+                if let Some(selected) = filter_selected(filter, value, gen_params) {
+                    expected.entry(filter.get_selected_name()).or_default().push(JsonValue::Boolean(selected));
+                }
+            }
+        },
+        JsonComponent::Array(array) => {
+            if array.get_counters().count {
+                expected.entry(array.get_count_name()).or_default().push(JsonValue::from(value.len()));
+            }
+
+            if array.get_counters().index {
+                expected.entry(array.get_index_name()).or_default().extend((0..value.len()).map(JsonValue::from));
+            }
+
+            // Elements outside of the slice are dropped
+            let elements = value.members().enumerate()
+                .filter(|(index, _)| array.get_slicer().is_none_or(|slicer| slicer.get_slice().contains(*index)))
+                .map(|(_, element)| element);
+
+            for element in elements {
+                match array.get_value() {
+                    Some(child) => collect_expected(child, element, gen_params, expected),
+                    None => expected.entry(array.get_output_name()).or_default().push(JsonValue::String(element.dump())),
+                }
+            }
+        },
+        JsonComponent::Record(record) => {
+            // Bit for every key which matches one of the keys of the record
+            if let Some(presence) = record.get_presence() {
+                let mut bits = 0u64;
+                for (bit, pattern) in presence.get_keys().iter().enumerate() {
+                    let present = value.entries().any(|(name, _)| {
+                        let raw = JsonValue::from(name).dump();
+                        matcher::regex_matches(pattern, &raw[1..raw.len() - 1]).unwrap_or(false)
+                    });
+
+                    if present {
+                        bits |= 1 << bit;
+                    }
+                }
+
+                expected.entry(presence.get_output_name().to_string()).or_default().push(JsonValue::from(bits));
+            }
+
+            // Value of every assembled key, zero if the record does not have it
+            if let Some(assembler) = record.get_assembler() {
+                let mut group = JsonValue::new_object();
+                for (key, name, field) in assembler.get_fields() {
+                    let field_value = &value[key.as_str()];
+                    group[name.as_str()] = match field.get_data_type() {
+                        JsonType::Boolean => JsonValue::Boolean(field_value.as_bool().unwrap_or(false)),
+                        _ => JsonValue::from(field_value.as_i64().unwrap_or(0)),
+                    };
+                }
+
+                expected.entry(assembler.get_output_name().to_string()).or_default().push(group);
+            }
+
+            for key in record.get_keys() {
+                // A map key takes every key matching its pattern, in order of appearance
+                let elements: Vec<(&str, &JsonValue)> = if key.is_map_key() {
+                    value.entries().filter(|(name, _)| {
+                        let raw = JsonValue::from(*name).dump();
+                        matcher::regex_matches(key.get_key(), &raw[1..raw.len() - 1]).unwrap_or(false)
+                    }).collect()
+                } else if value.has_key(key.get_key()) {
+                    vec![(key.get_key(), &value[key.get_key()])]
+                } else {
+                    Vec::new()
+                };
+
+                for (name, element) in elements {
+                    if let Some(length) = key.get_key_name_length() {
+                        let raw = JsonValue::from(name).dump();
+                        let raw = &raw.as_bytes()[1..raw.len() - 1];
+                        let name = String::from_utf8_lossy(&raw[..raw.len().min(length)]).to_string();
+                        expected.entry(key.get_key_name_output_name()).or_default().push(JsonValue::String(name));
+                    }
+
+                    match key.get_value() {
+                        Some(child) => collect_expected(child, element, gen_params, expected),
+                        None => if let Some(name) = output_name(&JsonComponent::Key(key.clone()), gen_params) {
+                            expected.entry(name).or_default().push(JsonValue::String(element.dump()));
+                        },
+                    }
+                }
+            }
+        },
+        JsonComponent::Enum(encoder) => {
+            // Unknown values are expected as -1
+            if let (Some(name), Some(text)) = (output_name(component, gen_params), value.as_str()) {
+                let code = encoder.get_code(text).map_or(-1, |code| code as i64);
+                expected.entry(name).or_default().push(JsonValue::from(code));
+            }
+        },
+        JsonComponent::Variant(variant) => {
+            let kind = match VariantKind::of(value) {
+                Some(kind) => kind,
+                None => return,
+            };
+
+            // Values of a kind without a parser are not dispatched
+            let child = match variant.get_branches().iter().find(|(branch_kind, _)| *branch_kind == kind) {
+                Some((_, child)) => child,
+                None => return,
+            };
+
+            // Integers and booleans are emitted with their tag, the other kinds have their own outputs
+            let variant_value = match variant.get_routed_value(kind) {
+                Some(_) if kind == VariantKind::Boolean => JsonValue::Boolean(value.as_bool().unwrap_or(false)),
+                Some(_) => JsonValue::from(value.as_i64().unwrap_or(0)),
+                None => JsonValue::Null,
+            };
+            expected.entry(variant.get_output_name().to_string()).or_default().push(json::object! { tag: kind.get_name(), value: variant_value });
+
+            collect_expected(child, value, gen_params, expected);
+        },
+        JsonComponent::Key(_) | JsonComponent::Matcher(_) | JsonComponent::Filter(_) | JsonComponent::Slicer(_) | JsonComponent::Presence(_) | JsonComponent::Assembler(_) => {},
+    }
+}
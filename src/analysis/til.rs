@@ -26,6 +26,11 @@ impl Generator {
 
         let (type_defs, stream_defs) = self.analyzer.get_definitions();
 
+        // Named element types, the stream types refer to them
+        for logical_type in self.analyzer.get_logical_types() {
+            til.push_str(&logical_type.get_type_def_string().unwrap());
+        }
+
         for type_def in type_defs {
             til.push_str(&type_def.get_type_def_string(&self.gen_params));
         }
@@ -0,0 +1,146 @@
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::analyzer::type_manager::StreamParams;
+
+/**********************************************************************************
+ * Tydi logical types of the elements of a stream. Composite types are declared   *
+ * once with a name in the TIL and referred to by that name.                      *
+ **********************************************************************************/
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LogicalType {
+    Null,
+    Bits(usize),
+    // Fields which are all present, the first field is in the lowest bits
+    Group(Vec<(String, LogicalType)>),
+    // One of the fields, selected by a tag in the lowest bits
+    Union(Vec<(String, LogicalType)>),
+    // Child stream, it has its own physical stream so it takes no bits of the parent
+    Stream(Box<StreamParams>),
+    // Type declared with a name
+    Named(String, Box<LogicalType>),
+}
+
+impl LogicalType {
+    pub fn named(name: &str, logical_type: LogicalType) -> LogicalType {
+        LogicalType::Named(name.to_string(), Box::new(logical_type))
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        match self {
+            LogicalType::Named(name, _) => Some(name),
+            _ => None,
+        }
+    }
+
+    // Type without the name it is declared with
+    pub fn resolve(&self) -> &LogicalType {
+        match self {
+            LogicalType::Named(_, logical_type) => logical_type.resolve(),
+            logical_type => logical_type,
+        }
+    }
+
+    // Fields of a group or union
+    pub fn get_fields(&self) -> &[(String, LogicalType)] {
+        match self.resolve() {
+            LogicalType::Group(fields) | LogicalType::Union(fields) => fields,
+            _ => &[],
+        }
+    }
+
+    // Width of the tag selecting the field of a union
+    pub fn get_tag_bits(&self) -> usize {
+        match self.resolve() {
+            LogicalType::Union(fields) => {
+                let mut bits = 0;
+                while (1 << bits) < fields.len() {
+                    bits += 1;
+                }
+
+                bits
+            },
+            _ => 0,
+        }
+    }
+
+    // Width of the data signal of the physical stream carrying the type
+    pub fn get_bits(&self) -> usize {
+        match self.resolve() {
+            LogicalType::Null | LogicalType::Stream(_) => 0,
+            LogicalType::Bits(bits) => *bits,
+            LogicalType::Group(fields) => fields.iter().map(|(_, field)| field.get_bits()).sum(),
+            // The fields share the bits above the tag
+            LogicalType::Union(fields) => self.get_tag_bits() + fields.iter().map(|(_, field)| field.get_bits()).max().unwrap_or(0),
+            LogicalType::Named(..) => unreachable!(),
+        }
+    }
+
+    // Named types used by the type, the ones it depends on first
+    pub fn get_named_types(&self) -> Vec<LogicalType> {
+        let mut named_types = Vec::new();
+
+        match self {
+            LogicalType::Group(fields) | LogicalType::Union(fields) => {
+                for (_, field) in fields {
+                    named_types.extend(field.get_named_types());
+                }
+            },
+            LogicalType::Stream(params) => named_types.extend(params.data.get_named_types()),
+            LogicalType::Named(_, logical_type) => {
+                named_types.extend(logical_type.get_named_types());
+                named_types.push(self.clone());
+            },
+            LogicalType::Null | LogicalType::Bits(_) => {},
+        }
+
+        named_types
+    }
+
+    // Declaration of a named type, the fields of a composite type are put on separate lines
+    pub fn get_type_def_string(&self) -> Option<String> {
+        let (name, logical_type) = match self {
+            LogicalType::Named(name, logical_type) => (name, logical_type),
+            _ => return None,
+        };
+
+        let def = match logical_type.as_ref() {
+            LogicalType::Group(fields) | LogicalType::Union(fields) => {
+                let kind = if let LogicalType::Group(_) = logical_type.as_ref() { "Group" } else { "Union" };
+
+                let mut def = format!("{} (\n", kind);
+                for (field_name, field) in fields {
+                    def.push_str(&format!("    {}: {},\n", field_name, field));
+                }
+                def.push(')');
+
+                def
+            },
+            LogicalType::Stream(params) => params.to_string(),
+            logical_type => logical_type.to_string(),
+        };
+
+        Some(format!("type {} = {};\n\n", name, def))
+    }
+}
+
+impl Display for LogicalType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogicalType::Null => write!(f, "Null"),
+            LogicalType::Bits(bits) => write!(f, "Bits({})", bits),
+            LogicalType::Group(fields) | LogicalType::Union(fields) => {
+                let kind = if let LogicalType::Group(_) = self { "Group" } else { "Union" };
+                let fields: Vec<String> = fields.iter().map(|(name, field)| format!("{}: {}", name, field)).collect();
+                write!(f, "{}({})", kind, fields.join(", "))
+            },
+            LogicalType::Stream(params) => write!(
+                f,
+                "Stream(data: {}, throughput: {}, dimensionality: {}, synchronicity: {:?}, complexity: {})",
+                params.data, params.throughput, params.dimensionality, params.synchronicity, params.complexity
+            ),
+            // Declared separately, referred to by its name
+            LogicalType::Named(name, _) => write!(f, "{}", name),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use self::{streaming_interface::{TilStream, Generic}, til_streamlet::TilImplementationType};
 
 pub mod stream_types;
+pub mod logical_type;
 pub mod signals;
 pub mod streaming_interface;
 pub mod til_streamlet;
+pub mod physical_stream;
 
 #[derive(Clone)]
 pub struct TilStreamlet {
@@ -0,0 +1,458 @@
+use std::fmt::{Display, Formatter};
+
+use crate::analysis::{GeneratorParams, analyzer::type_manager::Dimensionality};
+
+use super::stream_types::StreamTypeDecl;
+
+/**********************************************************************************
+ * Conversion between element sequences and Tydi physical stream transfers.       *
+ * The stream parameters are taken from the type manager so the transfers match   *
+ * the ports of the generated hardware exactly.                                   *
+ **********************************************************************************/
+
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalStream {
+    data_bits: usize,
+    lanes: usize,
+    dimensionality: usize,
+    complexity: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transfer {
+    // Data of every lane, lane 0 first
+    pub data: Vec<u64>,
+    // Last bits of every lane (complexity >= 8) or of the whole transfer (complexity < 8)
+    pub last: Vec<u64>,
+    pub stai: usize,
+    pub endi: usize,
+    pub strb: Vec<bool>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransferError {
+    ElementTooWide(u64),
+    LaneCountMismatch,
+    // Start and end index which are not an ordered pair of lanes
+    InvalidIndices(usize, usize),
+    UnterminatedSequence,
+}
+
+impl Display for TransferError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferError::ElementTooWide(element) => write!(f, "element {:#x} does not fit in the data of a lane", element),
+            TransferError::LaneCountMismatch => write!(f, "transfer does not have a signal entry for every lane"),
+            TransferError::InvalidIndices(stai, endi) => write!(f, "stai {} and endi {} do not select lanes of the transfer", stai, endi),
+            TransferError::UnterminatedSequence => write!(f, "transfers end in the middle of a sequence"),
+        }
+    }
+}
+
+impl PhysicalStream {
+    pub fn new(stream_type: &StreamTypeDecl, gen_params: &GeneratorParams) -> PhysicalStream {
+        let params = stream_type.get_type_params(gen_params);
+
+        // Generic types take their dimensionality from the declaration
+        let dimensionality = match params.dimensionality {
+            Dimensionality::Fixed(dim) => dim,
+            Dimensionality::Generic => stream_type.get_stream_dim().as_ref().map_or(0, |dim| dim.get_true_value()),
+        };
+
+        PhysicalStream {
+            data_bits: params.get_data_bits(),
+            lanes: params.throughput,
+            dimensionality,
+            complexity: params.complexity,
+        }
+    }
+
+    pub fn get_data_bits(&self) -> usize {
+        self.data_bits
+    }
+
+    pub fn get_lanes(&self) -> usize {
+        self.lanes
+    }
+
+    pub fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    pub fn get_complexity(&self) -> u8 {
+        self.complexity
+    }
+
+    // Width of the index signals (stai/endi)
+    pub fn get_index_bits(&self) -> usize {
+        let mut bits = 0;
+        while (1 << bits) < self.lanes {
+            bits += 1;
+        }
+
+        bits
+    }
+
+    // Signals of the physical stream and their widths, following the signal omission rules of Tydi
+    pub fn get_signals(&self) -> Vec<(&'static str, usize)> {
+        let lane_count = self.lanes;
+        let dim = self.dimensionality;
+        let complexity = self.complexity;
+
+        let mut signals = vec![("valid", 1), ("ready", 1)];
+
+        if self.data_bits > 0 {
+            signals.push(("data", self.data_bits * lane_count));
+        }
+        if dim > 0 {
+            signals.push(("last", if self.has_lane_last() { dim * lane_count } else { dim }));
+        }
+        if complexity >= 6 && lane_count > 1 {
+            signals.push(("stai", self.get_index_bits()));
+        }
+        if (complexity >= 5 || dim > 0) && lane_count > 1 {
+            signals.push(("endi", self.get_index_bits()));
+        }
+        if complexity >= 7 || dim > 0 {
+            signals.push(("strb", lane_count));
+        }
+
+        signals
+    }
+
+    // Whether the last bits are transferred per lane
+    pub fn has_lane_last(&self) -> bool {
+        self.complexity >= 8
+    }
+
+    /// Serialize a buffer of (whitespace separated) JSON documents
+    pub fn serialize_json(&self, buffer: &[u8]) -> Result<Vec<Transfer>, TransferError> {
+        let documents: Vec<Vec<u64>> = split_documents(buffer).iter()
+            .map(|doc| doc.iter().map(|&byte| byte as u64).collect())
+            .collect();
+
+        self.serialize(&documents)
+    }
+
+    /// Serialize a list of innermost sequences
+    ///
+    /// The innermost dimension closes every sequence, all outer dimensions are closed
+    /// together with the last sequence.
+    pub fn serialize(&self, sequences: &[Vec<u64>]) -> Result<Vec<Transfer>, TransferError> {
+        let mut transfers = Vec::new();
+        let mut current = self.empty_transfer();
+        let mut lane = 0;
+
+        for (seq_idx, sequence) in sequences.iter().enumerate() {
+            let last_bits = self.closing_bits(seq_idx + 1 == sequences.len());
+
+            // Empty sequences are transferred as a lane without strobe
+            if sequence.is_empty() {
+                self.place(&mut current, lane, None, last_bits);
+                lane += 1;
+            }
+
+            for (elem_idx, &element) in sequence.iter().enumerate() {
+                if self.data_bits < 64 && element >> self.data_bits != 0 {
+                    return Err(TransferError::ElementTooWide(element));
+                }
+
+                let closes = elem_idx + 1 == sequence.len();
+                self.place(&mut current, lane, Some(element), if closes { last_bits } else { 0 });
+                lane += 1;
+
+                // Lower complexities cannot continue a transfer after a last
+                if lane == self.lanes || (closes && !self.has_lane_last()) {
+                    transfers.push(std::mem::replace(&mut current, self.empty_transfer()));
+                    lane = 0;
+                }
+            }
+
+            if lane == self.lanes || (lane > 0 && !self.has_lane_last()) {
+                transfers.push(std::mem::replace(&mut current, self.empty_transfer()));
+                lane = 0;
+            }
+        }
+
+        if lane > 0 {
+            transfers.push(current);
+        }
+
+        Ok(transfers)
+    }
+
+    /// Deserialize transfers back into JSON documents
+    pub fn deserialize_json(&self, transfers: &[Transfer]) -> Result<Vec<Vec<u8>>, TransferError> {
+        let sequences = self.deserialize(transfers)?;
+
+        sequences.iter()
+            .map(|seq| seq.iter().map(|&elem| u8::try_from(elem).map_err(|_| TransferError::ElementTooWide(elem))).collect())
+            .collect()
+    }
+
+    /// Deserialize transfers into the innermost sequences
+    pub fn deserialize(&self, transfers: &[Transfer]) -> Result<Vec<Vec<u64>>, TransferError> {
+        let mut sequences = Vec::new();
+        let mut current = Vec::new();
+
+        let last_entries = if self.has_lane_last() { self.lanes } else { 1 };
+
+        for transfer in transfers {
+            if transfer.data.len() != self.lanes || transfer.strb.len() != self.lanes || transfer.last.len() != last_entries {
+                return Err(TransferError::LaneCountMismatch);
+            }
+
+            if transfer.stai > transfer.endi || transfer.endi >= self.lanes {
+                return Err(TransferError::InvalidIndices(transfer.stai, transfer.endi));
+            }
+
+            for lane in transfer.stai..=transfer.endi {
+                if transfer.strb[lane] {
+                    current.push(transfer.data[lane]);
+                }
+
+                let last = if self.has_lane_last() {
+                    transfer.last[lane]
+                } else if lane == transfer.endi {
+                    transfer.last[0]
+                } else {
+                    0
+                };
+
+                if self.dimensionality > 0 && last & 1 == 1 {
+                    sequences.push(std::mem::take(&mut current));
+                }
+            }
+        }
+
+        // Streams without dimensions are a single sequence
+        if self.dimensionality == 0 {
+            sequences.push(current);
+        } else if !current.is_empty() {
+            return Err(TransferError::UnterminatedSequence);
+        }
+
+        Ok(sequences)
+    }
+
+    fn empty_transfer(&self) -> Transfer {
+        let last_entries = if self.has_lane_last() { self.lanes } else { 1 };
+
+        Transfer {
+            data: vec![0; self.lanes],
+            last: vec![0; last_entries],
+            stai: 0,
+            endi: 0,
+            strb: vec![false; self.lanes],
+        }
+    }
+
+    // Last bits asserted at the end of a sequence
+    fn closing_bits(&self, final_sequence: bool) -> u64 {
+        match (self.dimensionality, final_sequence) {
+            (0, _) => 0,
+            (_, false) => 1,
+            // Dimensions beyond the width of the last entry cannot be represented
+            (dim, true) => 1u64.checked_shl(dim as u32).map_or(u64::MAX, |bit| bit - 1),
+        }
+    }
+
+    fn place(&self, transfer: &mut Transfer, lane: usize, element: Option<u64>, last: u64) {
+        if let Some(element) = element {
+            transfer.data[lane] = element;
+            transfer.strb[lane] = true;
+        }
+
+        if self.has_lane_last() {
+            transfer.last[lane] = last;
+        } else {
+            transfer.last[0] |= last;
+        }
+
+        transfer.endi = lane;
+    }
+}
+
+impl Transfer {
+    // Render the transfer as the bit vectors of the VHDL ports (MSB first)
+    pub fn to_port_vectors(&self, stream: &PhysicalStream) -> Vec<(&'static str, String)> {
+        let mut data = String::new();
+        for lane in self.data.iter().rev() {
+            data.push_str(&to_bits(*lane, stream.get_data_bits()));
+        }
+
+        let mut last = String::new();
+        for lane in self.last.iter().rev() {
+            last.push_str(&to_bits(*lane, stream.get_dimensionality()));
+        }
+
+        let strb: String = self.strb.iter().rev().map(|&s| if s { '1' } else { '0' }).collect();
+
+        vec![
+            ("data", data),
+            ("last", last),
+            ("stai", to_bits(self.stai as u64, stream.get_index_bits())),
+            ("endi", to_bits(self.endi as u64, stream.get_index_bits())),
+            ("strb", strb),
+        ]
+    }
+}
+
+fn to_bits(value: u64, width: usize) -> String {
+    (0..width).rev().map(|bit| if bit < 64 && (value >> bit) & 1 == 1 { '1' } else { '0' }).collect()
+}
+
+// Split a buffer into its top-level JSON documents, dropping the whitespace between them
+pub fn split_documents(buffer: &[u8]) -> Vec<&[u8]> {
+    let mut documents = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = None;
+
+    for (idx, &byte) in buffer.iter().enumerate() {
+        if start.is_none() {
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+            start = Some(idx);
+        }
+
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else {
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        // A document ends when all brackets are closed and the next byte cannot continue it
+        let at_end = idx + 1 == buffer.len() || (buffer[idx + 1].is_ascii_whitespace() && !in_string);
+        if depth == 0 && !in_string && (matches!(byte, b'}' | b']') || at_end) {
+            documents.push(&buffer[start.unwrap()..=idx]);
+            start = None;
+        }
+    }
+
+    documents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(lanes: usize, dimensionality: usize, complexity: u8) -> PhysicalStream {
+        PhysicalStream { data_bits: 8, lanes, dimensionality, complexity }
+    }
+
+    fn sequences(texts: &[&str]) -> Vec<Vec<u64>> {
+        texts.iter().map(|text| text.bytes().map(u64::from).collect()).collect()
+    }
+
+    #[test]
+    fn round_trip_single_lane() {
+        let stream = stream(1, 1, 8);
+        let input = sequences(&["{}", "[1]"]);
+
+        let transfers = stream.serialize(&input).unwrap();
+        assert_eq!(transfers.len(), 5);
+        assert_eq!(stream.deserialize(&transfers).unwrap(), input);
+    }
+
+    #[test]
+    fn round_trip_multi_lane() {
+        for complexity in [2, 8] {
+            let stream = stream(4, 2, complexity);
+            let input = sequences(&["abcdef", "", "ghi"]);
+
+            let transfers = stream.serialize(&input).unwrap();
+            assert_eq!(stream.deserialize(&transfers).unwrap(), input, "complexity {}", complexity);
+        }
+    }
+
+    #[test]
+    fn round_trip_json_documents() {
+        let stream = stream(4, 1, 8);
+        let buffer = br#"{"a": [1, 2]} {"b": "x y"}"#;
+
+        let transfers = stream.serialize_json(buffer).unwrap();
+        let documents = stream.deserialize_json(&transfers).unwrap();
+        assert_eq!(documents, vec![br#"{"a": [1, 2]}"#.to_vec(), br#"{"b": "x y"}"#.to_vec()]);
+    }
+
+    #[test]
+    fn final_sequence_closes_all_dimensions() {
+        let stream = stream(4, 3, 8);
+        let transfers = stream.serialize(&sequences(&["ab", "c"])).unwrap();
+
+        // The inner dimension closes "ab", all dimensions close "c" in the next lane
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].last, vec![0, 0b001, 0b111, 0]);
+        assert_eq!(transfers[0].endi, 2);
+    }
+
+    #[test]
+    fn deserialize_between_stai_and_endi() {
+        let stream = stream(4, 1, 8);
+        let transfer = Transfer {
+            data: vec![b'x' as u64, b'a' as u64, b'b' as u64, b'y' as u64],
+            last: vec![1, 0, 1, 1],
+            stai: 1,
+            endi: 2,
+            strb: vec![true; 4],
+        };
+
+        assert_eq!(stream.deserialize(&[transfer]).unwrap(), sequences(&["ab"]));
+    }
+
+    #[test]
+    fn deserialize_rejects_malformed_transfers() {
+        let stream = stream(4, 1, 8);
+        let valid = stream.serialize(&sequences(&["ab"])).unwrap().remove(0);
+
+        let mut transfer = valid.clone();
+        transfer.endi = 4;
+        assert_eq!(stream.deserialize(&[transfer]), Err(TransferError::InvalidIndices(0, 4)));
+
+        let mut transfer = valid.clone();
+        transfer.stai = 2;
+        transfer.endi = 1;
+        assert_eq!(stream.deserialize(&[transfer]), Err(TransferError::InvalidIndices(2, 1)));
+
+        let mut transfer = valid.clone();
+        transfer.last.pop();
+        assert_eq!(stream.deserialize(&[transfer]), Err(TransferError::LaneCountMismatch));
+
+        let mut transfer = valid.clone();
+        transfer.last = vec![0; 4];
+        assert_eq!(stream.deserialize(&[transfer]), Err(TransferError::UnterminatedSequence));
+    }
+
+    #[test]
+    fn deserialize_json_rejects_wide_elements() {
+        let stream = PhysicalStream { data_bits: 16, lanes: 1, dimensionality: 1, complexity: 8 };
+        let transfers = stream.serialize(&[vec![0x100]]).unwrap();
+
+        assert_eq!(stream.deserialize_json(&transfers), Err(TransferError::ElementTooWide(0x100)));
+    }
+
+    #[test]
+    fn serialize_rejects_wide_elements() {
+        assert_eq!(stream(1, 1, 8).serialize(&[vec![0x100]]), Err(TransferError::ElementTooWide(0x100)));
+    }
+
+    #[test]
+    fn closing_bits_of_wide_dimensionality() {
+        assert_eq!(stream(1, 64, 8).closing_bits(true), u64::MAX);
+        assert_eq!(stream(1, 70, 8).closing_bits(true), u64::MAX);
+        assert_eq!(stream(1, 3, 8).closing_bits(false), 1);
+    }
+}
@@ -1,11 +1,16 @@
 use std::{fmt::{Formatter, Display}, cmp::Ordering};
 
-use crate::analysis::analyzer::type_manager::StreamType;
+use crate::analysis::{analyzer::type_manager::{StreamType, StreamConfig, StreamParams, Dimensionality}, GeneratorParams};
+
+use super::logical_type::LogicalType;
 
 #[derive(Clone)]
 pub struct StreamTypeDecl {
     stream_type: StreamType,
     stream_dim: Option<StreamDim>,
+    throughput: Option<usize>,
+    data: Option<LogicalType>,
+    config: Option<StreamConfig>,
 }
 
 impl StreamTypeDecl {
@@ -13,22 +18,118 @@ impl StreamTypeDecl {
         StreamTypeDecl {
             stream_type,
             stream_dim,
+            throughput: None,
+            data: None,
+            config: None,
+        }
+    }
+
+    // Use a specific throughput instead of the project wide elements per cycle
+    pub fn with_throughput(mut self, throughput: Option<usize>) -> StreamTypeDecl {
+        self.throughput = throughput;
+        self
+    }
+
+    // Use a specific element width instead of the one of the stream type
+    pub fn with_data_bits(mut self, data_bits: Option<usize>) -> StreamTypeDecl {
+        self.data = data_bits.map(LogicalType::Bits);
+        self
+    }
+
+    // Use a specific element type instead of the bits of the stream type, composite types should be named
+    // as the name tells the stream types apart
+    pub fn with_data_type(mut self, data: LogicalType) -> StreamTypeDecl {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn get_data_type(&self) -> Option<&LogicalType> {
+        self.data.as_ref()
+    }
+
+    // Use a specific configuration instead of the one of the stream type
+    pub fn with_config(mut self, config: Option<StreamConfig>) -> StreamTypeDecl {
+        self.config = config;
+        self
+    }
+
+    pub fn get_name(&self) -> String {
+        let mut name = self.stream_type.get_name().to_string();
+
+        if let Some(type_name) = self.data.as_ref().and_then(|data| data.get_name()) {
+            name.push_str(&format!("_{}", type_name));
+        }
+
+        if let Some(throughput) = self.throughput {
+            name.push_str(&format!("_E{}", throughput));
+        }
+
+        if let Some(data) = self.data.as_ref().filter(|data| data.get_name().is_none()) {
+            name.push_str(&format!("_B{}", data.get_bits()));
+        }
+
+        if let Some(config) = &self.config {
+            name.push_str(&format!("_{}", config.get_name_suffix()));
+        }
+
+        name
+    }
+
+    pub fn get_config(&self) -> &Option<StreamConfig> {
+        &self.config
+    }
+
+    pub fn get_type_params(&self, gen_params: &GeneratorParams) -> StreamParams {
+        let mut type_params = self.stream_type.get_type_params(gen_params);
+
+        if let Some(throughput) = self.throughput {
+            type_params.throughput = throughput;
+        }
+
+        if let Some(data) = &self.data {
+            type_params.data = data.clone();
         }
+
+        if let Some(config) = &self.config {
+            type_params.complexity = config.complexity;
+            type_params.synchronicity = config.synchronicity;
+        }
+
+        type_params
+    }
+
+    pub fn get_type_def_string(&self, gen_params: &GeneratorParams) -> String {
+        let type_params = self.get_type_params(gen_params);
+
+        let dim_str = match type_params.dimensionality {
+            Dimensionality::Fixed(_) => "".to_string(),
+            Dimensionality::Generic => format!("<{}: dimensionality = 2>", Dimensionality::Generic),
+        };
+
+        format!("type {}{} = {};\n\n", self.get_name(), dim_str, type_params)
     }
 
-    pub fn get_name(&self) -> &str {
-        self.stream_type.get_name()
+    pub fn get_stream_type(&self) -> StreamType {
+        self.stream_type
     }
 
     pub fn get_stream_dim(&self) -> &Option<StreamDim> {
         &self.stream_dim
     }
+
+    // Name with the dimensionality resolved, e.g. JSONStream<3>
+    pub fn get_resolved_string(&self) -> String {
+        match &self.stream_dim {
+            Some(dim) => format!("{}<{}>", self.get_name(), dim.get_true_value()),
+            None => self.get_name(),
+        }
+    }
 }
 
 impl Display for StreamTypeDecl {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut inst_str = String::new();
-        inst_str.push_str(self.get_name());
+        inst_str.push_str(&self.get_name());
 
         if let Some(dim) = &self.stream_dim {
             inst_str.push_str(&dim.to_string());
@@ -25,6 +25,10 @@ impl TilStreamingInterface {
         &self.generics
     }
 
+    pub fn get_generic_value(&self, name: &str) -> Option<usize> {
+        self.generics.iter().find(|generic| generic.get_name() == name).map(|generic| generic.get_type().get_value())
+    }
+
     pub fn get_streams(&self) -> &Vec<TilStream> {
         &self.streams
     }
@@ -71,6 +75,10 @@ impl TilStream {
     pub fn get_type(&self) -> &StreamTypeDecl {
         &self.stream_type
     }
+
+    pub fn get_direction(&self) -> TilStreamDirection {
+        self.direction
+    }
 }
 
 impl Display for TilStream {
@@ -132,6 +140,36 @@ pub enum GenericType {
     Dimensionality(usize),
 }
 
+impl GenericType {
+    pub fn get_value(&self) -> usize {
+        match self {
+            GenericType::Integer(value) => (*value).max(0) as usize,
+            GenericType::Natural(value) => *value,
+            GenericType::Positive(value) => *value,
+            GenericType::Dimensionality(value) => *value,
+        }
+    }
+
+    // Value as it is written in TIL
+    pub fn get_value_string(&self) -> String {
+        match self {
+            GenericType::Integer(value) => value.to_string(),
+            GenericType::Natural(value) => value.to_string(),
+            GenericType::Positive(value) => value.to_string(),
+            GenericType::Dimensionality(value) => value.to_string(),
+        }
+    }
+
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            GenericType::Integer(_) => "integer",
+            GenericType::Natural(_) => "natural",
+            GenericType::Positive(_) => "positive",
+            GenericType::Dimensionality(_) => "dimensionality",
+        }
+    }
+}
+
 impl Display for GenericType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
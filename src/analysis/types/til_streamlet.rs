@@ -2,7 +2,7 @@ use std::fmt::{Display, Formatter};
 
 use indoc::{formatdoc, writedoc};
 
-use super::{TilStreamlet, TilStreamingInterface, TilSignal};
+use super::{TilStreamlet, TilStreamingInterface, TilSignal, streaming_interface::Generic};
 
 impl TilStreamlet {
     pub fn new(name: &str) -> TilStreamlet {
@@ -17,6 +17,10 @@ impl TilStreamlet {
         &self.name
     }
 
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+
     pub fn set_implementation(&mut self, implementation: TilImplementationType) {
         self.implementation = Some(implementation);
     }
@@ -101,10 +105,8 @@ pub struct TilInlineImplementation {
 }
 
 impl TilInlineImplementation {
-    pub fn add_instance(&mut self, component_name: String) -> String{
-        let instance_name = format!("{}_inst", component_name);
-        self.instances.push(TilInstance::new(&component_name, &instance_name));
-        instance_name
+    pub fn add_instance(&mut self, instance: TilInstance) {
+        self.instances.push(instance);
     }
 
     pub fn add_signal(&mut self, signal: TilSignal) {
@@ -170,19 +172,40 @@ impl Display for TilImplementationType {
 pub struct TilInstance {
     component_name: String,
     instance_name: String,
+    // Generic values of the instance, streamlets are shared by instances with different values
+    generics: Vec<Generic>,
 }
 
 impl TilInstance {
-    pub fn new(component_name: &str, instance_name: &str) -> TilInstance {
+    pub fn new(component_name: &str, instance_name: &str, generics: Vec<Generic>) -> TilInstance {
         TilInstance {
             component_name: String::from(component_name),
             instance_name: String::from(instance_name),
+            generics,
         }
     }
+
+    pub fn get_generics(&self) -> &Vec<Generic> {
+        &self.generics
+    }
+
+    pub fn get_component_name(&self) -> &str {
+        &self.component_name
+    }
+
+    pub fn get_instance_name(&self) -> &str {
+        &self.instance_name
+    }
 }
 
 impl Display for TilInstance {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} = {};", self.instance_name, self.component_name)
+        if self.generics.is_empty() {
+            return write!(f, "{} = {};", self.instance_name, self.component_name);
+        }
+
+        let values: Vec<String> = self.generics.iter().map(|generic| generic.get_type().get_value_string()).collect();
+
+        write!(f, "{} = {}<{}>;", self.instance_name, self.component_name, values.join(", "))
     }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
-use super::components::{JsonComponent, JsonComponentValue};
+use std::{collections::HashMap, io::Write};
+
+use super::{components::{JsonComponent, JsonComponentValue}, types::{TilStreamlet, TilSignal, stream_types::StreamTypeDecl, til_streamlet::TilImplementationType}, analyzer::type_manager::StreamType};
 
 /**********************************************************************************
  * Implementation of how to rendering the component tree to a dot file            *
@@ -28,24 +30,47 @@ fn update_graph(component: &JsonComponent, parent_id: Option<usize>, graph: &mut
     }
 }
 
-// Generate the dot file
-pub fn generate_dot(root: &JsonComponent, output_path: &str) {
+// Create the output file and its directory
+fn create_output_file(output_path: &str) -> std::fs::File {
     // Separate output path into directory and file name
     let (dir, _) = output_path.split_at(output_path.rfind('/').unwrap_or(0));
 
     // Create the directory if it doesn't exist
-    std::fs::create_dir_all(dir).unwrap();
+    if !dir.is_empty() {
+        std::fs::create_dir_all(dir).unwrap();
+    }
 
     // Create the file
-    let mut file = std::fs::File::create(output_path).unwrap();
+    std::fs::File::create(output_path).unwrap()
+}
+
+// Diagram languages the component tree and netlist can be exported to
+#[derive(Clone, Copy, Debug)]
+pub enum DiagramFormat {
+    Dot,
+    Mermaid,
+    PlantUml,
+}
 
-    // Create a graph and add the JSON components recursively starting from the root
+// Create a graph and add the JSON components recursively starting from the root
+fn build_graph(root: &JsonComponent) -> Graph {
     let mut graph = Graph { nodes: Vec::new(), edges: Vec::new() };
 
     update_graph(root, None, &mut graph);
 
-    // Render the graph to the dot file
-    dot::render(&graph, &mut file).unwrap()
+    graph
+}
+
+// Generate the diagram file of the component tree
+pub fn generate_tree_diagram(root: &JsonComponent, output_path: &str, format: DiagramFormat) {
+    let mut file = create_output_file(output_path);
+    let graph = build_graph(root);
+
+    match format {
+        DiagramFormat::Dot => dot::render(&graph, &mut file).unwrap(),
+        DiagramFormat::Mermaid => file.write_all(tree_to_mermaid(&graph).as_bytes()).unwrap(),
+        DiagramFormat::PlantUml => file.write_all(tree_to_plantuml(&graph).as_bytes()).unwrap(),
+    }
 }
 
 // Implementation of labeling of nodes and edges inside the dot file
@@ -73,4 +98,343 @@ impl<'a> dot::GraphWalk<'a, Nd<'a>, Ed<'a>> for Graph {
     }
     fn source(&self, e: &Ed<'a>) -> Nd<'a> { e.0 }
     fn target(&self, e: &Ed<'a>) -> Nd<'a> { e.1 }
-}
\ No newline at end of file
+}
+
+/**********************************************************************************
+ * Implementation of how to render the instance netlist of the top component      *
+ **********************************************************************************/
+
+// Instance of a component or a top-level stream of the netlist
+struct NetNode {
+    name: String,
+    // Name of the instantiated component, None for top-level streams
+    component: Option<String>,
+    // JSON key filtered by a key instance
+    key: Option<String>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+// Connection between two streams of the netlist
+struct NetEdge {
+    source: usize,
+    source_stream: String,
+    target: usize,
+    target_stream: String,
+    stream_type: String,
+    // Matcher and key side channel
+    side_channel: bool,
+}
+
+pub struct Netlist { nodes: Vec<NetNode>, edges: Vec<NetEdge> }
+
+// Collect the JSON key of every key instance in the component tree
+fn collect_keys(component: &JsonComponent, keys: &mut HashMap<String, String>) {
+    if let JsonComponent::Key(key) = component {
+        keys.insert(component.get_generatable().get_instance_name(), key.get_key().to_string());
+    }
+
+    for child in component.get_children() {
+        collect_keys(&child, keys);
+    }
+}
+
+fn find_stream_type<'a>(streamlet: &'a TilStreamlet, stream_name: &str) -> Option<&'a StreamTypeDecl> {
+    streamlet.get_streams().get_streams().iter()
+        .find(|stream| stream.get_name() == stream_name)
+        .map(|stream| stream.get_type())
+}
+
+// Build the netlist of the top component from its instances and signals
+pub fn build_netlist(top: &TilStreamlet, streamlets: &[TilStreamlet], root: &JsonComponent) -> Netlist {
+    let mut netlist = Netlist { nodes: Vec::new(), edges: Vec::new() };
+
+    let mut keys = HashMap::new();
+    collect_keys(root, &mut keys);
+
+    // Top-level streams
+    let mut port_ids = HashMap::new();
+    for stream in top.get_streams().get_streams() {
+        port_ids.insert(stream.get_name().to_string(), netlist.nodes.len());
+        netlist.nodes.push(NetNode {
+            name: stream.get_name().to_string(),
+            component: None,
+            key: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        });
+    }
+
+    let implementation = match top.get_implementation() {
+        Some(TilImplementationType::Inline(implementation)) => implementation,
+        _ => return netlist,
+    };
+
+    // Instances with their streams
+    let mut instance_ids = HashMap::new();
+    let mut instance_streamlets = HashMap::new();
+    for instance in implementation.get_instances() {
+        let streamlet = streamlets.iter().find(|streamlet| streamlet.get_name() == instance.get_component_name());
+
+        let (inputs, outputs) = match streamlet {
+            Some(streamlet) => (
+                streamlet.get_streams().get_input_streams().iter().map(|stream| stream.get_name().to_string()).collect(),
+                streamlet.get_streams().get_output_streams().iter().map(|stream| stream.get_name().to_string()).collect(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        if let Some(streamlet) = streamlet {
+            instance_streamlets.insert(instance.get_instance_name().to_string(), streamlet);
+        }
+
+        instance_ids.insert(instance.get_instance_name().to_string(), netlist.nodes.len());
+        netlist.nodes.push(NetNode {
+            name: instance.get_instance_name().to_string(),
+            component: Some(instance.get_component_name().to_string()),
+            key: keys.get(instance.get_instance_name()).cloned(),
+            inputs,
+            outputs,
+        });
+    }
+
+    // Signals between the streams
+    for signal in implementation.get_signals() {
+        let (source, stream_type) = match signal {
+            TilSignal::Input { source_stream_name, .. } => (
+                port_ids.get(source_stream_name),
+                find_stream_type(top, source_stream_name),
+            ),
+            TilSignal::Intermediate { source_inst_name, source_stream_name, .. } => (
+                instance_ids.get(source_inst_name),
+                instance_streamlets.get(source_inst_name).and_then(|streamlet| find_stream_type(streamlet, source_stream_name)),
+            ),
+            TilSignal::Output { source_inst_name, output_stream, .. } => (
+                instance_ids.get(source_inst_name),
+                Some(output_stream.get_type()),
+            ),
+        };
+
+        let target = match signal.get_dest_inst_name() {
+            Some(dest_inst_name) => instance_ids.get(dest_inst_name),
+            None => port_ids.get(signal.get_dest_stream_name()),
+        };
+
+        if let (Some(&source), Some(&target), Some(stream_type)) = (source, target, stream_type) {
+            netlist.edges.push(NetEdge {
+                source,
+                source_stream: signal.get_source_stream_name().to_string(),
+                target,
+                target_stream: signal.get_dest_stream_name().to_string(),
+                stream_type: stream_type.get_resolved_string(),
+                side_channel: matches!(stream_type.get_stream_type(), StreamType::MatcherStr | StreamType::MatcherMatch),
+            });
+        }
+    }
+
+    netlist
+}
+
+// Generate the diagram file of the instance netlist
+pub fn generate_netlist_diagram(netlist: &Netlist, output_path: &str, format: DiagramFormat) {
+    let mut file = create_output_file(output_path);
+
+    match format {
+        DiagramFormat::Dot => dot::render(netlist, &mut file).unwrap(),
+        DiagramFormat::Mermaid => file.write_all(netlist_to_mermaid(netlist).as_bytes()).unwrap(),
+        DiagramFormat::PlantUml => file.write_all(netlist_to_plantuml(netlist).as_bytes()).unwrap(),
+    }
+}
+
+// Implementation of labeling of the instances and signals inside the dot file
+impl<'a> dot::Labeller<'a, usize, &'a NetEdge> for Netlist {
+    fn graph_id(&'a self) -> dot::Id<'a> { dot::Id::new("netlist").unwrap() }
+    fn node_id(&'a self, n: &usize) -> dot::Id<'a> {
+        dot::Id::new(format!("N{}", n)).unwrap()
+    }
+    fn node_shape(&'a self, n: &usize) -> Option<dot::LabelText<'a>> {
+        match self.nodes[*n].component {
+            Some(_) => Some(dot::LabelText::label("plaintext")),
+            None => Some(dot::LabelText::label("ellipse")),
+        }
+    }
+    fn node_label<'b>(&'b self, n: &usize) -> dot::LabelText<'b> {
+        let node = &self.nodes[*n];
+
+        let component = match &node.component {
+            Some(component) => component,
+            None => return dot::LabelText::label(node.name.as_str()),
+        };
+
+        // Table with the instance in the header and its input and output streams below
+        let mut label = String::from("<table border=\"0\" cellborder=\"1\" cellspacing=\"0\">");
+        label.push_str(&format!("<tr><td colspan=\"2\"><b>{}</b><br/>{}</td></tr>", dot::escape_html(&node.name), dot::escape_html(component)));
+
+        if let Some(key) = &node.key {
+            label.push_str(&format!("<tr><td colspan=\"2\">key: \"{}\"</td></tr>", dot::escape_html(key)));
+        }
+
+        let inputs: Vec<String> = node.inputs.iter().map(|stream| dot::escape_html(stream)).collect();
+        let outputs: Vec<String> = node.outputs.iter().map(|stream| dot::escape_html(stream)).collect();
+        label.push_str(&format!("<tr><td align=\"left\">{}</td><td align=\"right\">{}</td></tr>", inputs.join("<br/>"), outputs.join("<br/>")));
+        label.push_str("</table>");
+
+        dot::LabelText::html(label)
+    }
+    fn edge_label<'b>(&'b self, e: &&'a NetEdge) -> dot::LabelText<'b> {
+        dot::LabelText::label(format!("{} -> {}\n{}", e.source_stream, e.target_stream, e.stream_type))
+    }
+    fn edge_style(&'a self, e: &&'a NetEdge) -> dot::Style {
+        if e.side_channel { dot::Style::Dashed } else { dot::Style::None }
+    }
+    fn edge_color(&'a self, e: &&'a NetEdge) -> Option<dot::LabelText<'a>> {
+        if e.side_channel { Some(dot::LabelText::label("blue")) } else { None }
+    }
+}
+
+// Implementation of how to traverse the netlist
+impl<'a> dot::GraphWalk<'a, usize, &'a NetEdge> for Netlist {
+    fn nodes(&'a self) -> dot::Nodes<'a, usize> {
+        (0..self.nodes.len()).collect()
+    }
+    fn edges(&'a self) -> dot::Edges<'a, &'a NetEdge> {
+        self.edges.iter().collect()
+    }
+    fn source(&self, e: &&'a NetEdge) -> usize { e.source }
+    fn target(&self, e: &&'a NetEdge) -> usize { e.target }
+}
+
+/**********************************************************************************
+ * Implementation of how to render the component tree and netlist as Mermaid      *
+ * flowcharts and PlantUML diagrams for embedding in Markdown documents           *
+ **********************************************************************************/
+
+// Mermaid labels cannot contain quotes and are rendered as HTML. Mermaid entities start
+// with #, so # goes first to keep it from starting an entity or escaping the others again.
+fn escape_mermaid(text: &str) -> String {
+    text.replace('#', "#35;")
+        .replace('&', "#amp;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', "<br/>")
+}
+
+// PlantUML labels cannot contain quotes and use \n for line breaks
+fn escape_plantuml(text: &str) -> String {
+    text.replace('"', "'")
+        .replace('\n', "\\n")
+}
+
+fn netlist_node_text(node: &NetNode) -> String {
+    let mut text = node.name.clone();
+
+    if let Some(component) = &node.component {
+        text.push_str(&format!("\n[{}]", component));
+    }
+
+    if let Some(key) = &node.key {
+        text.push_str(&format!("\nkey: \"{}\"", key));
+    }
+
+    if !node.inputs.is_empty() {
+        text.push_str(&format!("\nin: {}", node.inputs.join(", ")));
+    }
+
+    if !node.outputs.is_empty() {
+        text.push_str(&format!("\nout: {}", node.outputs.join(", ")));
+    }
+
+    text
+}
+
+fn netlist_edge_text(edge: &NetEdge) -> String {
+    format!("{} -> {}\n{}", edge.source_stream, edge.target_stream, edge.stream_type)
+}
+
+fn tree_to_mermaid(graph: &Graph) -> String {
+    let mut diagram = String::from("flowchart TD\n");
+
+    for (id, node) in graph.nodes.iter().enumerate() {
+        diagram.push_str(&format!("    N{}[\"{}\"]\n", id, escape_mermaid(node)));
+    }
+
+    for (source, target) in &graph.edges {
+        diagram.push_str(&format!("    N{} --> N{}\n", source, target));
+    }
+
+    diagram
+}
+
+fn tree_to_plantuml(graph: &Graph) -> String {
+    let mut diagram = String::from("@startuml\n");
+
+    for (id, node) in graph.nodes.iter().enumerate() {
+        diagram.push_str(&format!("rectangle \"{}\" as N{}\n", escape_plantuml(node), id));
+    }
+
+    for (source, target) in &graph.edges {
+        diagram.push_str(&format!("N{} --> N{}\n", source, target));
+    }
+
+    diagram.push_str("@enduml\n");
+    diagram
+}
+
+fn netlist_to_mermaid(netlist: &Netlist) -> String {
+    let mut diagram = String::from("flowchart LR\n");
+
+    for (id, node) in netlist.nodes.iter().enumerate() {
+        let text = escape_mermaid(&netlist_node_text(node));
+
+        // Top-level streams are drawn as rounded nodes
+        match node.component {
+            Some(_) => diagram.push_str(&format!("    N{}[\"{}\"]\n", id, text)),
+            None => diagram.push_str(&format!("    N{}([\"{}\"])\n", id, text)),
+        }
+    }
+
+    for edge in &netlist.edges {
+        // Side channels are drawn dotted
+        let arrow = if edge.side_channel { "-.->" } else { "-->" };
+        diagram.push_str(&format!("    N{} {}|\"{}\"| N{}\n", edge.source, arrow, escape_mermaid(&netlist_edge_text(edge)), edge.target));
+    }
+
+    diagram
+}
+
+fn netlist_to_plantuml(netlist: &Netlist) -> String {
+    let mut diagram = String::from("@startuml\nleft to right direction\n");
+
+    for (id, node) in netlist.nodes.iter().enumerate() {
+        let text = escape_plantuml(&netlist_node_text(node));
+
+        // Top-level streams are drawn as interfaces
+        match node.component {
+            Some(_) => diagram.push_str(&format!("component \"{}\" as N{}\n", text, id)),
+            None => diagram.push_str(&format!("interface \"{}\" as N{}\n", text, id)),
+        }
+    }
+
+    for edge in &netlist.edges {
+        // Side channels are drawn dashed
+        let arrow = if edge.side_channel { "..>" } else { "-->" };
+        diagram.push_str(&format!("N{} {} N{} : {}\n", edge.source, arrow, edge.target, escape_plantuml(&netlist_edge_text(edge))));
+    }
+
+    diagram.push_str("@enduml\n");
+    diagram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mermaid_labels_are_escaped() {
+        assert_eq!(escape_mermaid("key \"a&b\"\n<record>"), "key #quot;a#amp;b#quot;<br/>#lt;record#gt;");
+        assert_eq!(escape_mermaid("&quot;"), "#amp;quot;");
+        assert_eq!(escape_mermaid("#quot;"), "#35;quot;");
+        assert_eq!(escape_mermaid("#\""), "#35;#quot;");
+    }
+}
@@ -1,6 +1,6 @@
 mod analysis;
 
-use analysis::Generator;
+use analysis::{Generator, DiagramFormat};
 
 fn main() {
     let _multiple_keys = r#"
@@ -29,15 +29,29 @@ fn main() {
 
     let visualize = true;
 
+    // Optional JSON configuration of the generator, e.g. the complexity of the streams
+    let config = std::env::args().nth(1).map(|path| std::fs::read_to_string(path).unwrap());
+
     // Create a new generator
     let mut generator = Generator::new("schema_parser", 4, 64);
 
     // Analyze the JSON string
     generator.analyze(_multiple_keys).unwrap();
+
+    if let Some(config) = config {
+        generator.configure(&config).unwrap();
+    }
     
     if visualize {
         // Visualize the JSON string
         generator.visualize("output/schema.dot").unwrap();
+
+        // Visualize the netlist of the generated components
+        generator.visualize_netlist("output/netlist.dot").unwrap();
+
+        // Export the diagrams for embedding in Markdown documents
+        generator.visualize_as("output/schema.mmd", DiagramFormat::Mermaid).unwrap();
+        generator.visualize_netlist_as("output/netlist.puml", DiagramFormat::PlantUml).unwrap();
     }
 
     // Generate TIL code